@@ -0,0 +1,2 @@
+delete from muscle_group where id between 1 and 17;
+drop index movement_muscle__movement_id__muscle_group_id__key;
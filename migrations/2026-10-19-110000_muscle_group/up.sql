@@ -0,0 +1,31 @@
+update movement_muscle m set deleted = true
+    where deleted = false and exists (
+        select 1 from movement_muscle d
+        where d.deleted = false 
+            and d.movement_id = m.movement_id 
+            and d.muscle_group_id = m.muscle_group_id 
+            and d.id < m.id
+    );
+
+create unique index movement_muscle__movement_id__muscle_group_id__key
+    on movement_muscle (movement_id, muscle_group_id) where deleted = false;
+
+insert into muscle_group (id, name, description) values
+    (1, 'Abs', null),
+    (2, 'Obliques', null),
+    (3, 'Lower Back', null),
+    (4, 'Lats', null),
+    (5, 'Traps', null),
+    (6, 'Chest', null),
+    (7, 'Shoulders', null),
+    (8, 'Biceps', null),
+    (9, 'Triceps', null),
+    (10, 'Forearms', null),
+    (11, 'Glutes', null),
+    (12, 'Hip Flexors', null),
+    (13, 'Quadriceps', null),
+    (14, 'Hamstrings', null),
+    (15, 'Adductors', null),
+    (16, 'Abductors', null),
+    (17, 'Calves', null)
+    on conflict do nothing;
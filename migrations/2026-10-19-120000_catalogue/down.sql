@@ -0,0 +1 @@
+drop table catalogue_version;
@@ -0,0 +1,4 @@
+create table catalogue_version (
+    version integer primary key check (version >= 1),
+    applied timestamptz not null default now()
+);
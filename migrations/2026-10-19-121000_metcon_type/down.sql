@@ -0,0 +1,15 @@
+alter table metcon_movement drop column metcon_part;
+drop type metcon_part;
+
+alter table metcon
+    drop column rep_scheme,
+    drop column rest_time,
+    drop column work_time;
+
+-- enum values can not be removed, so the type has to be recreated
+delete from metcon where metcon_type in ('tabata', 'intervals');
+delete from metcon_archive where metcon_type in ('tabata', 'intervals');
+alter type metcon_type rename to metcon_type_old;
+create type metcon_type as enum('amrap', 'emom', 'for_time');
+alter table metcon alter column metcon_type type metcon_type using metcon_type::text::metcon_type;
+drop type metcon_type_old;
@@ -0,0 +1,12 @@
+alter type metcon_type add value 'tabata';
+alter type metcon_type add value 'intervals';
+
+create type metcon_part as enum('buy_in', 'main', 'cash_out');
+
+alter table metcon
+    add column work_time integer check (work_time > 0), -- milliseconds
+    add column rest_time integer check (rest_time >= 0), -- milliseconds
+    add column rep_scheme integer[] check (0 < all(rep_scheme));
+
+alter table metcon_movement
+    add column metcon_part metcon_part not null default 'main';
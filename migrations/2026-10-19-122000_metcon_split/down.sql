@@ -0,0 +1,14 @@
+drop table metcon_split_archive;
+drop table metcon_split;
+
+drop trigger delete_metcon_session_archive on metcon_session_archive;
+drop trigger archive_metcon_session on metcon_session;
+
+create trigger archive_metcon_session
+    after insert or update of deleted or delete
+    on metcon_session
+    for each row execute procedure archive_record();
+
+drop function check_metcon_session_exists;
+drop function delete_record_metcon_session;
+drop function archive_record_metcon_session;
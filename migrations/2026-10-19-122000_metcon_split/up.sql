@@ -0,0 +1,96 @@
+create function archive_record_metcon_session()
+    returns trigger as $$
+    begin
+        if (tg_op = 'INSERT' and new.deleted = true) then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using new.id;
+            return old;
+        end if;
+        -- when a soft-delete happens...
+        if (tg_op = 'UPDATE' and new.deleted = true) then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using old.id;
+            return old;
+        end if;
+        -- when a hard-delete or a cascaded delete happens
+        if (tg_op = 'DELETE') then
+            if (old.deleted = false) then 
+                old.deleted := true;
+            end if;
+            execute format('insert into %I.%I select $1.*', tg_table_schema, tg_table_name || '_archive')
+            using old;
+            raise notice 'soft deleting % %', tg_table_name, old.id;
+        end if;
+        return null;
+    exception when foreign_key_violation then 
+        raise notice 'hard deleting % %', tg_table_name, old.id;
+        raise notice 'soft deleting metcon_split where metcon_session_id = %', old.id;
+        execute format('delete from %I.metcon_split where metcon_session_id = $1', tg_table_schema) using old.id;
+        return null;
+    end;
+    $$ language plpgsql;
+
+create function delete_record_metcon_session()
+    returns trigger as $$
+    begin
+        raise notice 'hard deleting metcon_split_archive where metcon_session_id = %', old.id;
+        execute format('delete from %I.metcon_split_archive where metcon_session_id = $1', tg_table_schema) using old.id;
+        return null;
+    end;
+    $$ language plpgsql;
+
+create function check_metcon_session_exists()
+    returns trigger as $$
+    declare 
+        parent integer;
+    begin
+        execute format('select count(*) from metcon_session where id = $1') into parent using new.metcon_session_id;
+        if parent = 0 then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using new.id;
+            raise notice 'hard deleting % %', tg_table_name, new.id;
+        end if;
+        return null;
+    end;
+    $$ language plpgsql;
+
+drop trigger archive_metcon_session on metcon_session;
+
+create trigger archive_metcon_session
+    after insert or update of deleted or delete
+    on metcon_session
+    for each row execute procedure archive_record_metcon_session();
+
+create trigger delete_metcon_session_archive
+    after delete
+    on metcon_session_archive
+    for each row execute procedure delete_record_metcon_session();
+
+create table metcon_split (
+    id bigint primary key,
+    metcon_session_id bigint not null references metcon_session on delete cascade,
+    metcon_movement_id bigint references metcon_movement on delete set null, -- only for chippers
+    split_number integer not null check (split_number >= 0),
+    time integer not null check (time > 0), -- milliseconds
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create unique index metcon_split__metcon_session_id__split_number__key
+    on metcon_split (metcon_session_id, split_number)
+    where deleted = false;
+
+create trigger set_timestamp before update on metcon_split
+    for each row execute procedure trigger_set_timestamp();
+
+create table metcon_split_archive (
+    primary key (id),
+    check (deleted = true)
+) inherits (metcon_split);
+
+create trigger archive_metcon_split
+    after insert or update of deleted or delete
+    on metcon_split
+    for each row execute procedure archive_record();
+
+create trigger check_metcon_session_exists_trigger
+    after insert 
+    on metcon_split_archive
+    for each row execute procedure check_metcon_session_exists();
@@ -0,0 +1,5 @@
+alter table strength_set
+    drop column rest,
+    drop column tempo,
+    drop column rir,
+    drop column rpe;
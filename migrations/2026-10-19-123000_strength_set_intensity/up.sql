@@ -0,0 +1,6 @@
+alter table strength_set
+    add column rpe real check (rpe >= 1 and rpe <= 10),
+    add column rir integer check (rir >= 0),
+    add column tempo varchar(4) check (tempo ~ '^[0-9X]{4}$'),
+    add column rest integer check (rest >= 0), -- milliseconds
+    add check (rpe is null or rir is null);
@@ -0,0 +1,7 @@
+alter table strength_session
+    drop column superset_position,
+    drop column strength_superset_id;
+
+drop table strength_superset_archive;
+drop table strength_superset;
+drop type strength_superset_type;
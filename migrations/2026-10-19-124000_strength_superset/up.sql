@@ -0,0 +1,37 @@
+create type strength_superset_type as enum('superset', 'complex');
+
+create table strength_superset (
+    id bigint primary key,
+    user_id bigint not null references "user" on delete cascade,
+    datetime timestamptz not null default now(),
+    strength_superset_type strength_superset_type not null,
+    comments text,
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create index strength_superset__user_id__last_change__idx
+    on strength_superset (user_id, last_change) 
+    where deleted = false;
+
+create trigger set_timestamp before update on strength_superset
+    for each row execute procedure trigger_set_timestamp();
+
+create table strength_superset_archive (
+    primary key (id),
+    foreign key (user_id) references "user" on delete cascade,
+    check (deleted = true)
+) inherits (strength_superset);
+
+create trigger archive_strength_superset
+    after insert or update of deleted or delete
+    on strength_superset
+    for each row execute procedure archive_record();
+
+alter table strength_session
+    add column strength_superset_id bigint references strength_superset on delete set null,
+    add column superset_position integer check (superset_position >= 0);
+
+create unique index strength_session__strength_superset_id__superset_position__key
+    on strength_session (strength_superset_id, superset_position) 
+    where deleted = false;
@@ -0,0 +1,10 @@
+drop table planned_session_archive;
+drop table planned_session;
+drop table training_plan_entry_archive;
+drop table training_plan_entry;
+drop table training_plan_archive;
+drop table training_plan;
+drop type planned_type;
+drop function check_training_plan_exists;
+drop function delete_record_training_plan;
+drop function archive_record_training_plan;
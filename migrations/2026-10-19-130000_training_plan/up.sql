@@ -0,0 +1,167 @@
+create function archive_record_training_plan()
+    returns trigger as $$
+    begin
+        if (tg_op = 'INSERT' and new.deleted = true) then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using new.id;
+            return old;
+        end if;
+        -- when a soft-delete happens...
+        if (tg_op = 'UPDATE' and new.deleted = true) then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using old.id;
+            return old;
+        end if;
+        -- when a hard-delete or a cascaded delete happens
+        if (tg_op = 'DELETE') then
+            if (old.deleted = false) then 
+                old.deleted := true;
+            end if;
+            execute format('insert into %I.%I select $1.*', tg_table_schema, tg_table_name || '_archive')
+            using old;
+            raise notice 'soft deleting % %', tg_table_name, old.id;
+        end if;
+        return null;
+    exception when foreign_key_violation then 
+        raise notice 'hard deleting % %', tg_table_name, old.id;
+        raise notice 'soft deleting training_plan_entry where training_plan_id = %', old.id;
+        execute format('delete from %I.training_plan_entry where training_plan_id = $1', tg_table_schema) using old.id;
+        return null;
+    end;
+    $$ language plpgsql;
+
+create function delete_record_training_plan()
+    returns trigger as $$
+    begin
+        raise notice 'hard deleting training_plan_entry_archive where training_plan_id = %', old.id;
+        execute format('delete from %I.training_plan_entry_archive where training_plan_id = $1', tg_table_schema) using old.id;
+        return null;
+    end;
+    $$ language plpgsql;
+
+create function check_training_plan_exists()
+    returns trigger as $$
+    declare 
+        parent integer;
+    begin
+        execute format('select count(*) from training_plan where id = $1') into parent using new.training_plan_id;
+        if parent = 0 then
+            execute format('delete from %I.%I where id = $1', tg_table_schema, tg_table_name) using new.id;
+            raise notice 'hard deleting % %', tg_table_name, new.id;
+        end if;
+        return null;
+    end;
+    $$ language plpgsql;
+
+create type planned_type as enum('strength', 'metcon', 'cardio');
+
+create table training_plan (
+    id bigint primary key,
+    user_id bigint not null references "user" on delete cascade,
+    name varchar(80) not null check (length(name) >= 2),
+    description text,
+    weeks integer not null check (weeks between 1 and 520),
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create unique index training_plan__user_id__name__key
+    on training_plan (user_id, name) 
+    where deleted = false;
+
+create index training_plan__user_id__last_change__idx
+    on training_plan (user_id, last_change) 
+    where deleted = false;
+
+create trigger set_timestamp before update on training_plan
+    for each row execute procedure trigger_set_timestamp();
+
+create table training_plan_archive (
+    primary key (id),
+    foreign key (user_id) references "user" on delete cascade,
+    check (deleted = true)
+) inherits (training_plan);
+
+create trigger archive_training_plan
+    after insert or update of deleted or delete
+    on training_plan
+    for each row execute procedure archive_record_training_plan();
+
+create trigger delete_training_plan_archive
+    after delete
+    on training_plan_archive
+    for each row execute procedure delete_record_training_plan();
+
+create table training_plan_entry (
+    id bigint primary key,
+    training_plan_id bigint not null references training_plan on delete cascade,
+    week integer not null check (week >= 0),
+    day integer not null check (day >= 0 and day <= 6),
+    entry_number integer not null check (entry_number >= 0),
+    planned_type planned_type not null,
+    movement_id bigint references movement on delete cascade,
+    metcon_id bigint references metcon on delete cascade,
+    sets integer check (sets >= 1),
+    count integer check (count >= 1),
+    percentage real check (percentage > 0),
+    distance integer check (distance > 0), -- meter
+    time integer check (time > 0), -- milliseconds
+    description text,
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create unique index training_plan_entry__training_plan_id__week__day__entry_number__key
+    on training_plan_entry (training_plan_id, week, day, entry_number) 
+    where deleted = false;
+
+create trigger set_timestamp before update on training_plan_entry
+    for each row execute procedure trigger_set_timestamp();
+
+create table training_plan_entry_archive (
+    primary key (id),
+    check (deleted = true)
+) inherits (training_plan_entry);
+
+create trigger archive_training_plan_entry
+    after insert or update of deleted or delete
+    on training_plan_entry
+    for each row execute procedure archive_record();
+
+create trigger check_training_plan_exists_trigger
+    after insert 
+    on training_plan_entry_archive
+    for each row execute procedure check_training_plan_exists();
+
+create table planned_session (
+    id bigint primary key,
+    user_id bigint not null references "user" on delete cascade,
+    training_plan_entry_id bigint not null references training_plan_entry on delete cascade,
+    date date not null,
+    weight real check (weight > 0),
+    strength_session_id bigint references strength_session on delete set null,
+    metcon_session_id bigint references metcon_session on delete set null,
+    cardio_session_id bigint references cardio_session on delete set null,
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create unique index planned_session__training_plan_entry_id__date__key
+    on planned_session (training_plan_entry_id, date) 
+    where deleted = false;
+
+create index planned_session__user_id__last_change__idx
+    on planned_session (user_id, last_change) 
+    where deleted = false;
+
+create trigger set_timestamp before update on planned_session
+    for each row execute procedure trigger_set_timestamp();
+
+create table planned_session_archive (
+    primary key (id),
+    foreign key (user_id) references "user" on delete cascade,
+    check (deleted = true)
+) inherits (planned_session);
+
+create trigger archive_planned_session
+    after insert or update of deleted or delete
+    on planned_session
+    for each row execute procedure archive_record();
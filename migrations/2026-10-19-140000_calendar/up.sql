@@ -0,0 +1,10 @@
+create table calendar_token (
+    user_id bigint primary key references "user" on delete cascade,
+    token varchar(64) not null check (length(token) = 64),
+    last_change timestamptz not null default now()
+);
+
+create unique index calendar_token__token__key on calendar_token (token);
+
+create trigger set_timestamp before update on calendar_token
+    for each row execute procedure trigger_set_timestamp();
@@ -0,0 +1,5 @@
+alter table action_rule
+    drop column exclusion_dates,
+    drop column week_interval,
+    drop column additional_weekdays,
+    drop column time_zone;
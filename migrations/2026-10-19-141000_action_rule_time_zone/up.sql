@@ -0,0 +1,5 @@
+alter table action_rule
+    add column time_zone varchar(64) not null default 'UTC',
+    add column additional_weekdays weekday[] not null default '{}',
+    add column week_interval integer not null default 1 check (week_interval >= 1),
+    add column exclusion_dates date[] not null default '{}';
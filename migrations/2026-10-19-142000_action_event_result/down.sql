@@ -0,0 +1,3 @@
+drop table action_event_result_archive;
+drop table action_event_result;
+drop type action_event_status;
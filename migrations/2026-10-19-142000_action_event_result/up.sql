@@ -0,0 +1,32 @@
+create type action_event_status as enum('success', 'user_error', 'transient_error');
+
+create table action_event_result (
+    id bigint primary key,
+    user_id bigint not null references "user" on delete cascade,
+    action_id bigint not null references action on delete cascade,
+    action_event_id bigint not null, -- no foreign key because action events are deleted soon after their execution
+    action_event_datetime timestamptz not null,
+    status action_event_status not null,
+    message text,
+    datetime timestamptz not null,
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create index action_event_result__user_id__last_change__idx
+    on action_event_result (user_id, last_change)
+    where deleted = false;
+
+create trigger set_timestamp before update on action_event_result
+    for each row execute procedure trigger_set_timestamp();
+
+create table action_event_result_archive (
+    primary key (id),
+    foreign key (user_id) references "user" on delete cascade,
+    check (deleted = true)
+) inherits (action_event_result);
+
+create trigger archive_action_event_result
+    after insert or update of deleted or delete
+    on action_event_result
+    for each row execute procedure archive_record();
@@ -0,0 +1,5 @@
+alter table action_event
+    drop column retry_after,
+    drop column attempts;
+
+alter table action drop column max_attempts;
@@ -0,0 +1,6 @@
+alter table action
+    add column max_attempts integer not null default 3 check (max_attempts >= 1);
+
+alter table action_event
+    add column attempts integer not null default 0 check (attempts >= 0),
+    add column retry_after timestamptz;
@@ -0,0 +1,14 @@
+create table action_provider_heartbeat (
+    action_provider_id bigint primary key references action_provider on delete cascade,
+    version varchar(80) not null,
+    datetime timestamptz not null,
+    run_interval integer not null check (run_interval > 0), -- milliseconds
+    action_events integer not null check (action_events >= 0),
+    successes integer not null check (successes >= 0),
+    user_errors integer not null check (user_errors >= 0),
+    transient_errors integer not null check (transient_errors >= 0),
+    last_change timestamptz not null default now()
+);
+
+create trigger set_timestamp before update on action_provider_heartbeat
+    for each row execute procedure trigger_set_timestamp();
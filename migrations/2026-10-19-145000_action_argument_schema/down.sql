@@ -0,0 +1 @@
+alter table action drop column argument_schema;
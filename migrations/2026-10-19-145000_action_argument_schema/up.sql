@@ -0,0 +1 @@
+alter table action add column argument_schema text; -- json schema
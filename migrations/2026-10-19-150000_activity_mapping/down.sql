@@ -0,0 +1,2 @@
+drop table activity_mapping_archive;
+drop table activity_mapping;
@@ -0,0 +1,31 @@
+create table activity_mapping (
+    id bigint primary key,
+    user_id bigint not null references "user" on delete cascade,
+    platform_id bigint not null references platform on delete cascade,
+    activity varchar(80) not null check (length(activity) >= 1),
+    movement_id bigint references movement on delete set null, -- null if the activity is not mapped yet
+    last_change timestamptz not null default now(),
+    deleted boolean not null default false
+);
+
+create unique index activity_mapping__user_id__platform_id__activity__key
+    on activity_mapping (user_id, platform_id, activity) 
+    where deleted = false;
+
+create index activity_mapping__user_id__last_change__idx
+    on activity_mapping (user_id, last_change) 
+    where deleted = false;
+
+create trigger set_timestamp before update on activity_mapping
+    for each row execute procedure trigger_set_timestamp();
+
+create table activity_mapping_archive (
+    primary key (id),
+    foreign key (user_id) references "user" on delete cascade,
+    check (deleted = true)
+) inherits (activity_mapping);
+
+create trigger archive_activity_mapping
+    after insert or update of deleted or delete
+    on activity_mapping
+    for each row execute procedure archive_record();
@@ -0,0 +1 @@
+alter table action_event drop column time_zone;
@@ -0,0 +1,2 @@
+alter table action_event
+    add column time_zone varchar(64) not null default 'UTC';
@@ -0,0 +1,235 @@
+//! Activities parsed from GPX, TCX and FIT files.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sport_log_ap_utils::{average_rate, rate_samples_to_times};
+use sport_log_types::{CardioSession, CardioSessionId, CardioType, MovementId, Position, UserId};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("{0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl ParseError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+/// A recorded activity with at least one point.
+#[derive(Debug)]
+pub struct Activity {
+    /// The key of the activity in [`ActivityMapping`](sport_log_types::ActivityMapping).
+    pub activity: String,
+    /// The points ordered by time.
+    pub points: Vec<Point>,
+    pub calories: Option<i32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Point {
+    pub time: DateTime<Utc>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Elevation in meters.
+    pub elevation: Option<f64>,
+    /// Distance since the start in meters.
+    pub distance: Option<f64>,
+    /// Beats per minute.
+    pub heart_rate: Option<u32>,
+    /// Steps or revolutions per minute.
+    pub cadence: Option<u32>,
+}
+
+impl Activity {
+    pub fn new(
+        activity: String,
+        mut points: Vec<Point>,
+        calories: Option<i32>,
+    ) -> Result<Self, ParseError> {
+        if points.is_empty() {
+            return Err(ParseError::invalid("file contains no track points"));
+        }
+        points.sort_by_key(|point| point.time);
+
+        Ok(Self {
+            activity,
+            points,
+            calories,
+        })
+    }
+
+    pub fn into_cardio_session(self, user_id: UserId, movement_id: MovementId) -> CardioSession {
+        let start = self.points[0].time;
+        let millis_since_start = |point: &Point| (point.time - start).num_milliseconds() as i32;
+        let time = millis_since_start(&self.points[self.points.len() - 1]);
+
+        let mut track = vec![];
+        let mut distance = 0.;
+        let mut last_position: Option<(f64, f64)> = None;
+        for point in &self.points {
+            let position = point.latitude.zip(point.longitude);
+            if let (Some(last_position), Some(position)) = (last_position, position) {
+                distance += haversine_distance(last_position, position);
+            }
+            // a distance recorded by the device is more accurate than the one calculated from the track
+            if let Some(point_distance) = point.distance {
+                distance = point_distance;
+            }
+            if let Some((latitude, longitude)) = position {
+                last_position = position;
+                track.push(Position {
+                    longitude,
+                    latitude,
+                    elevation: point.elevation.unwrap_or(0.),
+                    distance,
+                    time: millis_since_start(point),
+                });
+            }
+        }
+
+        let mut ascent = 0.;
+        let mut descent = 0.;
+        let elevations: Vec<f64> = self.points.iter().filter_map(|p| p.elevation).collect();
+        for elevations in elevations.windows(2) {
+            let difference = elevations[1] - elevations[0];
+            if difference > 0. {
+                ascent += difference;
+            } else {
+                descent -= difference;
+            }
+        }
+        let has_elevation = !elevations.is_empty();
+
+        let heart_rate: Vec<(i32, u32)> = self
+            .points
+            .iter()
+            .filter_map(|point| Some((millis_since_start(point), point.heart_rate?)))
+            .collect();
+        let heart_rate = rate_samples_to_times(&heart_rate, time);
+        let avg_heart_rate = average_rate(&heart_rate, time);
+
+        let cadence: Vec<(i32, u32)> = self
+            .points
+            .iter()
+            .filter_map(|point| Some((millis_since_start(point), point.cadence?)))
+            .collect();
+        let cadence = rate_samples_to_times(&cadence, time);
+        let avg_cadence = average_rate(&cadence, time);
+
+        CardioSession {
+            id: CardioSessionId(rand::thread_rng().gen()),
+            user_id,
+            movement_id,
+            cardio_type: CardioType::Training,
+            datetime: start,
+            distance: (distance > 0.).then_some(distance as i32),
+            ascent: has_elevation.then_some(ascent as i32),
+            descent: has_elevation.then_some(descent as i32),
+            time: Some(time),
+            calories: self.calories,
+            track: (!track.is_empty()).then_some(track),
+            avg_cadence,
+            cadence: (!cadence.is_empty()).then_some(cadence),
+            avg_heart_rate,
+            heart_rate: (!heart_rate.is_empty()).then_some(heart_rate),
+            route_id: None,
+            comments: None,
+            deleted: false,
+        }
+    }
+}
+
+pub fn parse_time(time: &str) -> Result<DateTime<Utc>, ParseError> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.to_utc())
+        .map_err(|error| ParseError::invalid(format!("invalid time {time}: {error}")))
+}
+
+pub fn parse_number<T: FromStr>(value: Option<&str>, name: &str) -> Result<T, ParseError> {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| ParseError::invalid(format!("missing or invalid {name}")))
+}
+
+pub fn optional_number<T: FromStr>(
+    value: Option<&str>,
+    name: &str,
+) -> Result<Option<T>, ParseError> {
+    value
+        .map(|value| parse_number(Some(value), name))
+        .transpose()
+}
+
+/// The distance in meters between two positions given as latitude and longitude in degrees.
+fn haversine_distance(
+    (latitude1, longitude1): (f64, f64),
+    (latitude2, longitude2): (f64, f64),
+) -> f64 {
+    const EARTH_RADIUS: f64 = 6_371_000.;
+
+    let delta_latitude = (latitude2 - latitude1).to_radians();
+    let delta_longitude = (longitude2 - longitude1).to_radians();
+    let a = (delta_latitude / 2.).sin().powi(2)
+        + latitude1.to_radians().cos()
+            * latitude2.to_radians().cos()
+            * (delta_longitude / 2.).sin().powi(2);
+    2. * EARTH_RADIUS * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn activity_into_cardio_session() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let point = |seconds: i64, latitude: f64, elevation: f64| Point {
+            time: start + Duration::try_seconds(seconds).unwrap(),
+            latitude: Some(latitude),
+            longitude: Some(11.),
+            elevation: Some(elevation),
+            heart_rate: Some(120),
+            ..Point::default()
+        };
+        // 0.001° of latitude are about 111 m
+        let activity = Activity::new(
+            "running".to_owned(),
+            vec![
+                point(60, 47.001, 510.),
+                point(0, 47., 500.),
+                point(120, 47.002, 505.),
+            ],
+            Some(42),
+        )
+        .unwrap();
+
+        let cardio_session = activity.into_cardio_session(UserId(1), MovementId(2));
+        assert_eq!(cardio_session.datetime, start);
+        assert_eq!(cardio_session.time, Some(120_000));
+        assert_eq!(cardio_session.distance, Some(222));
+        assert_eq!(cardio_session.ascent, Some(10));
+        assert_eq!(cardio_session.descent, Some(5));
+        assert_eq!(cardio_session.calories, Some(42));
+        assert_eq!(cardio_session.avg_heart_rate, Some(120));
+        assert_eq!(cardio_session.heart_rate.unwrap().len(), 240);
+        assert_eq!(cardio_session.avg_cadence, None);
+        assert_eq!(cardio_session.cadence, None);
+        let track = cardio_session.track.unwrap();
+        assert_eq!(track.len(), 3);
+        assert_eq!(track[1].time, 60_000);
+        assert_eq!(track[1].distance as i32, 111);
+
+        assert!(Activity::new("running".to_owned(), vec![], None).is_err());
+    }
+}
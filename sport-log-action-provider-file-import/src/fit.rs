@@ -0,0 +1,352 @@
+//! Minimal decoder for FIT files.
+//!
+//! Only `record` and `session` messages are decoded.
+//! Array fields, developer fields and the CRC are skipped.
+//! The activity is the `sport` of the session, e.g. `running` or `cycling`, or the sport number if it is unknown.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::activity::{Activity, ParseError, Point};
+
+/// 1989-12-31T00:00:00Z in seconds since the unix epoch.
+const FIT_EPOCH: i64 = 631_065_600;
+
+const SESSION: u16 = 18;
+const RECORD: u16 = 20;
+
+const TIMESTAMP: u8 = 253;
+
+const SPORTS: [&str; 20] = [
+    "generic",
+    "running",
+    "cycling",
+    "transition",
+    "fitness_equipment",
+    "swimming",
+    "basketball",
+    "soccer",
+    "tennis",
+    "american_football",
+    "training",
+    "walking",
+    "cross_country_skiing",
+    "alpine_skiing",
+    "snowboarding",
+    "rowing",
+    "mountaineering",
+    "hiking",
+    "multisport",
+    "paddling",
+];
+
+#[derive(Debug)]
+struct Definition {
+    global_message_number: u16,
+    big_endian: bool,
+    fields: Vec<FieldDefinition>,
+    developer_data_size: usize,
+}
+
+#[derive(Debug)]
+struct FieldDefinition {
+    number: u8,
+    size: usize,
+    base_type: u8,
+}
+
+#[derive(Debug)]
+struct Message {
+    global_message_number: u16,
+    fields: Vec<(u8, Option<i64>)>,
+}
+
+impl Message {
+    fn field(&self, number: u8) -> Option<i64> {
+        self.fields
+            .iter()
+            .find(|(field_number, _)| *field_number == number)
+            .and_then(|(_, value)| *value)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], ParseError> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + count)
+            .ok_or_else(|| ParseError::invalid("unexpected end of FIT file"))?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn read_definition(&mut self, developer_data: bool) -> Result<Definition, ParseError> {
+        let _reserved = self.u8()?;
+        let big_endian = self.u8()? == 1;
+        let global_message_number = self.bytes(2)?;
+        let global_message_number = if big_endian {
+            u16::from_be_bytes([global_message_number[0], global_message_number[1]])
+        } else {
+            u16::from_le_bytes([global_message_number[0], global_message_number[1]])
+        };
+
+        let field_count = self.u8()?;
+        let mut fields = vec![];
+        for _ in 0..field_count {
+            let field = self.bytes(3)?;
+            fields.push(FieldDefinition {
+                number: field[0],
+                size: usize::from(field[1]),
+                base_type: field[2],
+            });
+        }
+
+        let mut developer_data_size = 0;
+        if developer_data {
+            let developer_field_count = self.u8()?;
+            for _ in 0..developer_field_count {
+                developer_data_size += usize::from(self.bytes(3)?[1]);
+            }
+        }
+
+        Ok(Definition {
+            global_message_number,
+            big_endian,
+            fields,
+            developer_data_size,
+        })
+    }
+
+    fn read_message(&mut self, definition: &Definition) -> Result<Message, ParseError> {
+        let mut fields = vec![];
+        for field in &definition.fields {
+            let bytes = self.bytes(field.size)?;
+            fields.push((
+                field.number,
+                decode(bytes, field.base_type, definition.big_endian),
+            ));
+        }
+        self.bytes(definition.developer_data_size)?;
+
+        Ok(Message {
+            global_message_number: definition.global_message_number,
+            fields,
+        })
+    }
+}
+
+/// Decode an integer field.
+///
+/// Returns `None` for invalid values and for all other types.
+fn decode(bytes: &[u8], base_type: u8, big_endian: bool) -> Option<i64> {
+    let (size, signed, zero_invalid) = match base_type & 0x1F {
+        0 | 2 | 13 => (1, false, false),
+        1 => (1, true, false),
+        10 => (1, false, true),
+        3 => (2, true, false),
+        4 => (2, false, false),
+        11 => (2, false, true),
+        5 => (4, true, false),
+        6 => (4, false, false),
+        12 => (4, false, true),
+        14 => (8, true, false),
+        15 => (8, false, false),
+        16 => (8, false, true),
+        _ => return None,
+    };
+    if bytes.len() != size {
+        return None;
+    }
+
+    let mut raw = 0u64;
+    if big_endian {
+        for byte in bytes {
+            raw = raw << 8 | u64::from(*byte);
+        }
+    } else {
+        for byte in bytes.iter().rev() {
+            raw = raw << 8 | u64::from(*byte);
+        }
+    }
+
+    let bits = size as u32 * 8;
+    if signed {
+        if raw == u64::MAX >> (65 - bits) {
+            return None;
+        }
+        // sign extend
+        let shift = 64 - bits;
+        Some(((raw << shift) as i64) >> shift)
+    } else if (zero_invalid && raw == 0) || (!zero_invalid && raw == u64::MAX >> (64 - bits)) {
+        None
+    } else {
+        Some(raw as i64)
+    }
+}
+
+fn datetime(timestamp: u32) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(FIT_EPOCH + i64::from(timestamp), 0)
+}
+
+fn semicircles_to_degrees(semicircles: i64) -> f64 {
+    semicircles as f64 * (180. / 2_147_483_648.)
+}
+
+pub fn parse(data: &[u8]) -> Result<Activity, ParseError> {
+    if data.len() < 12 || &data[8..12] != b".FIT" {
+        return Err(ParseError::invalid("not a FIT file"));
+    }
+    let header_size = usize::from(data[0]);
+    let data_size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let records = data
+        .get(header_size..header_size + data_size)
+        .ok_or_else(|| ParseError::invalid("unexpected end of FIT file"))?;
+
+    let mut reader = Reader {
+        data: records,
+        position: 0,
+    };
+    let mut definitions: HashMap<u8, Definition> = HashMap::new();
+    let mut last_timestamp: Option<u32> = None;
+    let mut sport = None;
+    let mut calories = None;
+    let mut points = vec![];
+
+    while !reader.is_empty() {
+        let header = reader.u8()?;
+        if header & 0x80 == 0 && header & 0x40 != 0 {
+            definitions.insert(header & 0x0F, reader.read_definition(header & 0x20 != 0)?);
+            continue;
+        }
+
+        let (local_message_type, compressed_timestamp) = if header & 0x80 == 0 {
+            (header & 0x0F, None)
+        } else {
+            // compressed timestamp header containing the lowest 5 bits of the timestamp
+            let offset = u32::from(header & 0x1F);
+            let last_timestamp = last_timestamp
+                .ok_or_else(|| ParseError::invalid("compressed timestamp without timestamp"))?;
+            let mut timestamp = (last_timestamp & !0x1F) + offset;
+            if offset < last_timestamp & 0x1F {
+                timestamp += 0x20;
+            }
+            ((header >> 5) & 0x03, Some(timestamp))
+        };
+        let definition = definitions
+            .get(&local_message_type)
+            .ok_or_else(|| ParseError::invalid("message without definition"))?;
+        let message = reader.read_message(definition)?;
+
+        let timestamp = message
+            .field(TIMESTAMP)
+            .map(|timestamp| timestamp as u32)
+            .or(compressed_timestamp);
+        if timestamp.is_some() {
+            last_timestamp = timestamp;
+        }
+
+        match message.global_message_number {
+            RECORD => {
+                let Some(time) = timestamp.and_then(datetime) else {
+                    continue;
+                };
+                points.push(Point {
+                    time,
+                    latitude: message.field(0).map(semicircles_to_degrees),
+                    longitude: message.field(1).map(semicircles_to_degrees),
+                    // enhanced altitude or altitude with scale 5 and offset 500
+                    elevation: message
+                        .field(78)
+                        .or_else(|| message.field(2))
+                        .map(|altitude| altitude as f64 / 5. - 500.),
+                    distance: message.field(5).map(|distance| distance as f64 / 100.),
+                    heart_rate: message.field(3).map(|heart_rate| heart_rate as u32),
+                    cadence: message.field(4).map(|cadence| cadence as u32),
+                });
+            }
+            SESSION => {
+                sport = message.field(5);
+                calories = message.field(11).map(|calories| calories as i32);
+            }
+            _ => {}
+        }
+    }
+
+    let activity = match sport {
+        Some(sport) => SPORTS
+            .get(sport as usize)
+            .map_or_else(|| sport.to_string(), |sport| (*sport).to_owned()),
+        None => "generic".to_owned(),
+    };
+
+    Activity::new(activity, points, calories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::parse_time;
+
+    #[test]
+    fn parse_fit() {
+        let activity = parse(include_bytes!("../fixtures/activity.fit")).unwrap();
+        assert_eq!(activity.activity, "running");
+        assert_eq!(activity.calories, Some(30));
+        assert_eq!(activity.points.len(), 3);
+
+        let start = parse_time("2026-10-18T09:00:00Z").unwrap();
+        let times: Vec<i64> = activity
+            .points
+            .iter()
+            .map(|point| (point.time - start).num_seconds())
+            .collect();
+        assert_eq!(times, [0, 10, 40]);
+
+        let point = &activity.points[0];
+        assert!((point.latitude.unwrap() - 47.).abs() < 1e-6);
+        assert!((point.longitude.unwrap() - 11.).abs() < 1e-6);
+        assert_eq!(point.elevation, Some(500.));
+        assert_eq!(point.distance, Some(0.));
+        assert_eq!(point.heart_rate, Some(120));
+        assert_eq!(point.cadence, Some(80));
+
+        // big endian record with altitude instead of enhanced altitude and invalid heart rate
+        let point = &activity.points[1];
+        assert!((point.latitude.unwrap() - 47.001).abs() < 1e-6);
+        assert_eq!(point.elevation, Some(510.));
+        assert_eq!(point.distance, Some(111.));
+        assert_eq!(point.heart_rate, None);
+        assert_eq!(point.cadence, Some(85));
+
+        assert!(parse(b"not a fit file").is_err());
+        let data = include_bytes!("../fixtures/activity.fit");
+        assert!(parse(&data[..data.len() - 10]).is_err());
+    }
+
+    #[test]
+    fn decode_fields() {
+        assert_eq!(decode(&[0xFF], 2, false), None);
+        assert_eq!(decode(&[0x00], 10, false), None);
+        assert_eq!(decode(&[0xFE], 1, false), Some(-2));
+        assert_eq!(decode(&[0x7F], 1, false), None);
+        assert_eq!(decode(&[0x01, 0x02], 0x84, false), Some(0x0201));
+        assert_eq!(decode(&[0x01, 0x02], 0x84, true), Some(0x0102));
+        assert_eq!(decode(&[0xFF, 0xFF, 0xFF, 0xFE], 0x85, true), Some(-2));
+        assert_eq!(decode(&[0x01, 0x02], 0x02, false), None);
+        assert_eq!(decode(&[0x00; 4], 0x88, false), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! Parser for GPX files.
+//!
+//! Heart rate and cadence are read from the Garmin `TrackPointExtension`.
+//! The activity is the `type` of the first track or `gpx` if the track has no type.
+
+use roxmltree::{Document, Node};
+
+use crate::activity::{optional_number, parse_number, parse_time, Activity, ParseError, Point};
+
+pub fn parse(content: &str) -> Result<Activity, ParseError> {
+    let document = Document::parse(content)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "gpx" {
+        return Err(ParseError::invalid("root element is not gpx"));
+    }
+
+    let activity = root
+        .children()
+        .find(|node| node.has_tag_name("trk"))
+        .and_then(|track| child_text(track, "type"))
+        .map_or_else(|| "gpx".to_owned(), str::to_lowercase);
+
+    let points = root
+        .descendants()
+        .filter(|node| node.has_tag_name("trkpt"))
+        .map(parse_point)
+        .collect::<Result<_, _>>()?;
+
+    Activity::new(activity, points, None)
+}
+
+fn parse_point(node: Node) -> Result<Point, ParseError> {
+    let time = child_text(node, "time").ok_or_else(|| ParseError::invalid("trkpt without time"))?;
+
+    Ok(Point {
+        time: parse_time(time)?,
+        latitude: Some(parse_number(node.attribute("lat"), "lat")?),
+        longitude: Some(parse_number(node.attribute("lon"), "lon")?),
+        elevation: optional_number(child_text(node, "ele"), "ele")?,
+        distance: None,
+        heart_rate: optional_number(extension_text(node, "hr"), "hr")?,
+        cadence: optional_number(extension_text(node, "cad"), "cad")?,
+    })
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+fn extension_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.tag_name().name() == "extensions")?
+        .descendants()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_gpx() {
+        let activity = parse(include_str!("../fixtures/activity.gpx")).unwrap();
+        assert_eq!(activity.activity, "running");
+        assert_eq!(activity.points.len(), 3);
+        assert_eq!(
+            activity.points[0],
+            Point {
+                time: parse_time("2026-10-18T07:00:00Z").unwrap(),
+                latitude: Some(47.),
+                longitude: Some(11.),
+                elevation: Some(500.),
+                distance: None,
+                heart_rate: Some(120),
+                cadence: Some(80),
+            }
+        );
+        assert_eq!(activity.points[2].heart_rate, None);
+
+        assert!(parse("<gpx><trk><trkseg><trkpt lat=\"47\"/></trkseg></trk></gpx>").is_err());
+        assert!(parse("<gpx><trk></trk></gpx>").is_err());
+        assert!(parse("<gpx>").is_err());
+    }
+}
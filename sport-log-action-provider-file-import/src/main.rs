@@ -0,0 +1,691 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    result::Result as StdResult,
+};
+
+use chrono::Duration;
+use reqwest::{Client, Error as ReqwestError, StatusCode};
+use serde::Deserialize;
+use sport_log_ap_utils::{
+    create_cardio_session, init_tracing, run, ActivityMapper, Config, EventResult, NoArgs, Provider,
+};
+use sport_log_types::{
+    uri::{route_max_version, AP_ACTION_PROVIDER, STRENGTH_SESSION, STRENGTH_SET},
+    ActionProvider, ExecutableActionEvent, PlatformId, StrengthSession, UserId, ID_HEADER,
+};
+use thiserror::Error;
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+use crate::activity::ParseError;
+
+mod activity;
+mod fit;
+mod gpx;
+mod strength;
+mod tcx;
+
+/// Subdirectory of the import directory to which imported files are moved.
+const ARCHIVE: &str = "archive";
+/// Subdirectory of the import directory to which files that can not be imported are moved.
+const FAILED: &str = "failed";
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("{0}")]
+    Reqwest(#[from] ReqwestError),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+type Result<T> = StdResult<T, Error>;
+
+#[derive(Debug, Error)]
+enum UserError {
+    #[error("no import directory configured")]
+    NoDirectory,
+    #[error("some files could not be imported: {0}")]
+    ImportFailed(String),
+}
+
+/// The reason why a single file was not imported.
+#[derive(Debug, Error)]
+enum FileError {
+    /// The file is moved to [`FAILED`].
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    /// The file stays in the import directory and is imported once the activities are mapped.
+    #[error("not mapped to a movement: {0}")]
+    Unmapped(String),
+    /// The server rejected the sessions of the file.
+    ///
+    /// The file is moved to [`FAILED`].
+    #[error("rejected by the server: {0}")]
+    Rejected(StatusCode),
+}
+
+impl FileError {
+    /// A 4xx response to an upload is caused by the content of the file and is therefore not retried.
+    fn rejected(error: ReqwestError) -> Result<Self> {
+        match error.status() {
+            Some(status) if status.is_client_error() => Ok(Self::Rejected(status)),
+            _ => Err(error.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FileType {
+    Gpx,
+    Tcx,
+    Fit,
+    Csv,
+}
+
+impl FileType {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gpx" => Some(Self::Gpx),
+            "tcx" => Some(Self::Tcx),
+            "fit" => Some(Self::Fit),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct FileImportConfig {
+    directories: Vec<ImportDirectory>,
+}
+
+/// A directory from which the files of a user are imported.
+#[derive(Deserialize, Debug, Clone)]
+struct ImportDirectory {
+    user_id: i64,
+    path: PathBuf,
+}
+
+/// File Import Action Provider
+struct FileImport {
+    client: Client,
+    server_url: String,
+    password: String,
+    platform_id: PlatformId,
+    directories: Vec<ImportDirectory>,
+}
+
+impl Provider for FileImport {
+    const NAME: &'static str = "file-import";
+    const DESCRIPTION: &'static str = "File Import imports GPX, TCX and FIT files as cardio sessions and CSV files as strength sessions from your import directory. The directory is not watched, new files are imported when the import action is executed at the times of your action rules.";
+    const PLATFORM_NAME: &'static str = "file-import";
+    const CREDENTIAL: bool = false;
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[(
+        "import",
+        "Import new files from the import directory.",
+        None,
+    )];
+    const CONFIG_FILE: &'static str = "sport-log-action-provider-file-import.toml";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    type Config = FileImportConfig;
+    type Args = NoArgs;
+    type Error = Error;
+    type UserError = UserError;
+
+    fn create_before() -> Duration {
+        Duration::try_hours(168).unwrap()
+    }
+
+    fn delete_after() -> Duration {
+        Duration::zero()
+    }
+
+    fn event_window() -> (Duration, Duration) {
+        (
+            Duration::try_hours(0).unwrap(),
+            Duration::try_hours(1).unwrap() + Duration::try_minutes(1).unwrap(),
+        )
+    }
+
+    async fn new(config: &Config<FileImportConfig>, _args: &NoArgs) -> Result<Self> {
+        let client = Client::new();
+
+        let action_provider: ActionProvider = client
+            .get(route_max_version(
+                &config.server_url,
+                AP_ACTION_PROVIDER,
+                None,
+            ))
+            .basic_auth(Self::NAME, Some(&config.password))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Self {
+            client,
+            server_url: config.server_url.clone(),
+            password: config.password.clone(),
+            platform_id: action_provider.platform_id,
+            directories: config.provider.directories.clone(),
+        })
+    }
+
+    async fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> EventResult<UserError, Error> {
+        let user_id = exec_action_event.user_id;
+        let Some(directory) = self
+            .directories
+            .iter()
+            .find(|directory| directory.user_id == user_id.0)
+        else {
+            return Ok(Err(UserError::NoDirectory));
+        };
+
+        let files = import_files(&directory.path).await?;
+        if files.is_empty() {
+            info!("no new files for user {}", user_id.0);
+            return Ok(Ok(None));
+        }
+
+        let mut activity_mapper = ActivityMapper::new(
+            &self.client,
+            &self.server_url,
+            Self::NAME,
+            &self.password,
+            self.platform_id,
+            user_id,
+        )
+        .await?;
+        let mut failed_files = vec![];
+
+        for (path, file_type) in files {
+            let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+            match self
+                .import_file(&path, file_type, user_id, &mut activity_mapper)
+                .await?
+            {
+                Ok(()) => {
+                    info!("imported {file_name} for user {}", user_id.0);
+                    move_file(&path, &directory.path.join(ARCHIVE)).await?;
+                }
+                Err(error @ (FileError::Parse(_) | FileError::Rejected(_))) => {
+                    warn!(
+                        "failed to import {file_name} for user {}: {error}",
+                        user_id.0
+                    );
+                    move_file(&path, &directory.path.join(FAILED)).await?;
+                    failed_files.push(format!("{file_name}: {error}"));
+                }
+                Err(error @ FileError::Unmapped(_)) => {
+                    debug!("skipped {file_name} for user {}: {error}", user_id.0);
+                    failed_files.push(format!("{file_name}: {error}"));
+                }
+            }
+        }
+
+        if !failed_files.is_empty() {
+            return Ok(Err(UserError::ImportFailed(failed_files.join("; "))));
+        }
+
+        Ok(Ok(None))
+    }
+}
+
+impl FileImport {
+    async fn import_file(
+        &self,
+        path: &Path,
+        file_type: FileType,
+        user_id: UserId,
+        activity_mapper: &mut ActivityMapper,
+    ) -> Result<StdResult<(), FileError>> {
+        let data = fs::read(path).await?;
+
+        let activity = match file_type {
+            FileType::Gpx => utf8(data).and_then(|content| gpx::parse(&content)),
+            FileType::Tcx => utf8(data).and_then(|content| tcx::parse(&content)),
+            FileType::Fit => fit::parse(&data),
+            FileType::Csv => {
+                return match utf8(data).and_then(|content| strength::parse(&content)) {
+                    Ok(strengths) => {
+                        self.import_strength(strengths, user_id, activity_mapper)
+                            .await
+                    }
+                    Err(error) => Ok(Err(error.into())),
+                };
+            }
+        };
+        let activity = match activity {
+            Ok(activity) => activity,
+            Err(error) => return Ok(Err(error.into())),
+        };
+
+        let Some(movement_id) = activity_mapper
+            .movement_id(
+                &self.client,
+                &self.server_url,
+                Self::NAME,
+                &self.password,
+                &activity.activity,
+            )
+            .await?
+        else {
+            return Ok(Err(FileError::Unmapped(activity.activity)));
+        };
+
+        let cardio_session = activity.into_cardio_session(user_id, movement_id);
+        match create_cardio_session(
+            &self.client,
+            &self.server_url,
+            Self::NAME,
+            &self.password,
+            &cardio_session,
+        )
+        .await
+        {
+            Ok(_) => Ok(Ok(())),
+            Err(error) => FileError::rejected(error).map(Err),
+        }
+    }
+
+    async fn import_strength(
+        &self,
+        strengths: Vec<strength::Strength>,
+        user_id: UserId,
+        activity_mapper: &mut ActivityMapper,
+    ) -> Result<StdResult<(), FileError>> {
+        let mut movement_ids = vec![];
+        let mut unmapped_movements: Vec<String> = vec![];
+        for strength in &strengths {
+            let movement_id = activity_mapper
+                .movement_id(
+                    &self.client,
+                    &self.server_url,
+                    Self::NAME,
+                    &self.password,
+                    &strength.movement,
+                )
+                .await?;
+            match movement_id {
+                Some(movement_id) => movement_ids.push(movement_id),
+                None if !unmapped_movements.contains(&strength.movement) => {
+                    unmapped_movements.push(strength.movement.clone());
+                }
+                None => {}
+            }
+        }
+        if !unmapped_movements.is_empty() {
+            return Ok(Err(FileError::Unmapped(unmapped_movements.join(", "))));
+        }
+
+        let mut strength_sessions = vec![];
+        let mut strength_sets = vec![];
+        for (strength, movement_id) in strengths.into_iter().zip(movement_ids) {
+            let movement = strength.movement.clone();
+            let (strength_session, sets) = strength.into_strength_session(user_id, movement_id);
+            if let Some(movement) = activity_mapper.movement(movement_id) {
+                for set in &sets {
+                    if let Err(error) = set.validate(movement.movement_dimension) {
+                        return Ok(Err(ParseError::invalid(format!(
+                            "invalid set of {}: {error}",
+                            movement.name
+                        ))
+                        .into()));
+                    }
+                }
+            }
+            debug!("{movement}: {} sets", sets.len());
+            strength_sessions.push(strength_session);
+            strength_sets.extend(sets);
+        }
+
+        // a session that already exists was created by an earlier import of the file
+        // whose sets may have failed, so only its sets are created
+        let existing_sessions = self
+            .get_strength_sessions(&strength_sessions, user_id)
+            .await?;
+        for strength_session in strength_sessions {
+            let mut sets: Vec<_> = strength_sets
+                .iter()
+                .filter(|set| set.strength_session_id == strength_session.id)
+                .cloned()
+                .collect();
+            let existing_session = existing_sessions.iter().find(|existing_session| {
+                existing_session.datetime == strength_session.datetime
+                    && existing_session.movement_id == strength_session.movement_id
+            });
+
+            if let Some(existing_session) = existing_session {
+                for set in &mut sets {
+                    set.strength_session_id = existing_session.id;
+                }
+            } else if let Err(error) = self
+                .client
+                .post(route_max_version(&self.server_url, STRENGTH_SESSION, None))
+                .basic_auth(Self::NAME, Some(&self.password))
+                .header(ID_HEADER, user_id.0)
+                .json(&strength_session)
+                .send()
+                .await?
+                .error_for_status()
+            {
+                return FileError::rejected(error).map(Err);
+            }
+
+            if let Err(error) = self
+                .client
+                .post(route_max_version(&self.server_url, STRENGTH_SET, None))
+                .basic_auth(Self::NAME, Some(&self.password))
+                .header(ID_HEADER, user_id.0)
+                .json(&sets)
+                .send()
+                .await?
+                .error_for_status()
+            {
+                // the set numbers of a session are unique, so the sets have already been created
+                if existing_session.is_some() && error.status() == Some(StatusCode::CONFLICT) {
+                    debug!("strength session already exists for user {}", user_id.0);
+                    continue;
+                }
+                return FileError::rejected(error).map(Err);
+            }
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// Get the strength sessions of the user between the first and the last of `strength_sessions`.
+    async fn get_strength_sessions(
+        &self,
+        strength_sessions: &[StrengthSession],
+        user_id: UserId,
+    ) -> Result<Vec<StrengthSession>> {
+        let datetimes = strength_sessions
+            .iter()
+            .map(|strength_session| strength_session.datetime);
+        let (Some(start), Some(end)) = (datetimes.clone().min(), datetimes.max()) else {
+            return Ok(vec![]);
+        };
+        let start = start.to_rfc3339().replace("+00:00", "Z");
+        let end = end.to_rfc3339().replace("+00:00", "Z");
+
+        Ok(self
+            .client
+            .get(route_max_version(
+                &self.server_url,
+                STRENGTH_SESSION,
+                Some(&[("start", start.as_str()), ("end", end.as_str())]),
+            ))
+            .basic_auth(Self::NAME, Some(&self.password))
+            .header(ID_HEADER, user_id.0)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing(env!("CARGO_CRATE_NAME"));
+    run::<FileImport>().await
+}
+
+fn utf8(data: Vec<u8>) -> StdResult<String, ParseError> {
+    String::from_utf8(data).map_err(|_| ParseError::invalid("file is not valid UTF-8"))
+}
+
+/// The files in `directory` that can be imported ordered by name.
+async fn import_files(directory: &Path) -> Result<Vec<(PathBuf, FileType)>> {
+    let mut files = vec![];
+    let mut entries = fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if let Some(file_type) = FileType::from_path(&path) {
+            files.push((path, file_type));
+        }
+    }
+    files.sort_by(|(path1, _), (path2, _)| path1.cmp(path2));
+    Ok(files)
+}
+
+async fn move_file(path: &Path, directory: &Path) -> Result<()> {
+    fs::create_dir_all(directory).await?;
+    fs::rename(path, directory.join(path.file_name().unwrap())).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sport_log_ap_utils::mock_server::MockServer;
+    use sport_log_types::{
+        ActionEventId, ActionId, ActionProviderId, ActivityMapping, ActivityMappingId, Movement,
+        MovementDimension, MovementId, StrengthSessionId,
+    };
+
+    use super::*;
+
+    const PASSWORD: &str = "file-import-password";
+
+    async fn start_server() -> MockServer {
+        let server = MockServer::start().await;
+        server.state().action_providers.push(ActionProvider {
+            id: ActionProviderId(1),
+            name: FileImport::NAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            description: None,
+            deleted: false,
+        });
+        for (id, name, movement_dimension, cardio) in [
+            (1, "Running", MovementDimension::Distance, true),
+            (2, "Back Squat", MovementDimension::Reps, false),
+            (3, "Pull-Up", MovementDimension::Reps, false),
+        ] {
+            server.add_movement(Movement {
+                id: MovementId(id),
+                user_id: None,
+                name: name.to_owned(),
+                description: None,
+                movement_dimension,
+                cardio,
+                deleted: false,
+            });
+        }
+        server
+    }
+
+    fn exec_action_event() -> ExecutableActionEvent {
+        ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: "import".to_owned(),
+            datetime: Utc::now(),
+            arguments: None,
+            attempts: 0,
+            time_zone: "UTC".to_owned(),
+            user_id: UserId(1),
+            username: None,
+            password: None,
+        }
+    }
+
+    fn file_import(server: &MockServer, directory: &Path) -> FileImport {
+        FileImport {
+            client: Client::new(),
+            server_url: server.url().to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            directories: vec![ImportDirectory {
+                user_id: 1,
+                path: directory.to_owned(),
+            }],
+        }
+    }
+
+    fn copy_fixture(file: &str, directory: &Path) {
+        std::fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("fixtures")
+                .join(file),
+            directory.join(file),
+        )
+        .unwrap();
+    }
+
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut file_names: Vec<String> = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.file_type().unwrap().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        file_names.sort();
+        file_names
+    }
+
+    #[tokio::test]
+    async fn import_directory() {
+        let server = start_server().await;
+        let directory = tempfile::tempdir().unwrap();
+        for file in [
+            "activity.gpx",
+            "activity.tcx",
+            "activity.fit",
+            "strength.csv",
+        ] {
+            copy_fixture(file, directory.path());
+        }
+        std::fs::write(directory.path().join("broken.gpx"), "<gpx>").unwrap();
+        std::fs::write(directory.path().join("notes.txt"), "").unwrap();
+
+        let file_import = file_import(&server, directory.path());
+
+        // biking is not mapped and broken.gpx can not be parsed
+        let result = file_import.handle(&exec_action_event()).await.unwrap();
+        let Err(UserError::ImportFailed(message)) = result else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(message.contains("activity.tcx: not mapped to a movement: biking"));
+        assert!(message.contains("broken.gpx: "));
+        {
+            let state = server.state();
+            assert_eq!(state.cardio_sessions.len(), 2);
+            assert_eq!(state.strength_sessions.len(), 2);
+            assert_eq!(state.strength_sets.len(), 5);
+        }
+        assert_eq!(file_names(directory.path()), ["activity.tcx", "notes.txt"]);
+        assert_eq!(
+            file_names(&directory.path().join(ARCHIVE)),
+            ["activity.fit", "activity.gpx", "strength.csv"]
+        );
+        assert_eq!(file_names(&directory.path().join(FAILED)), ["broken.gpx"]);
+
+        // the user maps biking to running
+        server
+            .state()
+            .activity_mappings
+            .iter_mut()
+            .find(|activity_mapping| activity_mapping.activity == "biking")
+            .unwrap()
+            .movement_id = Some(MovementId(1));
+
+        let result = file_import.handle(&exec_action_event()).await.unwrap();
+        assert!(result.is_ok());
+        assert_eq!(server.state().cardio_sessions.len(), 3);
+        assert_eq!(file_names(directory.path()), ["notes.txt"]);
+
+        let mut exec_action_event = exec_action_event();
+        exec_action_event.user_id = UserId(2);
+        let result = file_import.handle(&exec_action_event).await.unwrap();
+        assert!(matches!(result, Err(UserError::NoDirectory)));
+    }
+
+    #[tokio::test]
+    async fn resume_strength_import() {
+        let server = start_server().await;
+        let directory = tempfile::tempdir().unwrap();
+        let file_import = file_import(&server, directory.path());
+
+        // an earlier import created the back squat session but failed to create its sets
+        let strength_session = StrengthSession {
+            id: StrengthSessionId(1),
+            user_id: UserId(1),
+            datetime: "2026-10-18T18:00:00Z".parse().unwrap(),
+            movement_id: MovementId(2),
+            interval: None,
+            strength_superset_id: None,
+            superset_position: None,
+            comments: None,
+            deleted: false,
+        };
+        server.state().strength_sessions.push(strength_session);
+
+        copy_fixture("strength.csv", directory.path());
+        let result = file_import.handle(&exec_action_event()).await.unwrap();
+        assert!(result.is_ok());
+        {
+            let state = server.state();
+            assert_eq!(state.strength_sessions.len(), 2);
+            assert_eq!(state.strength_sets.len(), 5);
+            let squat_sets = state
+                .strength_sets
+                .iter()
+                .filter(|set| set.strength_session_id == StrengthSessionId(1))
+                .count();
+            assert_eq!(squat_sets, 3);
+        }
+
+        // importing the same file again does not duplicate anything
+        copy_fixture("strength.csv", directory.path());
+        let result = file_import.handle(&exec_action_event()).await.unwrap();
+        assert!(result.is_ok());
+        {
+            let state = server.state();
+            assert_eq!(state.strength_sessions.len(), 2);
+            assert_eq!(state.strength_sets.len(), 5);
+        }
+        assert_eq!(
+            file_names(&directory.path().join(ARCHIVE)),
+            ["strength.csv"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_file() {
+        let server = start_server().await;
+        let directory = tempfile::tempdir().unwrap();
+        let file_import = file_import(&server, directory.path());
+
+        // the user mapped pull-ups to a movement that does not exist anymore
+        server.state().activity_mappings.push(ActivityMapping {
+            id: ActivityMappingId(1),
+            user_id: UserId(1),
+            platform_id: PlatformId(1),
+            activity: "Pull-Up".to_owned(),
+            movement_id: Some(MovementId(99)),
+            deleted: false,
+        });
+
+        copy_fixture("strength.csv", directory.path());
+        let result = file_import.handle(&exec_action_event()).await.unwrap();
+        let Err(UserError::ImportFailed(message)) = result else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(message.contains("strength.csv: rejected by the server: 409 Conflict"));
+        assert_eq!(file_names(&directory.path().join(FAILED)), ["strength.csv"]);
+        assert!(file_names(directory.path()).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Parser for CSV files with strength sets.
+//!
+//! Every row is a set with the columns `datetime` (RFC 3339), `movement` and `count`
+//! and the optional columns `weight`, `rpe` and `rir`.
+//! Sets with the same datetime and movement belong to the same session and are numbered in the order of the file.
+
+use chrono::{DateTime, Utc};
+use csv::{ReaderBuilder, Trim};
+use rand::Rng;
+use serde::Deserialize;
+use sport_log_types::{
+    MovementId, StrengthSession, StrengthSessionId, StrengthSet, StrengthSetId, UserId,
+};
+
+use crate::activity::ParseError;
+
+#[derive(Deserialize, Debug)]
+struct Row {
+    datetime: DateTime<Utc>,
+    movement: String,
+    count: i32,
+    #[serde(default)]
+    weight: Option<f32>,
+    #[serde(default)]
+    rpe: Option<f32>,
+    #[serde(default)]
+    rir: Option<i32>,
+}
+
+/// The sets of a movement done at the same time.
+#[derive(Debug, PartialEq)]
+pub struct Strength {
+    pub datetime: DateTime<Utc>,
+    /// The key of the movement in [`ActivityMapping`](sport_log_types::ActivityMapping).
+    pub movement: String,
+    pub sets: Vec<Set>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Set {
+    pub count: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+    pub rir: Option<i32>,
+}
+
+pub fn parse(content: &str) -> Result<Vec<Strength>, ParseError> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut strengths: Vec<Strength> = vec![];
+    for row in reader.deserialize() {
+        let row: Row = row?;
+        let set = Set {
+            count: row.count,
+            weight: row.weight,
+            rpe: row.rpe,
+            rir: row.rir,
+        };
+        match strengths
+            .iter_mut()
+            .find(|strength| strength.datetime == row.datetime && strength.movement == row.movement)
+        {
+            Some(strength) => strength.sets.push(set),
+            None => strengths.push(Strength {
+                datetime: row.datetime,
+                movement: row.movement,
+                sets: vec![set],
+            }),
+        }
+    }
+
+    if strengths.is_empty() {
+        return Err(ParseError::invalid("file contains no sets"));
+    }
+
+    Ok(strengths)
+}
+
+impl Strength {
+    pub fn into_strength_session(
+        self,
+        user_id: UserId,
+        movement_id: MovementId,
+    ) -> (StrengthSession, Vec<StrengthSet>) {
+        let strength_session = StrengthSession {
+            id: StrengthSessionId(rand::thread_rng().gen()),
+            user_id,
+            datetime: self.datetime,
+            movement_id,
+            interval: None,
+            strength_superset_id: None,
+            superset_position: None,
+            comments: None,
+            deleted: false,
+        };
+
+        let strength_sets = self
+            .sets
+            .into_iter()
+            .enumerate()
+            .map(|(set_number, set)| StrengthSet {
+                id: StrengthSetId(rand::thread_rng().gen()),
+                strength_session_id: strength_session.id,
+                set_number: set_number as i32,
+                count: set.count,
+                weight: set.weight,
+                rpe: set.rpe,
+                rir: set.rir,
+                tempo: None,
+                rest: None,
+                deleted: false,
+            })
+            .collect();
+
+        (strength_session, strength_sets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv() {
+        let strengths = parse(include_str!("../fixtures/strength.csv")).unwrap();
+        assert_eq!(strengths.len(), 2);
+        assert_eq!(strengths[0].movement, "Back Squat");
+        assert_eq!(
+            strengths[0].sets,
+            [
+                Set {
+                    count: 5,
+                    weight: Some(100.),
+                    rpe: None,
+                    rir: None,
+                },
+                Set {
+                    count: 5,
+                    weight: Some(102.5),
+                    rpe: Some(8.5),
+                    rir: None,
+                },
+                Set {
+                    count: 3,
+                    weight: Some(105.),
+                    rpe: None,
+                    rir: Some(1),
+                },
+            ]
+        );
+        assert_eq!(strengths[1].movement, "Pull-Up");
+        assert_eq!(strengths[1].sets.len(), 2);
+        assert_eq!(strengths[1].sets[0].weight, None);
+
+        let (strength_session, strength_sets) = parse(include_str!("../fixtures/strength.csv"))
+            .unwrap()
+            .remove(0)
+            .into_strength_session(UserId(1), MovementId(2));
+        assert_eq!(strength_session.movement_id, MovementId(2));
+        let set_numbers: Vec<i32> = strength_sets.iter().map(|set| set.set_number).collect();
+        assert_eq!(set_numbers, [0, 1, 2]);
+        assert!(strength_sets
+            .iter()
+            .all(|set| set.strength_session_id == strength_session.id));
+
+        assert!(parse("datetime,movement,count\n").is_err());
+        assert!(parse("datetime,movement,count\n2026-10-18T18:00:00Z,Pull-Up,many\n").is_err());
+        assert!(parse("datetime,movement\n2026-10-18T18:00:00Z,Pull-Up\n").is_err());
+    }
+}
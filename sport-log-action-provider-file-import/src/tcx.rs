@@ -0,0 +1,122 @@
+//! Parser for TCX files.
+//!
+//! Only the first activity of a file is imported.
+//! The activity is the lowercase `Sport` of the activity, e.g. `running`, `biking` or `other`.
+
+use roxmltree::{Document, Node};
+
+use crate::activity::{optional_number, parse_number, parse_time, Activity, ParseError, Point};
+
+pub fn parse(content: &str) -> Result<Activity, ParseError> {
+    let document = Document::parse(content)?;
+    let activity = document
+        .descendants()
+        .find(|node| node.has_tag_name("Activity"))
+        .ok_or_else(|| ParseError::invalid("file contains no activity"))?;
+
+    let sport = activity
+        .attribute("Sport")
+        .unwrap_or("other")
+        .to_lowercase();
+
+    let laps: Vec<Node> = activity
+        .children()
+        .filter(|node| node.has_tag_name("Lap"))
+        .collect();
+
+    let calories = laps
+        .iter()
+        .filter_map(|lap| child(*lap, "Calories"))
+        .map(|calories| parse_number::<i32>(calories.text(), "Calories"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let calories = (!calories.is_empty()).then(|| calories.iter().sum());
+
+    let points = laps
+        .iter()
+        .filter_map(|lap| child(*lap, "Track"))
+        .flat_map(|track| {
+            track
+                .children()
+                .filter(|node| node.has_tag_name("Trackpoint"))
+        })
+        .map(parse_trackpoint)
+        .collect::<Result<_, _>>()?;
+
+    Activity::new(sport, points, calories)
+}
+
+fn parse_trackpoint(node: Node) -> Result<Point, ParseError> {
+    let time = child(node, "Time").and_then(|time| time.text());
+    let time = time.ok_or_else(|| ParseError::invalid("Trackpoint without Time"))?;
+    let position = child(node, "Position");
+
+    Ok(Point {
+        time: parse_time(time.trim())?,
+        latitude: position
+            .map(|position| {
+                parse_number(child_text(position, "LatitudeDegrees"), "LatitudeDegrees")
+            })
+            .transpose()?,
+        longitude: position
+            .map(|position| {
+                parse_number(child_text(position, "LongitudeDegrees"), "LongitudeDegrees")
+            })
+            .transpose()?,
+        elevation: optional_number(child_text(node, "AltitudeMeters"), "AltitudeMeters")?,
+        distance: optional_number(child_text(node, "DistanceMeters"), "DistanceMeters")?,
+        heart_rate: optional_number(
+            child(node, "HeartRateBpm").and_then(|heart_rate| child_text(heart_rate, "Value")),
+            "HeartRateBpm",
+        )?,
+        cadence: optional_number(
+            child_text(node, "Cadence").or_else(|| {
+                node.descendants()
+                    .find(|node| node.has_tag_name("RunCadence"))
+                    .and_then(|cadence| cadence.text())
+            }),
+            "Cadence",
+        )?,
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|child| child.text())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tcx() {
+        let activity = parse(include_str!("../fixtures/activity.tcx")).unwrap();
+        assert_eq!(activity.activity, "biking");
+        assert_eq!(activity.calories, Some(42));
+        assert_eq!(activity.points.len(), 3);
+        assert_eq!(
+            activity.points[0],
+            Point {
+                time: parse_time("2026-10-18T16:00:00Z").unwrap(),
+                latitude: Some(48.1),
+                longitude: Some(11.5),
+                elevation: Some(520.5),
+                distance: Some(0.),
+                heart_rate: Some(110),
+                cadence: Some(90),
+            }
+        );
+        assert_eq!(activity.points[1].cadence, Some(85));
+        assert_eq!(activity.points[2].latitude, None);
+        assert_eq!(activity.points[2].distance, Some(780.));
+
+        assert!(parse("<TrainingCenterDatabase/>").is_err());
+        assert!(parse(
+            "<TrainingCenterDatabase><Activities><Activity><Lap><Track><Trackpoint/></Track></Lap></Activity></Activities></TrainingCenterDatabase>"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,526 @@
+use std::{process::ExitCode, result::Result as StdResult};
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use lettre::{
+    address::AddressError,
+    error::Error as EmailError,
+    message::{header::ContentType, Mailbox},
+    transport::smtp::{authentication::Credentials, Error as SmtpError},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use reqwest::{Client, Error as ReqwestError};
+use serde::{de::DeserializeOwned, Deserialize};
+use sport_log_ap_utils::{init_tracing, run, Config, EventResult, NoArgs, Provider};
+use sport_log_types::{
+    uri::{route_max_version, CARDIO_SESSION, METCON_SESSION, STRENGTH_SESSION, USER},
+    CardioSession, ExecutableActionEvent, MetconSession, StrengthSession, User, UserId, ID_HEADER,
+};
+use thiserror::Error;
+use tracing::info;
+
+#[cfg(test)]
+mod smtp_sink;
+
+const REMINDER: &str = "Reminder";
+
+const REMINDER_ARGUMENT_SCHEMA: &str = r#"{
+    "type": "object",
+    "properties": {
+        "subject": {
+            "type": "string",
+            "minLength": 1,
+            "maxLength": 200,
+            "description": "The subject template. Defaults to \"Sport Log Reminder\"."
+        },
+        "message": {
+            "type": "string",
+            "minLength": 1,
+            "description": "The message template. {date}, {weekday} and {time} are replaced with the time of the action event in the time zone of the action rule."
+        },
+        "condition": {
+            "type": "object",
+            "properties": {
+                "no_session_for_days": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 3650,
+                    "description": "Only send the reminder if no strength, cardio or metcon session was logged within this many days before the action event."
+                }
+            },
+            "required": ["no_session_for_days"],
+            "additionalProperties": false
+        }
+    },
+    "required": ["message"],
+    "additionalProperties": false
+}"#;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("{0}")]
+    Reqwest(#[from] ReqwestError),
+    #[error("{0}")]
+    Smtp(#[from] SmtpError),
+    #[error("{0}")]
+    Email(#[from] EmailError),
+    #[error("invalid sender address: {0}")]
+    Address(#[from] AddressError),
+}
+
+type Result<T> = StdResult<T, Error>;
+
+#[derive(Debug, Error)]
+enum UserError {
+    #[error("invalid arguments: {0}")]
+    InvalidArguments(String),
+    #[error("invalid email address: {0}")]
+    InvalidAddress(String),
+}
+
+type UserResult<T> = StdResult<T, UserError>;
+
+/// How the connection to the SMTP server is secured.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Security {
+    /// Unencrypted connection, only for local SMTP servers.
+    None,
+    #[default]
+    Starttls,
+    Tls,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmtpConfig {
+    host: String,
+    /// Defaults to the standard port of the [`Security`].
+    port: Option<u16>,
+    #[serde(default)]
+    security: Security,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReminderConfig {
+    /// The sender of the reminders, e.g. `Sport Log <reminder@example.com>`.
+    from: String,
+    smtp: SmtpConfig,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ReminderArguments {
+    #[serde(default = "default_subject")]
+    subject: String,
+    message: String,
+    condition: Option<Condition>,
+}
+
+fn default_subject() -> String {
+    "Sport Log Reminder".to_owned()
+}
+
+/// A condition that has to be fulfilled for the reminder to be sent.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Condition {
+    NoSessionForDays(i64),
+}
+
+/// Reminder Action Provider
+struct Reminder {
+    client: Client,
+    server_url: String,
+    password: String,
+    from: Mailbox,
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl Provider for Reminder {
+    const NAME: &'static str = "reminder";
+    const DESCRIPTION: &'static str =
+        "Reminder sends emails to the address of your account at the time of the action events, optionally only if you have not logged a session for some days.";
+    const PLATFORM_NAME: &'static str = "email";
+    const CREDENTIAL: bool = false;
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[(
+        REMINDER,
+        "Send a reminder email.",
+        Some(REMINDER_ARGUMENT_SCHEMA),
+    )];
+    const CONFIG_FILE: &'static str = "sport-log-action-provider-reminder.toml";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    type Config = ReminderConfig;
+    type Args = NoArgs;
+    type Error = Error;
+    type UserError = UserError;
+
+    fn create_before() -> Duration {
+        Duration::try_hours(168).unwrap()
+    }
+
+    fn delete_after() -> Duration {
+        Duration::try_hours(24).unwrap()
+    }
+
+    fn event_window() -> (Duration, Duration) {
+        // reminders that are more than a few hours late are useless
+        (Duration::try_hours(-3).unwrap(), Duration::zero())
+    }
+
+    async fn new(config: &Config<ReminderConfig>, _args: &NoArgs) -> Result<Self> {
+        let smtp = &config.provider.smtp;
+        let mut mailer = match smtp.security {
+            Security::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+            Security::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?,
+            Security::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?,
+        };
+        if let Some(port) = smtp.port {
+            mailer = mailer.port(port);
+        }
+        if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+            mailer = mailer.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            client: Client::new(),
+            server_url: config.server_url.clone(),
+            password: config.password.clone(),
+            from: config.provider.from.parse()?,
+            mailer: mailer.build(),
+        })
+    }
+
+    async fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> EventResult<UserError, Error> {
+        let arguments = match parse_arguments(exec_action_event) {
+            Ok(arguments) => arguments,
+            Err(error) => return Ok(Err(error)),
+        };
+        let tz = exec_action_event.time_zone.parse().unwrap_or(Tz::UTC);
+
+        if let Some(Condition::NoSessionForDays(days)) = arguments.condition {
+            let Some(start) = Duration::try_days(days)
+                .and_then(|duration| exec_action_event.datetime.checked_sub_signed(duration))
+            else {
+                return Ok(Err(UserError::InvalidArguments(format!(
+                    "no_session_for_days {days} is out of range"
+                ))));
+            };
+            if self
+                .has_session(exec_action_event.user_id, start, exec_action_event.datetime)
+                .await?
+            {
+                info!("no reminder sent to user {}", exec_action_event.user_id.0);
+                return Ok(Ok(Some(format!(
+                    "no reminder sent: a session was logged within the last {days} days"
+                ))));
+            }
+        }
+
+        // reminders are only sent to the owner of the action event
+        let user = self.user(exec_action_event.user_id).await?;
+        let Ok(to) = user.email.parse::<Mailbox>() else {
+            return Ok(Err(UserError::InvalidAddress(user.email)));
+        };
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(render(&arguments.subject, exec_action_event.datetime, tz))
+            .header(ContentType::TEXT_PLAIN)
+            .body(render(&arguments.message, exec_action_event.datetime, tz))?;
+        self.mailer.send(email).await?;
+        info!("reminder sent to user {}", exec_action_event.user_id.0);
+
+        Ok(Ok(Some(format!("reminder sent to {}", user.email))))
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing(env!("CARGO_CRATE_NAME"));
+    run::<Reminder>().await
+}
+
+fn parse_arguments(exec_action_event: &ExecutableActionEvent) -> UserResult<ReminderArguments> {
+    let arguments = exec_action_event
+        .arguments
+        .as_deref()
+        .ok_or_else(|| UserError::InvalidArguments("no arguments".to_owned()))?;
+
+    serde_json::from_str(arguments).map_err(|error| UserError::InvalidArguments(error.to_string()))
+}
+
+/// Replace the placeholders `{date}`, `{weekday}` and `{time}` in the template with the local time of `datetime` in `tz`.
+fn render(template: &str, datetime: DateTime<Utc>, tz: Tz) -> String {
+    let datetime = datetime.with_timezone(&tz);
+    template
+        .replace("{date}", &datetime.format("%Y-%m-%d").to_string())
+        .replace("{weekday}", &datetime.format("%A").to_string())
+        .replace("{time}", &datetime.format("%H:%M").to_string())
+}
+
+impl Reminder {
+    async fn user(&self, user_id: UserId) -> Result<User> {
+        Ok(self
+            .client
+            .get(route_max_version(&self.server_url, USER, None))
+            .basic_auth(Self::NAME, Some(&self.password))
+            .header(ID_HEADER, user_id.0)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    async fn sessions<T: DeserializeOwned>(
+        &self,
+        route: &str,
+        user_id: UserId,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<T>> {
+        Ok(self
+            .client
+            .get(route_max_version(
+                &self.server_url,
+                route,
+                Some(&[("start", start), ("end", end)]),
+            ))
+            .basic_auth(Self::NAME, Some(&self.password))
+            .header(ID_HEADER, user_id.0)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Check if the user logged a strength, cardio or metcon session between `start` and `end`.
+    async fn has_session(
+        &self,
+        user_id: UserId,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<bool> {
+        let start = start.to_rfc3339().replace("+00:00", "Z");
+        let end = end.to_rfc3339().replace("+00:00", "Z");
+
+        let strength_sessions: Vec<StrengthSession> = self
+            .sessions(STRENGTH_SESSION, user_id, &start, &end)
+            .await?;
+        if strength_sessions.iter().any(|session| !session.deleted) {
+            return Ok(true);
+        }
+
+        let cardio_sessions: Vec<CardioSession> =
+            self.sessions(CARDIO_SESSION, user_id, &start, &end).await?;
+        if cardio_sessions.iter().any(|session| !session.deleted) {
+            return Ok(true);
+        }
+
+        let metcon_sessions: Vec<MetconSession> =
+            self.sessions(METCON_SESSION, user_id, &start, &end).await?;
+        Ok(metcon_sessions.iter().any(|session| !session.deleted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sport_log_ap_utils::mock_server::MockServer;
+    use sport_log_types::{
+        argument_schema::ArgumentSchema, ActionEventId, ActionId, ActionProvider, ActionProviderId,
+        CardioSessionId, CardioType, MovementId, PlatformId,
+    };
+
+    use super::*;
+    use crate::smtp_sink::SmtpSink;
+
+    const PASSWORD: &str = "reminder-password";
+
+    fn exec_action_event(
+        datetime: DateTime<Utc>,
+        arguments: Option<&str>,
+    ) -> ExecutableActionEvent {
+        ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: REMINDER.to_owned(),
+            datetime,
+            arguments: arguments.map(ToOwned::to_owned),
+            attempts: 0,
+            time_zone: "UTC".to_owned(),
+            user_id: UserId(1),
+            username: None,
+            password: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_reminder() {
+        assert!(ArgumentSchema::parse(REMINDER_ARGUMENT_SCHEMA).is_ok());
+
+        let server = MockServer::start().await;
+        server.state().action_providers.push(ActionProvider {
+            id: ActionProviderId(1),
+            name: Reminder::NAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            description: None,
+            deleted: false,
+        });
+        server.state().users.push(User {
+            id: UserId(1),
+            username: "athlete".to_owned(),
+            password: String::new(),
+            email: "athlete@example.com".to_owned(),
+        });
+        let sink = SmtpSink::start().await;
+
+        let config = Config {
+            password: PASSWORD.to_owned(),
+            server_url: server.url().to_owned(),
+            run_interval_minutes: 15,
+            provider: ReminderConfig {
+                from: "Sport Log <reminder@example.com>".to_owned(),
+                smtp: SmtpConfig {
+                    host: "127.0.0.1".to_owned(),
+                    port: Some(sink.port()),
+                    security: Security::None,
+                    username: None,
+                    password: None,
+                },
+            },
+        };
+        let reminder = Reminder::new(&config, &NoArgs {}).await.unwrap();
+
+        let datetime = Utc::now();
+        let arguments = r#"{
+            "subject": "Plan your week",
+            "message": "It is {weekday}, plan the week starting {date}."
+        }"#;
+        let result = reminder
+            .handle(&exec_action_event(datetime, Some(arguments)))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("reminder sent to athlete@example.com")
+        );
+        {
+            let mails = sink.mails();
+            assert_eq!(mails.len(), 1);
+            assert_eq!(mails[0].from, "reminder@example.com");
+            assert_eq!(mails[0].to, ["athlete@example.com"]);
+            assert!(mails[0].data.contains("Subject: Plan your week"));
+            assert!(mails[0].data.contains(&render(
+                "It is {weekday}, plan the week starting {date}.",
+                datetime,
+                Tz::UTC
+            )));
+        }
+
+        // the reminder is only sent if there is no session in the last 3 days
+        let arguments = r#"{
+            "message": "You have not trained for 3 days.",
+            "condition": {"no_session_for_days": 3}
+        }"#;
+        server.state().cardio_sessions.push(CardioSession {
+            id: CardioSessionId(1),
+            user_id: UserId(1),
+            movement_id: MovementId(1),
+            cardio_type: CardioType::Training,
+            datetime: datetime - Duration::try_days(2).unwrap(),
+            distance: None,
+            ascent: None,
+            descent: None,
+            time: None,
+            calories: None,
+            track: None,
+            avg_cadence: None,
+            cadence: None,
+            avg_heart_rate: None,
+            heart_rate: None,
+            route_id: None,
+            comments: None,
+            deleted: false,
+        });
+        let result = reminder
+            .handle(&exec_action_event(datetime, Some(arguments)))
+            .await
+            .unwrap();
+        assert!(result.unwrap().unwrap().starts_with("no reminder sent"));
+        assert_eq!(sink.mails().len(), 1);
+
+        let later = datetime + Duration::try_days(2).unwrap();
+        let result = reminder
+            .handle(&exec_action_event(later, Some(arguments)))
+            .await
+            .unwrap();
+        assert!(result.is_ok());
+        {
+            let mails = sink.mails();
+            assert_eq!(mails.len(), 2);
+            assert!(mails[1].data.contains("Subject: Sport Log Reminder"));
+            assert!(mails[1].data.contains("You have not trained for 3 days."));
+        }
+
+        let result = reminder
+            .handle(&exec_action_event(datetime, None))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(UserError::InvalidArguments(_))));
+        // reminders can not be sent to other addresses
+        let result = reminder
+            .handle(&exec_action_event(
+                datetime,
+                Some(r#"{"to": "someone@example.com", "message": "Hi"}"#),
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(UserError::InvalidArguments(_))));
+        let result = reminder
+            .handle(&exec_action_event(
+                datetime,
+                Some(&format!(
+                    r#"{{"message": "Hi", "condition": {{"no_session_for_days": {}}}}}"#,
+                    i64::MAX
+                )),
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(UserError::InvalidArguments(_))));
+
+        server.state().users[0].email = "not an address".to_owned();
+        let result = reminder
+            .handle(&exec_action_event(datetime, Some(r#"{"message": "Hi"}"#)))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(UserError::InvalidAddress(_))));
+        assert_eq!(sink.mails().len(), 2);
+    }
+
+    #[test]
+    fn render_in_time_zone() {
+        let datetime = "2026-10-19T22:30:00Z".parse().unwrap();
+        let template = "{weekday} {date} {time}";
+        assert_eq!(
+            render(template, datetime, Tz::UTC),
+            "Monday 2026-10-19 22:30"
+        );
+        assert_eq!(
+            render(template, datetime, chrono_tz::America::New_York),
+            "Monday 2026-10-19 18:30"
+        );
+        assert_eq!(
+            render(template, datetime, chrono_tz::Europe::Vienna),
+            "Tuesday 2026-10-20 00:30"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Local SMTP server for tests that accepts all mails and keeps them in memory.
+//!
+//! Only the commands needed to deliver a mail without authentication and encryption are supported.
+
+use std::{
+    io::Result,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// A mail as received by the [`SmtpSink`].
+#[derive(Debug, Clone, Default)]
+pub struct Mail {
+    pub from: String,
+    pub to: Vec<String>,
+    /// The headers and the body of the mail.
+    pub data: String,
+}
+
+type Mails = Arc<Mutex<Vec<Mail>>>;
+
+/// An SMTP server listening on a random local port.
+///
+/// The server is stopped when it is dropped.
+pub struct SmtpSink {
+    port: u16,
+    mails: Mails,
+    task: JoinHandle<()>,
+}
+
+impl SmtpSink {
+    pub async fn start() -> Self {
+        let mails = Mails::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let task_mails = mails.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mails = task_mails.clone();
+                tokio::spawn(async move {
+                    // the connection is closed on errors
+                    let _ = session(stream, mails).await;
+                });
+            }
+        });
+
+        Self { port, mails, task }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn mails(&self) -> MutexGuard<'_, Vec<Mail>> {
+        self.mails.lock().unwrap()
+    }
+}
+
+impl Drop for SmtpSink {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Strip the angle brackets and parameters from the path of `MAIL FROM` and `RCPT TO`.
+fn address(path: &str) -> String {
+    let path = path.trim();
+    let path = path.split_once(' ').map_or(path, |(path, _)| path);
+    path.trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_owned()
+}
+
+async fn session(stream: TcpStream, mails: Mails) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut mail = Mail::default();
+
+    writer.write_all(b"220 localhost SMTP sink\r\n").await?;
+
+    while let Some(line) = lines.next_line().await? {
+        let command = line.to_ascii_uppercase();
+        let response: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+            b"250 localhost\r\n"
+        } else if command.starts_with("MAIL FROM:") {
+            mail.from = address(&line["MAIL FROM:".len()..]);
+            b"250 OK\r\n"
+        } else if command.starts_with("RCPT TO:") {
+            mail.to.push(address(&line["RCPT TO:".len()..]));
+            b"250 OK\r\n"
+        } else if command == "DATA" {
+            writer
+                .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                .await?;
+            while let Some(line) = lines.next_line().await? {
+                if line == "." {
+                    break;
+                }
+                // remove the dot stuffing
+                mail.data += line.strip_prefix('.').unwrap_or(&line);
+                mail.data += "\n";
+            }
+            mails.lock().unwrap().push(std::mem::take(&mut mail));
+            b"250 OK\r\n"
+        } else if command == "RSET" {
+            mail = Mail::default();
+            b"250 OK\r\n"
+        } else if command == "NOOP" {
+            b"250 OK\r\n"
+        } else if command == "QUIT" {
+            return writer.write_all(b"221 Bye\r\n").await;
+        } else {
+            b"502 Command not implemented\r\n"
+        };
+        writer.write_all(response).await?;
+    }
+
+    Ok(())
+}
@@ -1,70 +1,47 @@
-use std::{env, fs, process::ExitCode, result::Result as StdResult};
+use std::{
+    collections::HashMap, io::ErrorKind, process::ExitCode, result::Result as StdResult,
+    sync::Mutex,
+};
 
 use chrono::{DateTime, Duration};
-use clap::Parser;
 use rand::Rng;
 use reqwest::{Client, Error as ReqwestError};
 use serde::Deserialize;
-use sport_log_ap_utils::{disable_events, get_events, setup as setup_db};
+use sport_log_ap_utils::{
+    average_rate, create_cardio_session, credential, init_tracing, rate_samples_to_times, run,
+    ActivityMapper, Config, EventResult, NoArgs, Provider,
+};
 use sport_log_types::{
-    uri::{route_max_version, CARDIO_SESSION, MOVEMENT},
-    ActionEventId, CardioSession, CardioSessionId, CardioType, ExecutableActionEvent, Movement,
-    Position, ID_HEADER,
+    uri::{route_max_version, AP_ACTION_PROVIDER},
+    ActionProvider, CardioSession, CardioSessionId, CardioType, ExecutableActionEvent, MovementId,
+    PlatformId, Position,
 };
 use thiserror::Error;
-use tokio::task::{JoinError, JoinHandle};
-use tracing::{debug, error, info, warn};
-use tracing_subscriber::EnvFilter;
-
-const CONFIG_FILE: &str = "sport-log-action-provider-sportstracker.toml";
-const NAME: &str = "sportstracker-fetch";
-const DESCRIPTION: &str = "Sportstracker Fetch can fetch the latests workouts recorded with sportstracker and save them in your cardio sessions.";
-const PLATFORM_NAME: &str = "sportstracker";
+use tokio::fs;
+use tracing::{debug, info};
 
 #[derive(Debug, Error)]
 enum Error {
     #[error("{0}")]
     Reqwest(#[from] ReqwestError),
     #[error("{0}")]
-    Join(#[from] JoinError),
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
 }
 
 type Result<T> = StdResult<T, Error>;
 
 #[derive(Debug, Error)]
 enum UserError {
-    #[error("can not log in: no credentials provided")]
-    NoCredential(ActionEventId),
     #[error("can not log in: login failed")]
-    LoginFailed(ActionEventId),
-}
-
-impl UserError {
-    fn action_event_id(&self) -> ActionEventId {
-        match self {
-            Self::NoCredential(action_event_id) | Self::LoginFailed(action_event_id) => {
-                *action_event_id
-            }
-        }
-    }
+    LoginFailed,
+    #[error("activities not mapped to a movement: {0}")]
+    UnmappedActivities(String),
 }
 
 type UserResult<T> = StdResult<T, UserError>;
 
-/// The config for [`sport-log-action-provider-sportstracker`](crate).
-///
-/// The name of the config file is specified in [`CONFIG_FILE`].
-/// The name of the config file is specified in [`CONFIG_FILE`].
-///
-/// `admin_password` is the password for the admin endpoints.
-///
-/// `base_url` is the left part of the URL (everything before `/<version>/...`)
-#[derive(Deserialize, Debug, Clone)]
-struct Config {
-    password: String,
-    server_url: String,
-}
-
 #[derive(Deserialize, Debug)]
 struct User {
     #[serde(rename(serialize = "sessionkey", deserialize = "sessionkey"))]
@@ -118,6 +95,25 @@ struct WorkoutTrack {
     locations: Vec<Location>,
 }
 
+#[derive(Deserialize, Debug)]
+struct WorkoutSamplesWrapper {
+    payload: WorkoutSamples,
+}
+
+#[derive(Deserialize, Debug)]
+struct WorkoutSamples {
+    #[serde(default, rename(deserialize = "heartRate"))]
+    heart_rate: Vec<Sample>,
+    #[serde(default)]
+    cadence: Vec<Sample>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Sample {
+    t: u32, // seconds since start
+    v: u32, // beats or steps per minute
+}
+
 #[derive(Deserialize, Debug)]
 struct Location {
     t: u32,  // seconds since start
@@ -131,273 +127,271 @@ struct Location {
     d: u64, // timestamp in 1 / 1000 s
 }
 
-/// Sportstracker Action Provider
-#[derive(Parser, Debug)]
-#[command( about, long_about = None)]
-struct Args {
-    /// create own actions
-    #[arg(short, long)]
-    setup: bool,
+#[derive(Deserialize, Debug)]
+struct SportstrackerConfig {
+    /// File in which the key of the newest imported workout of every user is stored.
+    #[serde(default = "default_state_file")]
+    state_file: String,
 }
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    if env::var("RUST_LOG").is_err() {
-        if cfg!(debug_assertions) {
-            env::set_var(
-                "RUST_LOG",
-                "info,sport_log_action_provider_sportstracker=debug",
-            );
-        } else {
-            env::set_var(
-                "RUST_LOG",
-                "warn,sport_log_action_provider_sportstracker=info",
-            );
-        }
-    }
-
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
-    let args = Args::parse();
+fn default_state_file() -> String {
+    "sport-log-action-provider-sportstracker-state.json".to_owned()
+}
 
-    let config_file = match fs::read_to_string(CONFIG_FILE) {
-        Ok(file) => file,
-        Err(error) => {
-            error!("failed to read {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
-    let config = match toml::from_str(&config_file) {
-        Ok(config) => config,
-        Err(error) => {
-            error!("failed to parse {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
+/// Sportstracker Action Provider
+struct Sportstracker {
+    client: Client,
+    server_url: String,
+    password: String,
+    platform_id: PlatformId,
+    state_file: String,
+    /// The key of the newest imported workout by user id.
+    last_workout_keys: Mutex<HashMap<i64, String>>,
+}
 
-    if args.setup {
-        if let Err(error) = setup(&config).await {
-            warn!("setup failed: {error}");
-        }
-    } else {
-        #[allow(clippy::collapsible_else_if)]
-        if let Err(error) = fetch(&config).await {
-            warn!("fetching session failed: {error}");
-        }
+impl Provider for Sportstracker {
+    const NAME: &'static str = "sportstracker-fetch";
+    const DESCRIPTION: &'static str = "Sportstracker Fetch can fetch the latests workouts recorded with sportstracker and save them in your cardio sessions.";
+    const PLATFORM_NAME: &'static str = "sportstracker";
+    const CREDENTIAL: bool = true;
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] =
+        &[("fetch", "Fetch and save new workouts.", None)];
+    const CONFIG_FILE: &'static str = "sport-log-action-provider-sportstracker.toml";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    type Config = SportstrackerConfig;
+    type Args = NoArgs;
+    type Error = Error;
+    type UserError = UserError;
+
+    fn create_before() -> Duration {
+        Duration::try_hours(168).unwrap()
     }
 
-    ExitCode::SUCCESS
-}
-
-async fn setup(config: &Config) -> Result<()> {
-    setup_db(
-        &config.server_url,
-        NAME,
-        &config.password,
-        DESCRIPTION,
-        PLATFORM_NAME,
-        true,
-        &[("fetch", "Fetch and save new workouts.")],
-        Duration::try_hours(168).unwrap(),
-        Duration::zero(),
-    )
-    .await?;
-
-    Ok(())
-}
+    fn delete_after() -> Duration {
+        Duration::zero()
+    }
 
-async fn fetch(config: &Config) -> Result<()> {
-    let client = Client::new();
-
-    let exec_action_events = get_events(
-        &client,
-        &config.server_url,
-        NAME,
-        &config.password,
-        Duration::try_hours(0).unwrap(),
-        Duration::try_hours(1).unwrap() + Duration::try_minutes(1).unwrap(),
-    )
-    .await?;
-
-    let mut tasks: Vec<JoinHandle<Result<UserResult<ActionEventId>>>> = vec![];
-    for exec_action_event in exec_action_events {
-        let client = client.clone();
-        let config: Config = config.clone();
-        tasks.push(tokio::spawn(async move {
-            debug!("processing {:#?}", exec_action_event);
-
-            let (Some(username), Some(password)) =
-                (&exec_action_event.username, &exec_action_event.password)
-            else {
-                return Ok(Err(UserError::NoCredential(
-                    exec_action_event.action_event_id,
-                )));
-            };
+    fn event_window() -> (Duration, Duration) {
+        (
+            Duration::try_hours(0).unwrap(),
+            Duration::try_hours(1).unwrap() + Duration::try_minutes(1).unwrap(),
+        )
+    }
 
-            let token = match get_token(
-                &client,
-                username,
-                password,
-                exec_action_event.action_event_id,
-            )
+    async fn new(config: &Config<SportstrackerConfig>, _args: &NoArgs) -> Result<Self> {
+        let last_workout_keys = match fs::read_to_string(&config.provider.state_file).await {
+            Ok(state) => serde_json::from_str(&state)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        let client = Client::new();
+
+        let action_provider: ActionProvider = client
+            .get(route_max_version(
+                &config.server_url,
+                AP_ACTION_PROVIDER,
+                None,
+            ))
+            .basic_auth(Self::NAME, Some(&config.password))
+            .send()
             .await?
-            {
-                Ok(token) => token,
-                Err(error) => return Ok(Err(error)),
-            };
-
-            let token = (token.0, token.1.as_str());
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Self {
+            client,
+            server_url: config.server_url.clone(),
+            password: config.password.clone(),
+            platform_id: action_provider.platform_id,
+            state_file: config.provider.state_file.clone(),
+            last_workout_keys: Mutex::new(last_workout_keys),
+        })
+    }
 
-            let workout_keys = get_workout_keys(&client, &token).await?;
+    async fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> EventResult<UserError, Error> {
+        let (username, password) = credential(exec_action_event);
+
+        let token = match get_token(&self.client, username, password).await? {
+            Ok(token) => token,
+            Err(error) => return Ok(Err(error)),
+        };
+
+        let token = (token.0, token.1.as_str());
+
+        let workout_keys = get_workout_keys(&self.client, &token).await?;
+
+        let user_id = exec_action_event.user_id;
+        let last_workout_key = self
+            .last_workout_keys
+            .lock()
+            .unwrap()
+            .get(&user_id.0)
+            .cloned();
+        let workout_keys = new_workout_keys(workout_keys.payload, last_workout_key.as_deref());
+        if workout_keys.is_empty() {
+            info!("everything up to date for user {}", user_id.0);
+        }
 
-            let movements: Vec<Movement> = client
-                .get(route_max_version(&config.server_url, MOVEMENT, None))
-                .basic_auth(NAME, Some(&config.password))
-                .header(ID_HEADER, exec_action_event.user_id.0)
-                .send()
-                .await?
-                .json::<Vec<Movement>>()
-                .await?
-                .into_iter()
-                .map(|mut movement| {
-                    movement.name.make_ascii_lowercase();
-                    movement.name.retain(|c| !c.is_whitespace() && c != '-');
-                    movement
-                })
-                .collect();
-
-            for workout_key in workout_keys.payload {
-                let workout_stats =
-                    get_workout_stats(&client, &token, &workout_key.workout_key).await?;
-                let workout_track =
-                    get_workout_track(&client, &token, &workout_key.workout_key).await?;
-
-                let Some(cardio_session) = try_into_cardio_session(
-                    workout_stats,
-                    workout_track,
-                    &exec_action_event,
-                    &movements,
-                ) else {
-                    continue;
-                };
-
-                let datetime = cardio_session.datetime.to_rfc3339().replace("+00:00", "Z");
-                // all cardio sessions of user with same datetime
-                let conflicting_cardio_sessions: Vec<CardioSession> = client
-                    .get(route_max_version(
-                        &config.server_url,
-                        CARDIO_SESSION,
-                        Some(&[("start", datetime.as_str()), ("end", datetime.as_str())]),
-                    ))
-                    .basic_auth(NAME, Some(&config.password))
-                    .header(ID_HEADER, exec_action_event.user_id.0)
-                    .send()
-                    .await?
-                    .json()
-                    .await?;
-
-                if conflicting_cardio_sessions
-                    .iter()
-                    .any(|c| c.movement_id == cardio_session.movement_id)
-                {
-                    info!(
-                        "everything up to date for user {}",
-                        exec_action_event.user_id.0
-                    );
-                    break;
+        let mut activity_mapper = ActivityMapper::new(
+            &self.client,
+            &self.server_url,
+            Self::NAME,
+            &self.password,
+            self.platform_id,
+            user_id,
+        )
+        .await?;
+        let mut unmapped_activities: Vec<String> = vec![];
+
+        for workout_key in workout_keys {
+            let workout_stats =
+                get_workout_stats(&self.client, &token, &workout_key.workout_key).await?;
+
+            let activity = activity_key(workout_stats.activity_id);
+            let movement_id = activity_mapper
+                .movement_id(
+                    &self.client,
+                    &self.server_url,
+                    Self::NAME,
+                    &self.password,
+                    &activity,
+                )
+                .await?;
+            let Some(movement_id) = movement_id else {
+                info!(
+                    "activity {activity} of user {} is not mapped to a movement",
+                    user_id.0
+                );
+                if !unmapped_activities.contains(&activity) {
+                    unmapped_activities.push(activity);
                 }
+                continue;
+            };
 
-                let response = client
-                    .post(route_max_version(&config.server_url, CARDIO_SESSION, None))
-                    .basic_auth(NAME, Some(&config.password))
-                    .header(ID_HEADER, exec_action_event.user_id.0)
-                    .json(&cardio_session)
-                    .send()
-                    .await?;
-
-                match response.status() {
-                    status if status.is_success() => {
-                        info!(
-                            "cardio session saved for user {}",
-                            exec_action_event.user_id.0
-                        );
-                    }
-                    _ => {
-                        response.error_for_status()?; // this will always fail and return the error
-                        break;
-                    }
-                }
+            let workout_track =
+                get_workout_track(&self.client, &token, &workout_key.workout_key).await?;
+            let workout_samples =
+                get_workout_samples(&self.client, &token, &workout_key.workout_key).await?;
+
+            let cardio_session = into_cardio_session(
+                workout_stats,
+                workout_track,
+                &workout_samples,
+                exec_action_event,
+                movement_id,
+            );
+            create_cardio_session(
+                &self.client,
+                &self.server_url,
+                Self::NAME,
+                &self.password,
+                &cardio_session,
+            )
+            .await?;
+
+            // workouts after an unmapped one are imported again once the activity is mapped
+            if unmapped_activities.is_empty() {
+                self.last_workout_keys
+                    .lock()
+                    .unwrap()
+                    .insert(user_id.0, workout_key.workout_key);
             }
+        }
+
+        if !unmapped_activities.is_empty() {
+            return Ok(Err(UserError::UnmappedActivities(
+                unmapped_activities.join(", "),
+            )));
+        }
 
-            Ok(Ok(exec_action_event.action_event_id))
-        }));
+        Ok(Ok(None))
     }
 
-    let mut delete_action_event_ids = vec![];
-    for task in tasks {
-        match task.await?? {
-            Ok(action_event_id) => delete_action_event_ids.push(action_event_id),
-            Err(error) => {
-                info!("{error}");
-                delete_action_event_ids.push(error.action_event_id());
-            }
-        }
+    async fn shutdown(self) -> Result<()> {
+        let last_workout_keys = self.last_workout_keys.into_inner().unwrap();
+        fs::write(&self.state_file, serde_json::to_string(&last_workout_keys)?).await?;
+        Ok(())
     }
+}
 
-    if !delete_action_event_ids.is_empty() {
-        disable_events(
-            &client,
-            &config.server_url,
-            NAME,
-            &config.password,
-            &delete_action_event_ids,
-        )
-        .await?;
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing(env!("CARGO_CRATE_NAME"));
+    run::<Sportstracker>().await
+}
+
+/// The keys of all workouts newer than `last_workout_key`, oldest first.
+///
+/// `workout_keys` are ordered newest first.
+/// If `last_workout_key` is not found (for example because the workout was deleted), all workouts are returned.
+fn new_workout_keys(
+    workout_keys: Vec<WorkoutKey>,
+    last_workout_key: Option<&str>,
+) -> Vec<WorkoutKey> {
+    let mut workout_keys: Vec<WorkoutKey> = workout_keys
+        .into_iter()
+        .take_while(|workout_key| Some(workout_key.workout_key.as_str()) != last_workout_key)
+        .collect();
+    workout_keys.reverse();
+    workout_keys
+}
+
+/// The key of the activity in [`ActivityMapping`].
+///
+/// Known activities use their name, all others their sportstracker activity id.
+fn activity_key(activity_id: u32) -> String {
+    match activity_id {
+        1 => "running".to_owned(),
+        2 => "biking".to_owned(),
+        11 => "hiking".to_owned(),
+        22 => "trailrunning".to_owned(),
+        31 => "skitouring".to_owned(),
+        id => id.to_string(),
     }
+}
 
-    Ok(())
+fn rate_samples(samples: &[Sample]) -> Vec<(i32, u32)> {
+    samples
+        .iter()
+        .map(|sample| (sample.t as i32 * 1000, sample.v))
+        .collect()
 }
 
-fn try_into_cardio_session(
+fn into_cardio_session(
     workout_stats: WorkoutStats,
     workout_track: WorkoutTrack,
+    workout_samples: &WorkoutSamples,
     exec_action_event: &ExecutableActionEvent,
-    movements: &[Movement],
-) -> Option<CardioSession> {
-    let movement_name = match workout_stats.activity_id {
-        1 => "running",
-        2 => "biking",
-        11 => "hiking",
-        22 => "trailrunning",
-        31 => "skitouring",
-        id => {
-            info!("failed to create cardio session: unknown activity id {id}");
-            return None;
-        }
+    movement_id: MovementId,
+) -> CardioSession {
+    let cardio_type = if matches!(workout_stats.activity_id, 1 | 22) {
+        CardioType::Training
+    } else {
+        CardioType::Freetime
     };
 
-    let movement_id = movements
-        .iter()
-        .find(|movement| movement.name == movement_name)
-        .map(|movement| movement.id);
+    let time = workout_stats.total_time as i32 * 1000;
 
-    let Some(movement_id) = movement_id else {
-        info!("failed to create cardio session: unknown movement {movement_name}");
-        return None;
-    };
+    let heart_rate = rate_samples_to_times(&rate_samples(&workout_samples.heart_rate), time);
+    let avg_heart_rate = average_rate(&heart_rate, time);
+    let heart_rate = (!heart_rate.is_empty()).then_some(heart_rate);
 
-    let cardio_type = if movement_name == "running" || movement_name == "trailrunning" {
-        CardioType::Training
+    let cadence = rate_samples_to_times(&rate_samples(&workout_samples.cadence), time);
+    let avg_cadence = if cadence.is_empty() {
+        (workout_stats.step_count > 0).then(|| {
+            (f64::from(workout_stats.step_count) / (f64::from(workout_stats.total_time) / 60.))
+                as i32
+        })
     } else {
-        CardioType::Freetime
+        average_rate(&cadence, time)
     };
-
-    let avg_cadence = (workout_stats.step_count > 0).then(|| {
-        (f64::from(workout_stats.step_count) / (f64::from(workout_stats.total_time) / 60.)) as i32
-    });
+    let cadence = (!cadence.is_empty()).then_some(cadence);
 
     let track = workout_track
         .locations
@@ -411,7 +405,7 @@ fn try_into_cardio_session(
         })
         .collect();
 
-    Some(CardioSession {
+    CardioSession {
         id: CardioSessionId(rand::thread_rng().gen()),
         user_id: exec_action_event.user_id,
         movement_id,
@@ -420,24 +414,23 @@ fn try_into_cardio_session(
         distance: Some(workout_stats.total_distance as i32),
         ascent: Some(workout_stats.total_ascent as i32),
         descent: Some(workout_stats.total_descent as i32),
-        time: Some(workout_stats.total_time as i32 * 1000),
+        time: Some(time),
         calories: Some(i32::from(workout_stats.energy_consumption)),
         track: Some(track),
         avg_cadence,
-        cadence: None,
-        avg_heart_rate: None,
-        heart_rate: None,
+        cadence,
+        avg_heart_rate,
+        heart_rate,
         route_id: None,
         comments: workout_stats.description,
         deleted: false,
-    })
+    }
 }
 
 async fn get_token(
     client: &Client,
     username: &str,
     password: &str,
-    action_event_id: ActionEventId,
 ) -> Result<UserResult<(&'static str, String)>> {
     let credentials = [("l", username), ("p", password), ("captchaToken", "0")];
     let user: User = client
@@ -453,7 +446,7 @@ async fn get_token(
     Ok(user
         .session_key
         .map(|key| ("token", key))
-        .ok_or(UserError::LoginFailed(action_event_id)))
+        .ok_or(UserError::LoginFailed))
 }
 
 async fn get_workout_keys(client: &Client, token: &(&str, &str)) -> Result<WorkoutKeys> {
@@ -508,8 +501,140 @@ async fn get_workout_track(
         .payload)
 }
 
+async fn get_workout_samples(
+    client: &Client,
+    token: &(&str, &str),
+    workout_key: &str,
+) -> Result<WorkoutSamples> {
+    let samples = &("samples", "100000");
+
+    Ok(client
+        .get(format!(
+            "https://api.sports-tracker.com/apiserver/v1/workouts/{workout_key}/samples"
+        ))
+        .query(&[token, samples])
+        .send()
+        .await?
+        .json::<WorkoutSamplesWrapper>()
+        .await?
+        .payload)
+}
+
 // workout overview:https://api.sports-tracker.com/apiserver/v1/workouts?token=sessionkey&limited=true&limit=1000000
 // workout stats:   https://api.sports-tracker.com/apiserver/v1/workouts/<workout_key>?token=sessionkey
 // workout data:    https://api.sports-tracker.com/apiserver/v1/workouts/<workout_key>/data?token=sessionkey
+// workout samples: https://api.sports-tracker.com/apiserver/v1/workouts/<workout_key>/samples?token=sessionkey
 // gpx:             https://api.sports-tracker.com/apiserver/v1/workout/exportGpx/<workout_key>?token=sessionkey
 // similar routes:  https://api.sports-tracker.com/apiserver/v1/workouts/similarRoutes/<workout_key>?token=sessionkey
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde::de::DeserializeOwned;
+    use sport_log_types::{ActionEventId, ActionId, UserId};
+
+    use super::*;
+
+    fn fixture<T: DeserializeOwned>(file: &str) -> T {
+        let path = format!("{}/fixtures/{file}", env!("CARGO_MANIFEST_DIR"));
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn exec_action_event() -> ExecutableActionEvent {
+        ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: "fetch".to_owned(),
+            datetime: Utc::now(),
+            arguments: None,
+            attempts: 0,
+            time_zone: "UTC".to_owned(),
+            user_id: UserId(1),
+            username: Some("user".to_owned()),
+            password: Some("password".to_owned()),
+        }
+    }
+
+    fn cardio_session_with_samples(workout_samples: &WorkoutSamples) -> CardioSession {
+        into_cardio_session(
+            fixture::<WorkoutStatsWrapper>("workout_stats.json").payload,
+            fixture::<WorkoutTrackWrapper>("workout_track.json").payload,
+            workout_samples,
+            &exec_action_event(),
+            MovementId(1),
+        )
+    }
+
+    fn cardio_session() -> CardioSession {
+        cardio_session_with_samples(
+            &fixture::<WorkoutSamplesWrapper>("workout_samples.json").payload,
+        )
+    }
+
+    #[test]
+    fn workout_into_cardio_session() {
+        let cardio_session = cardio_session();
+
+        assert_eq!(cardio_session.movement_id, MovementId(1));
+        assert_eq!(cardio_session.datetime.timestamp(), 1_710_400_000);
+        assert_eq!(cardio_session.time, Some(120_000));
+        assert_eq!(cardio_session.distance, Some(400));
+        assert_eq!(cardio_session.track.as_ref().unwrap().len(), 3);
+
+        // 60 s at 120 bpm and 60 s at 150 bpm
+        let heart_rate = cardio_session.heart_rate.unwrap();
+        assert_eq!(heart_rate.len(), 270);
+        assert_eq!(heart_rate[..3], [0, 500, 1000]);
+        assert_eq!(heart_rate[120..123], [60_000, 60_400, 60_800]);
+        assert_eq!(cardio_session.avg_heart_rate, Some(135));
+
+        // no steps for the first 30 s and then 90 s at 160 spm
+        let cadence = cardio_session.cadence.unwrap();
+        assert_eq!(cadence.len(), 240);
+        assert_eq!(cadence[..2], [30_000, 30_375]);
+        assert_eq!(cardio_session.avg_cadence, Some(120));
+    }
+
+    #[test]
+    fn workout_without_samples() {
+        let workout_samples = WorkoutSamples {
+            heart_rate: vec![],
+            cadence: vec![],
+        };
+        let cardio_session = cardio_session_with_samples(&workout_samples);
+
+        assert!(cardio_session.heart_rate.is_none());
+        assert!(cardio_session.avg_heart_rate.is_none());
+        assert!(cardio_session.cadence.is_none());
+        // 340 steps in 2 min
+        assert_eq!(cardio_session.avg_cadence, Some(170));
+    }
+
+    #[test]
+    fn only_new_workout_keys() {
+        let workout_keys = || fixture::<WorkoutKeys>("workout_keys.json").payload;
+        let keys = |workout_keys: Vec<WorkoutKey>| {
+            workout_keys
+                .into_iter()
+                .map(|workout_key| workout_key.workout_key)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            keys(new_workout_keys(workout_keys(), None)),
+            [
+                "65d2f0c6e4b0a1b2c3d4e5f1",
+                "65e8a1b7e4b0a1b2c3d4e5f2",
+                "65f1c2a8e4b0a1b2c3d4e5f3"
+            ]
+        );
+        assert_eq!(
+            keys(new_workout_keys(
+                workout_keys(),
+                Some("65e8a1b7e4b0a1b2c3d4e5f2")
+            )),
+            ["65f1c2a8e4b0a1b2c3d4e5f3"]
+        );
+        assert!(new_workout_keys(workout_keys(), Some("65f1c2a8e4b0a1b2c3d4e5f3")).is_empty());
+    }
+}
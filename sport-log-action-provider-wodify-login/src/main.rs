@@ -1,80 +1,76 @@
-use std::{
-    env, fs,
-    io::Error as IoError,
-    process::{ExitCode, Stdio},
-    result::Result as StdResult,
-    time::Duration as StdDuration,
-};
-
-use chrono::{DateTime, Duration, Local, Utc};
-use clap::Parser;
-use reqwest::{Client, Error as ReqwestError};
+use std::{process::ExitCode, result::Result as StdResult, time::Duration as StdDuration};
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use clap::Args as ClapArgs;
+use reqwest::Error as ReqwestError;
 use serde::Deserialize;
-use sport_log_ap_utils::{disable_events, get_events, setup as setup_db};
-use sport_log_types::{ActionEventId, ExecutableActionEvent};
-use sysinfo::System;
-use thirtyfour::{error::WebDriverError, prelude::*, WebDriver};
+use sport_log_ap_utils::{credential, init_tracing, run, Config, EventResult, Provider};
+use sport_log_types::ExecutableActionEvent;
+use sport_log_wodify::{Error as WodifyError, Geckodriver, ReservationState, WodifyClient};
 use thiserror::Error;
-use tokio::{process::Command, task::JoinError, time};
-use tracing::{debug, error, info, warn};
-use tracing_subscriber::EnvFilter;
-
-const CONFIG_FILE: &str = "sport-log-action-provider-wodify-login.toml";
-const NAME: &str = "wodify-login";
-const DESCRIPTION: &str =
-    "Wodify Login can reserve spots in classes. The action names correspond to the class types.";
-const PLATFORM_NAME: &str = "wodify";
-
-const GECKODRIVER: &str = "geckodriver";
-const WEBDRIVER_ADDRESS: &str = "http://localhost:4444/";
+use tokio::time;
+use tracing::info;
+
+/// The number of times the schedule is loaded to find the class after the reservation opened.
+const ATTEMPTS: u32 = 3;
+const RETRY_INTERVAL: StdDuration = StdDuration::from_secs(1);
+
+const WAITLIST: &str = "Waitlist";
+const CANCEL: &str = "Cancel";
+const RESERVATION_STATE: &str = "Reservation State";
+
+/// The arguments of the actions that take the class type as argument instead of as action name.
+const CLASS_ARGUMENT_SCHEMA: &str = r#"{
+    "type": "object",
+    "properties": {
+        "class": {
+            "type": "string",
+            "minLength": 1,
+            "description": "The class type, e.g. CrossFit. The class starts at the time of the action event."
+        }
+    },
+    "required": ["class"],
+    "additionalProperties": false
+}"#;
 
 #[derive(Debug, Error)]
 enum Error {
     #[error("{0}")]
     Reqwest(#[from] ReqwestError),
     #[error("{0}")]
-    Io(#[from] IoError),
-    #[error("{0}")]
-    WebDriver(#[from] WebDriverError),
-    #[error("{0}")]
-    Join(#[from] JoinError),
+    Wodify(#[from] WodifyError),
 }
 
 type Result<T> = StdResult<T, Error>;
 
 #[derive(Debug, Error)]
 enum UserError {
-    #[error("can not log in: no credentials provided")]
-    NoCredential(ActionEventId),
     #[error("can not log in: login failed")]
-    LoginFailed(ActionEventId),
-    #[error("{1} class at {2} not found within timeout")]
-    ClassNotFound(ActionEventId, String, DateTime<Utc>),
-}
-
-impl UserError {
-    fn action_event_id(&self) -> ActionEventId {
-        match self {
-            Self::NoCredential(action_event_id)
-            | Self::LoginFailed(action_event_id)
-            | Self::ClassNotFound(action_event_id, ..) => *action_event_id,
-        }
-    }
+    LoginFailed,
+    #[error("{0} class at {1} not found within timeout")]
+    ClassNotFound(String, DateTime<Utc>),
+    #[error("invalid arguments: the class type is missing")]
+    InvalidArguments,
+    #[error("can not cancel {0} class at {1}: no reservation or waitlist spot")]
+    NotReserved(String, DateTime<Utc>),
 }
 
 type UserResult<T> = StdResult<T, UserError>;
 
-/// The config for [`sport-log-action-provider-wodify-login`](crate).
-///
-/// The name of the config file is specified in [`CONFIG_FILE`].
-///
-/// `admin_password` is the password for the admin endpoints.
-///
-/// `server_url` is the left part of the URL (everything before `/<version>/...`)
+/// What an action event does with its class.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operation {
+    Reserve,
+    /// Reserve a spot or join the waitlist if the class is full.
+    Waitlist,
+    Cancel,
+    ReservationState,
+}
+
 #[derive(Deserialize, Debug)]
-struct Config {
-    password: String,
-    server_url: String,
+struct ClassArguments {
+    class: String,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -83,308 +79,383 @@ enum Mode {
     Interactive,
 }
 
-/// Wodify Login Action Provider
-#[derive(Parser, Debug)]
-#[command( about, long_about = None)]
+#[derive(ClapArgs, Debug)]
 struct Args {
-    /// create own actions
-    #[arg(short, long)]
-    setup: bool,
-
     /// use interactive webdriver session (with browser window)
     #[arg(short, long)]
     interactive: bool,
 }
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    if env::var("RUST_LOG").is_err() {
-        if cfg!(debug_assertions) {
-            env::set_var(
-                "RUST_LOG",
-                "info,sport_log_action_provider_wodify_login=debug",
-            );
-        } else {
-            env::set_var(
-                "RUST_LOG",
-                "warn,sport_log_action_provider_wodify_login=info",
-            );
-        }
-    }
+/// Wodify Login Action Provider
+struct WodifyLogin {
+    geckodriver: Geckodriver,
+    mode: Mode,
+}
 
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+impl Provider for WodifyLogin {
+    const NAME: &'static str = "wodify-login";
+    const DESCRIPTION: &'static str =
+        "Wodify Login can reserve spots in classes, join waitlists, cancel reservations and report the reservation state. The names of the reservation actions correspond to the class types.";
+    const PLATFORM_NAME: &'static str = "wodify";
+    const CREDENTIAL: bool = true;
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[
+        ("CrossFit", "Reserve a spot in a CrossFit class.", None),
+        (
+            "Weightlifting",
+            "Reserve a spot in a Weightlifting class.",
+            None,
+        ),
+        (
+            "Open Fridge",
+            "Reserve a spot in a Open Fridge class.",
+            None,
+        ),
+        ("Open Gym", "Reserve a spot in a Open Gym class.", None),
+        ("Gymnastics", "Reserve a spot in a Gymnastics class.", None),
+        ("Strongmen", "Reserve a spot in a Strongmen class.", None),
+        ("Yoga", "Reserve a spot in a Yoga class.", None),
+        ("Swim WOD", "Reserve a spot in a Swim class.", None),
+        (
+            WAITLIST,
+            "Reserve a spot in a class or join the waitlist if the class is full.",
+            Some(CLASS_ARGUMENT_SCHEMA),
+        ),
+        (
+            CANCEL,
+            "Cancel the reservation or leave the waitlist of a class.",
+            Some(CLASS_ARGUMENT_SCHEMA),
+        ),
+        (
+            RESERVATION_STATE,
+            "Report whether a spot in a class is reserved, waitlisted, available or the class is full.",
+            Some(CLASS_ARGUMENT_SCHEMA),
+        ),
+    ];
+    // every event waits until the reservation opens, so all events of the next day have to run at the same time
+    const MAX_CONCURRENT_EVENTS: usize = 32;
+    const CONFIG_FILE: &'static str = "sport-log-action-provider-wodify-login.toml";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    type Config = ();
+    type Args = Args;
+    type Error = Error;
+    type UserError = UserError;
+
+    fn create_before() -> Duration {
+        Duration::try_hours(168).unwrap()
+    }
 
-    let args = Args::parse();
+    fn delete_after() -> Duration {
+        Duration::zero()
+    }
 
-    let config_file = match fs::read_to_string(CONFIG_FILE) {
-        Ok(file) => file,
-        Err(error) => {
-            error!("failed to read {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
-    let config = match toml::from_str(&config_file) {
-        Ok(config) => config,
-        Err(error) => {
-            error!("failed to parse {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
+    fn event_window() -> (Duration, Duration) {
+        (
+            Duration::zero(),
+            Duration::try_days(1).unwrap() + Duration::try_minutes(2).unwrap(),
+        )
+    }
 
-    if args.setup {
-        if let Err(error) = setup(&config).await {
-            warn!("setup failed: {error}");
-        }
-    } else {
+    async fn new(_config: &Config<()>, args: &Args) -> Result<Self> {
         let mode = if args.interactive {
             Mode::Interactive
         } else {
             Mode::Headless
         };
-        if let Err(error) = login(&config, mode).await {
-            warn!("login failed: {error}");
-        }
-    }
-
-    ExitCode::SUCCESS
-}
-
-async fn setup(config: &Config) -> Result<()> {
-    setup_db(
-        &config.server_url,
-        NAME,
-        &config.password,
-        DESCRIPTION,
-        PLATFORM_NAME,
-        true,
-        &[
-            ("CrossFit", "Reserve a spot in a CrossFit class."),
-            ("Weightlifting", "Reserve a spot in a Weightlifting class."),
-            ("Open Fridge", "Reserve a spot in a Open Fridge class."),
-            ("Open Gym", "Reserve a spot in a Open Gym class."),
-            ("Gymnastics", "Reserve a spot in a Gymnastics class."),
-            ("Strongmen", "Reserve a spot in a Strongmen class."),
-            ("Yoga", "Reserve a spot in a Yoga class."),
-            ("Swim WOD", "Reserve a spot in a Swim class."),
-        ],
-        Duration::try_hours(168).unwrap(),
-        Duration::zero(),
-    )
-    .await?;
-
-    Ok(())
-}
-
-async fn login(config: &Config, mode: Mode) -> Result<()> {
-    let client = Client::new();
-
-    let exec_action_events = get_events(
-        &client,
-        &config.server_url,
-        NAME,
-        &config.password,
-        Duration::zero(),
-        Duration::try_days(1).unwrap() + Duration::try_minutes(2).unwrap(),
-    )
-    .await?;
-
-    if exec_action_events.is_empty() {
-        return Ok(());
-    }
-
-    for p in System::new_all().processes_by_name(GECKODRIVER) {
-        p.kill();
-    }
-
-    let mut webdriver = Command::new(GECKODRIVER)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
 
-    time::sleep(StdDuration::from_secs(1)).await; // make sure geckodriver is available
+        let geckodriver = Geckodriver::start(mode == Mode::Headless).await?;
 
-    let mut caps = DesiredCapabilities::firefox();
-    if mode == Mode::Headless {
-        caps.set_headless()?;
+        Ok(Self { geckodriver, mode })
     }
 
-    let mut tasks = vec![];
-    for exec_action_event in exec_action_events {
-        let caps = caps.clone();
+    async fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> EventResult<UserError, Error> {
+        let (username, password) = credential(exec_action_event);
 
-        tasks.push(tokio::spawn(async move {
-            debug!("processing {:#?}", exec_action_event);
+        let client = self.geckodriver.client().await?;
 
-            let (Some(username), Some(password)) =
-                (&exec_action_event.username, &exec_action_event.password)
-            else {
-                return Ok(Err(UserError::NoCredential(
-                    exec_action_event.action_event_id,
-                )));
-            };
+        let result = wodify_login(&client, username, password, exec_action_event).await;
 
-            let driver = WebDriver::new(WEBDRIVER_ADDRESS, caps).await?;
+        if self.mode == Mode::Interactive {
+            time::sleep(StdDuration::from_secs(3)).await;
+        }
+        client.quit().await?;
 
-            let result = wodify_login(&driver, username, password, &exec_action_event, mode).await;
+        result
+    }
 
-            debug!("closing browser");
-            driver.quit().await?;
+    async fn shutdown(self) -> Result<()> {
+        self.geckodriver.kill().await?;
 
-            result
-        }));
+        Ok(())
     }
+}
 
-    let mut disable_action_event_ids = vec![];
-    for task in tasks {
-        match task.await?? {
-            Ok(action_event_id) => disable_action_event_ids.push(action_event_id),
-            Err(error) => {
-                info!("{error}");
-                disable_action_event_ids.push(error.action_event_id());
-            }
-        }
-    }
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing(env!("CARGO_CRATE_NAME"));
+    run::<WodifyLogin>().await
+}
 
-    if !disable_action_event_ids.is_empty() {
-        disable_events(
-            &client,
-            &config.server_url,
-            NAME,
-            &config.password,
-            &disable_action_event_ids,
-        )
-        .await?;
-    }
+/// Get the class type and the operation of the action event.
+///
+/// The reservation actions are named after the class type, all other actions get the class type as argument.
+fn class_and_operation(
+    exec_action_event: &ExecutableActionEvent,
+) -> UserResult<(String, Operation)> {
+    let operation = match exec_action_event.action_name.as_str() {
+        WAITLIST => Operation::Waitlist,
+        CANCEL => Operation::Cancel,
+        RESERVATION_STATE => Operation::ReservationState,
+        class => return Ok((class.to_owned(), Operation::Reserve)),
+    };
 
-    debug!("terminating webdriver");
-    webdriver.kill().await?;
+    let arguments: ClassArguments = exec_action_event
+        .arguments
+        .as_deref()
+        .and_then(|arguments| serde_json::from_str(arguments).ok())
+        .ok_or(UserError::InvalidArguments)?;
 
-    Ok(())
+    Ok((arguments.class, operation))
 }
 
 async fn wodify_login(
-    driver: &WebDriver,
+    client: &impl WodifyClient,
     username: &str,
     password: &str,
     exec_action_event: &ExecutableActionEvent,
-    mode: Mode,
-) -> Result<UserResult<ActionEventId>> {
-    let time = exec_action_event
-        .datetime
-        .with_timezone(&Local)
-        .format("%-H:%M")
-        .to_string();
-    let date = exec_action_event.datetime.format("%m/%d/%Y").to_string();
-
-    driver.delete_all_cookies().await?;
-    driver.goto("https://app.wodify.com").await?;
-
-    time::sleep(StdDuration::from_secs(3)).await;
-
-    driver
-        .find(By::Id("Input_UserName"))
-        .await?
-        .send_keys(username)
-        .await?;
-    driver
-        .find(By::Id("Input_Password"))
-        .await?
-        .send_keys(password)
-        .await?;
-    driver
-        .find(By::ClassName("signin-btn"))
-        .await?
-        .click()
-        .await?;
-
-    time::sleep(StdDuration::from_secs(5)).await;
-
-    if driver.find(By::LinkText("Logout")).await.is_err() {
-        return Ok(Err(UserError::LoginFailed(
-            exec_action_event.action_event_id,
-        )));
-    }
-    debug!("login successful");
+) -> Result<UserResult<Option<String>>> {
+    let (class_type, operation) = match class_and_operation(exec_action_event) {
+        Ok(class_and_operation) => class_and_operation,
+        Err(error) => return Ok(Err(error)),
+    };
+    // the schedule shows the classes in the local time of the gym
+    let tz = exec_action_event.time_zone.parse().unwrap_or(Tz::UTC);
+    let start = exec_action_event.datetime.with_timezone(&tz).naive_local();
 
-    driver
-        .goto("https://app.wodify.com/Schedule/CalendarListViewEntry.aspx")
-        .await?;
+    if !client.login(username, password).await? {
+        return Ok(Err(UserError::LoginFailed));
+    }
 
-    if let Ok(duration) =
-        (exec_action_event.datetime - Duration::try_days(1).unwrap() - Utc::now()).to_std()
-    {
-        time::sleep(duration).await;
+    // cancellations and the reservation state do not have to wait until the reservation opens
+    if matches!(operation, Operation::Reserve | Operation::Waitlist) {
+        if let Ok(duration) =
+            (exec_action_event.datetime - Duration::try_days(1).unwrap() - Utc::now()).to_std()
+        {
+            time::sleep(duration).await;
+        }
+        info!("ready"); // info for timing purposes
     }
-    info!("ready"); // info for timing purposes
 
-    for _ in 0..3 {
-        driver.refresh().await?;
+    let mut class = None;
+    for attempt in 0..ATTEMPTS {
+        if attempt > 0 {
+            time::sleep(RETRY_INTERVAL).await;
+        }
+
+        class = client.find_class(&class_type, start).await?;
         info!("reload done"); // info for timing purposes
 
-        let table = driver
-            .find(By::ClassName("TableRecords"))
-            .await?
-            .find(By::Tag("tbody"))
-            .await?;
-
-        let rows = table.find_all(By::Tag("tr")).await?;
-
-        let mut start_row_number = rows.len();
-        for (i, row) in rows.iter().enumerate() {
-            if let Ok(day) = row
-                .find(By::XPath("./td[1]/span[contains(@class, \"h3\")]"))
-                .await
-            {
-                if day.inner_html().await?.contains(&date) {
-                    start_row_number = i + 1;
-                    break;
-                }
-            }
+        if class.is_some() {
+            break;
         }
+    }
+    let Some(class) = class else {
+        return Ok(Err(UserError::ClassNotFound(
+            class_type,
+            exec_action_event.datetime,
+        )));
+    };
 
-        let mut end_row_number = rows.len();
-        for (i, row) in rows[start_row_number..].iter().enumerate() {
-            if row
-                .find(By::XPath("./td[1]/span[contains(@class, \"h3\")]"))
-                .await
-                .is_ok()
-            {
-                end_row_number = start_row_number + i;
-                break;
+    let message = match operation {
+        Operation::Reserve => {
+            client.reserve(&class).await?;
+            info!("reservation for {} done", exec_action_event.datetime);
+            None
+        }
+        Operation::Waitlist => match client.reservation_state(&class).await? {
+            ReservationState::Available => {
+                client.reserve(&class).await?;
+                info!("reservation for {} done", exec_action_event.datetime);
+                Some(format!("reserved a spot in {}", class.name))
+            }
+            ReservationState::Full => {
+                client.join_waitlist(&class).await?;
+                info!("joined waitlist for {}", exec_action_event.datetime);
+                Some(format!("joined the waitlist of {}", class.name))
             }
+            state => Some(format!("{} is already {state}", class.name)),
+        },
+        Operation::Cancel => match client.reservation_state(&class).await? {
+            ReservationState::Reserved | ReservationState::Waitlisted => {
+                client.cancel(&class).await?;
+                info!("cancellation for {} done", exec_action_event.datetime);
+                Some(format!("cancelled {}", class.name))
+            }
+            ReservationState::Available | ReservationState::Full => {
+                return Ok(Err(UserError::NotReserved(
+                    class_type,
+                    exec_action_event.datetime,
+                )));
+            }
+        },
+        Operation::ReservationState => {
+            let state = client.reservation_state(&class).await?;
+            Some(format!("{}: {state}", class.name))
         }
+    };
 
-        for row in &rows[start_row_number..end_row_number] {
-            let row_matches = row
-                .find(By::XPath("./td[1]/div/span"))
-                .await?
-                .attr("title")
-                .await?
-                .map_or(false, |title| {
-                    title.contains(&exec_action_event.action_name) && title.contains(&time)
-                });
-
-            if row_matches {
-                let icon = row.find(By::XPath("./td[3]/div")).await?;
-                icon.scroll_into_view().await?;
-                icon.click().await?;
-                info!("reservation for {} done", exec_action_event.datetime);
+    Ok(Ok(message))
+}
 
-                if mode == Mode::Interactive {
-                    time::sleep(StdDuration::from_secs(3)).await;
-                }
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Vienna;
+    use sport_log_types::{argument_schema::ArgumentSchema, ActionEventId, ActionId, UserId};
+    use sport_log_wodify::{fake::FakeWodify, Class};
 
-                return Ok(Ok(exec_action_event.action_event_id));
-            }
+    use super::*;
+
+    fn exec_action_event(datetime: DateTime<Utc>, password: &str) -> ExecutableActionEvent {
+        class_action_event("CrossFit", None, datetime, password)
+    }
+
+    fn class_action_event(
+        action_name: &str,
+        arguments: Option<&str>,
+        datetime: DateTime<Utc>,
+        password: &str,
+    ) -> ExecutableActionEvent {
+        ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: action_name.to_owned(),
+            datetime,
+            arguments: arguments.map(ToOwned::to_owned),
+            attempts: 0,
+            time_zone: "Europe/Vienna".to_owned(),
+            user_id: UserId(1),
+            username: Some("athlete".to_owned()),
+            password: Some(password.to_owned()),
         }
     }
 
-    Ok(Err(UserError::ClassNotFound(
-        exec_action_event.action_event_id,
-        exec_action_event.action_name.clone(),
-        exec_action_event.datetime,
-    )))
+    /// Run the action event with a new client like [`WodifyLogin::handle`].
+    async fn run_event(
+        wodify: &FakeWodify,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> UserResult<Option<String>> {
+        let (username, password) = credential(exec_action_event);
+        wodify_login(&wodify.client(), username, password, exec_action_event)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reserve_class() {
+        let wodify = FakeWodify::new();
+        wodify.add_user("athlete", "password");
+        // the reservation is already open
+        let datetime = Utc::now() + Duration::try_hours(2).unwrap();
+        wodify.state().classes.push(Class {
+            id: "42".to_owned(),
+            name: "CrossFit - Coach".to_owned(),
+            start: datetime.with_timezone(&Vienna).naive_local(),
+        });
+
+        let result = run_event(&wodify, &exec_action_event(datetime, "wrong")).await;
+        assert!(matches!(result, Err(UserError::LoginFailed)));
+
+        let result = run_event(&wodify, &exec_action_event(datetime, "password")).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            wodify.state().reservations,
+            [("athlete".to_owned(), "42".to_owned())]
+        );
+
+        let later = datetime + Duration::try_hours(1).unwrap();
+        let result = run_event(&wodify, &exec_action_event(later, "password")).await;
+        assert!(matches!(result, Err(UserError::ClassNotFound(_, _))));
+        assert_eq!(wodify.state().reservations.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn waitlist_and_cancel() {
+        assert!(ArgumentSchema::parse(CLASS_ARGUMENT_SCHEMA).is_ok());
+
+        let wodify = FakeWodify::new();
+        wodify.add_user("athlete", "password");
+        let datetime = Utc::now() + Duration::try_hours(2).unwrap();
+        wodify.state().classes.push(Class {
+            id: "42".to_owned(),
+            name: "CrossFit - Coach".to_owned(),
+            start: datetime.with_timezone(&Vienna).naive_local(),
+        });
+
+        let arguments = Some(r#"{"class": "CrossFit"}"#);
+        let handle = |action_name, arguments| {
+            let exec_action_event =
+                class_action_event(action_name, arguments, datetime, "password");
+            let wodify = &wodify;
+            async move { run_event(wodify, &exec_action_event).await }
+        };
+
+        let result = handle(WAITLIST, None).await;
+        assert!(matches!(result, Err(UserError::InvalidArguments)));
+        let result = handle(CANCEL, Some(r#"{"class": "Yoga"}"#)).await;
+        assert!(matches!(result, Err(UserError::ClassNotFound(_, _))));
+
+        let result = handle(RESERVATION_STATE, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("CrossFit - Coach: available")
+        );
+
+        // the class is not full, so a spot is reserved
+        let result = handle(WAITLIST, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("reserved a spot in CrossFit - Coach")
+        );
+        assert_eq!(wodify.state().reservations.len(), 1);
+        let result = handle(RESERVATION_STATE, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("CrossFit - Coach: reserved")
+        );
+
+        let result = handle(CANCEL, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("cancelled CrossFit - Coach")
+        );
+        assert!(wodify.state().reservations.is_empty());
+        let result = handle(CANCEL, arguments).await;
+        assert!(matches!(result, Err(UserError::NotReserved(_, _))));
+
+        wodify.state().capacities.push(("42".to_owned(), 0));
+        let result = handle(RESERVATION_STATE, arguments).await;
+        assert_eq!(result.unwrap().as_deref(), Some("CrossFit - Coach: full"));
+
+        let result = handle(WAITLIST, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("joined the waitlist of CrossFit - Coach")
+        );
+        assert_eq!(
+            wodify.state().waitlists,
+            [("athlete".to_owned(), "42".to_owned())]
+        );
+        let result = handle(WAITLIST, arguments).await;
+        assert_eq!(
+            result.unwrap().as_deref(),
+            Some("CrossFit - Coach is already waitlisted")
+        );
+
+        let result = handle(CANCEL, arguments).await;
+        assert!(result.is_ok());
+        assert!(wodify.state().waitlists.is_empty());
+    }
 }
@@ -1,396 +1,188 @@
-use std::{
-    env, fs,
-    io::Error as IoError,
-    process::{ExitCode, Stdio},
-    result::Result as StdResult,
-    time::Duration as StdDuration,
-};
+use std::{process::ExitCode, result::Result as StdResult, time::Duration as StdDuration};
 
 use chrono::Duration;
-use clap::Parser;
+use chrono_tz::Tz;
+use clap::Args as ClapArgs;
 use rand::Rng;
-use regex::Regex;
 use reqwest::{Client, Error as ReqwestError, StatusCode};
-use serde::Deserialize;
-use sport_log_ap_utils::{disable_events, get_events, setup as setup_db};
+use sport_log_ap_utils::{credential, init_tracing, run, Config, EventResult, Provider};
 use sport_log_types::{
-    uri::{route_max_version, WOD},
-    ActionEventId, ExecutableActionEvent, Wod, WodId, ID_HEADER,
+    uri::{route_max_version, METCON, METCON_DRAFT, METCON_MOVEMENT, METCON_SESSION, WOD},
+    wod_parser::{MetconDraft, WodText},
+    ExecutableActionEvent, Metcon, MetconSession, MetconSessionId, MetconType, UserId, Wod, WodId,
+    ID_HEADER,
 };
-use sysinfo::System;
-use thirtyfour::{error::WebDriverError, prelude::*, WebDriver};
+use sport_log_wodify::{Error as WodifyError, Geckodriver, MetconResult, WodifyClient};
 use thiserror::Error;
-use tokio::{process::Command, task::JoinError, time};
-use tracing::{debug, error, info, warn};
-use tracing_subscriber::EnvFilter;
-
-const CONFIG_FILE: &str = "sport-log-action-provider-wodify-wod.toml";
-const NAME: &str = "wodify-wod";
-const DESCRIPTION: &str =
-    "Wodify Wod can fetch the Workout of the Day and save it in your wods. The action names correspond to the class type the wod should be fetched for.";
-const PLATFORM_NAME: &str = "wodify";
-
-const GECKODRIVER: &str = "geckodriver";
-const WEBDRIVER_ADDRESS: &str = "http://localhost:4444/";
+use tokio::time;
+use tracing::info;
 
 #[derive(Debug, Error)]
 enum Error {
     #[error("{0}")]
     Reqwest(#[from] ReqwestError),
     #[error("{0}")]
-    Io(#[from] IoError),
-    #[error("{0}")]
-    WebDriver(#[from] WebDriverError),
-    #[error("{0}")]
-    Join(#[from] JoinError),
+    Wodify(#[from] WodifyError),
 }
 
 type Result<T> = StdResult<T, Error>;
 
 #[derive(Debug, Error)]
 enum UserError {
-    #[error("can not log in: no credentials provided")]
-    NoCredential(ActionEventId),
     #[error("can not log in: login failed")]
-    LoginFailed(ActionEventId),
+    LoginFailed,
     #[error("no wod found")]
-    WodNotFound(ActionEventId),
+    WodNotFound,
     #[error("no wod result found")]
-    ResultNotFound(ActionEventId),
-}
-
-impl UserError {
-    fn action_event_id(&self) -> ActionEventId {
-        match self {
-            Self::NoCredential(action_event_id)
-            | Self::LoginFailed(action_event_id)
-            | Self::WodNotFound(action_event_id)
-            | Self::ResultNotFound(action_event_id) => *action_event_id,
-        }
-    }
+    ResultNotFound,
 }
 
 type UserResult<T> = StdResult<T, UserError>;
 
-/// The config for [`sport-log-action-provider-wodify-wod`](crate).
-///
-/// The name of the config file is specified in [`CONFIG_FILE`].
-///
-/// `admin_password` is the password for the admin endpoints.
-///
-/// `server_url` is the left part of the URL (everything before `/<version>/...`)
-#[derive(Deserialize, Debug, Clone)]
-struct Config {
-    password: String,
-    server_url: String,
-}
-
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum Mode {
     Headless,
     Interactive,
 }
 
-/// Wodify Wod Action Provider
-#[derive(Parser, Debug)]
-#[command( about, long_about = None)]
+#[derive(ClapArgs, Debug)]
 struct Args {
-    /// create own actions
-    #[arg(short, long)]
-    setup: bool,
-
     /// use interactive webdriver session (with browser window)
     #[arg(short, long)]
     interactive: bool,
 }
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    if env::var("RUST_LOG").is_err() {
-        if cfg!(debug_assertions) {
-            env::set_var(
-                "RUST_LOG",
-                "info,sport_log_action_provider_wodify_wod=debug",
-            );
-        } else {
-            env::set_var("RUST_LOG", "warn,sport_log_action_provider_wodify_wod=info");
-        }
-    }
+/// Wodify Wod Action Provider
+struct WodifyWod {
+    config: Config<()>,
+    client: Client,
+    geckodriver: Geckodriver,
+    mode: Mode,
+}
 
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+impl Provider for WodifyWod {
+    const NAME: &'static str = "wodify-wod";
+    const DESCRIPTION: &'static str =
+        "Wodify Wod can fetch the Workout of the Day and save it in your wods. The action names correspond to the class type the wod should be fetched for.";
+    const PLATFORM_NAME: &'static str = "wodify";
+    const CREDENTIAL: bool = true;
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] = &[(
+        "Metcon",
+        "Fetch and save the metcon description and results for the current day.",
+        None,
+    )];
+    const MAX_ATTEMPTS: i32 = 8; // the result is often entered hours after the class
+    const CONFIG_FILE: &'static str = "sport-log-action-provider-wodify-wod.toml";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    type Config = ();
+    type Args = Args;
+    type Error = Error;
+    type UserError = UserError;
+
+    fn create_before() -> Duration {
+        Duration::try_hours(168).unwrap()
+    }
 
-    let args = Args::parse();
+    fn delete_after() -> Duration {
+        Duration::try_hours(24).unwrap()
+    }
 
-    let config_file = match fs::read_to_string(CONFIG_FILE) {
-        Ok(file) => file,
-        Err(error) => {
-            error!("failed to read {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
-    let config = match toml::from_str(&config_file) {
-        Ok(config) => config,
-        Err(error) => {
-            error!("failed to parse {CONFIG_FILE}: {error}");
-            return ExitCode::FAILURE;
-        }
-    };
+    fn event_window() -> (Duration, Duration) {
+        (Duration::try_days(-1).unwrap(), Duration::zero())
+    }
 
-    if args.setup {
-        if let Err(error) = setup(&config).await {
-            warn!("setup failed: {error}");
-        }
-    } else {
+    async fn new(config: &Config<()>, args: &Args) -> Result<Self> {
         let mode = if args.interactive {
             Mode::Interactive
         } else {
             Mode::Headless
         };
-        if let Err(error) = get_wod(&config, mode).await {
-            warn!("fetching wod failed: {error}");
-        }
-    }
-
-    ExitCode::SUCCESS
-}
 
-async fn setup(config: &Config) -> Result<()> {
-    setup_db(
-        &config.server_url,
-        NAME,
-        &config.password,
-        DESCRIPTION,
-        PLATFORM_NAME,
-        true,
-        &[(
-            "Metcon",
-            "Fetch and save the metcon description and results for the current day.",
-        )],
-        Duration::try_hours(168).unwrap(),
-        Duration::try_hours(24).unwrap(),
-    )
-    .await?;
+        let geckodriver = Geckodriver::start(mode == Mode::Headless).await?;
 
-    Ok(())
-}
-
-async fn get_wod(config: &Config, mode: Mode) -> Result<()> {
-    let client = Client::new();
-
-    let exec_action_events = get_events(
-        &client,
-        &config.server_url,
-        NAME,
-        &config.password,
-        Duration::try_days(-1).unwrap(),
-        Duration::zero(),
-    )
-    .await?;
-
-    if exec_action_events.is_empty() {
-        return Ok(());
+        Ok(Self {
+            config: config.clone(),
+            client: Client::new(),
+            geckodriver,
+            mode,
+        })
     }
 
-    for p in System::new_all().processes_by_name(GECKODRIVER) {
-        p.kill();
-    }
-
-    let mut webdriver = Command::new(GECKODRIVER)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
+    async fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> EventResult<UserError, Error> {
+        let (username, password) = credential(exec_action_event);
+
+        let wodify = self.geckodriver.client().await?;
+
+        let result = try_create_wod(
+            &self.config,
+            &wodify,
+            &self.client,
+            username,
+            password,
+            exec_action_event,
+        )
+        .await;
 
-    time::sleep(StdDuration::from_secs(1)).await; // make sure geckodriver is available
+        if self.mode == Mode::Interactive {
+            time::sleep(StdDuration::from_secs(3)).await;
+        }
+        wodify.quit().await?;
 
-    let mut caps = DesiredCapabilities::firefox();
-    if mode == Mode::Headless {
-        caps.set_headless()?;
+        result
     }
 
-    let mut tasks = vec![];
-    for exec_action_event in exec_action_events {
-        let caps = caps.clone();
-        let client = client.clone();
-        let config = config.clone();
-        tasks.push(tokio::spawn(async move {
-            debug!("processing: {:#?}", exec_action_event);
-
-            let (Some(username), Some(password)) =
-                (&exec_action_event.username, &exec_action_event.password)
-            else {
-                return Ok(Err(UserError::NoCredential(
-                    exec_action_event.action_event_id,
-                )));
-            };
-
-            let driver = WebDriver::new(WEBDRIVER_ADDRESS, caps).await?;
-
-            let result = try_create_wod(
-                &config,
-                &driver,
-                &client,
-                username,
-                password,
-                &exec_action_event,
-                mode,
-            )
-            .await;
-
-            debug!("closing browser");
-            driver.quit().await?;
-
-            result
-        }));
+    fn is_transient(error: &UserError) -> bool {
+        matches!(error, UserError::ResultNotFound)
     }
 
-    let mut disable_action_event_ids = vec![];
-    for task in tasks {
-        match task.await?? {
-            Ok(action_event_id) => disable_action_event_ids.push(action_event_id),
-            Err(error) => {
-                info!("{error}");
-                if let UserError::ResultNotFound(_) = error {
-                    info!("trying again on next invocation");
-                } else {
-                    disable_action_event_ids.push(error.action_event_id());
-                }
-            }
-        }
-    }
+    async fn shutdown(self) -> Result<()> {
+        self.geckodriver.kill().await?;
 
-    if !disable_action_event_ids.is_empty() {
-        disable_events(
-            &client,
-            &config.server_url,
-            NAME,
-            &config.password,
-            &disable_action_event_ids,
-        )
-        .await?;
+        Ok(())
     }
+}
 
-    debug!("terminating webdriver");
-    webdriver.kill().await?;
-
-    Ok(())
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing(env!("CARGO_CRATE_NAME"));
+    run::<WodifyWod>().await
 }
 
 async fn try_create_wod(
-    config: &Config,
-    driver: &WebDriver,
+    config: &Config<()>,
+    wodify: &impl WodifyClient,
     client: &Client,
     username: &str,
     password: &str,
     exec_action_event: &ExecutableActionEvent,
-    mode: Mode,
-) -> Result<UserResult<ActionEventId>> {
-    let action_date = exec_action_event.datetime.date_naive();
-    let action_date_string = action_date.format("%m/%d/%Y").to_string();
-
-    driver.delete_all_cookies().await?;
-    driver.goto("https://app.wodify.com").await?;
-
-    time::sleep(StdDuration::from_secs(3)).await;
-
-    driver
-        .find(By::Id("Input_UserName"))
-        .await?
-        .send_keys(username)
-        .await?;
-    driver
-        .find(By::Id("Input_Password"))
-        .await?
-        .send_keys(password)
-        .await?;
-    driver
-        .find(By::ClassName("signin-btn"))
-        .await?
-        .click()
-        .await?;
-
-    time::sleep(StdDuration::from_secs(5)).await;
+) -> Result<UserResult<Option<String>>> {
+    let tz = exec_action_event.time_zone.parse().unwrap_or(Tz::UTC);
+    let action_date = exec_action_event.datetime.with_timezone(&tz).date_naive();
 
-    if driver.find(By::LinkText("Logout")).await.is_err() {
-        return Ok(Err(UserError::LoginFailed(
-            exec_action_event.action_event_id,
-        )));
+    if !wodify.login(username, password).await? {
+        return Ok(Err(UserError::LoginFailed));
     }
-    debug!("login successful");
-
-    driver
-        .goto("https://app.wodify.com/WOD/WODEntry.aspx")
-        .await?;
-
-    let date_input = driver.find(By::Id("AthleteTheme_wtLayoutNormal_block_wtSubNavigation_W_Utils_UI_wt3_block_wtDateInputFrom")).await?;
-    date_input.clear().await?;
-    date_input.send_keys(&action_date_string).await?;
-
-    time::sleep(StdDuration::from_secs(3)).await;
 
-    let Ok(wod) = driver
-        .find(By::Id(
-            "AthleteTheme_wtLayoutNormal_block_wtMainContent_WOD_UI_wt9_block_wtWODComponentsList",
-        ))
-        .await
-    else {
-        return Ok(Err(UserError::WodNotFound(
-            exec_action_event.action_event_id,
-        )));
+    let Some(wod) = wodify.wod(action_date).await? else {
+        return Ok(Err(UserError::WodNotFound));
     };
+    let name = wod.name;
+    let content = wod.description;
 
-    let name = wod
-        .find(By::ClassName("component_name"))
-        .await?
-        .inner_html()
-        .await?;
-
-    let content = wod
-        .find(By::ClassName("component_wrapper"))
-        .await?
-        .inner_html()
-        .await?;
-
-    let name = parse_inner_html(&name);
-    let content = parse_inner_html(&content);
-
-    driver
-        .goto("https://app.wodify.com/Performance/MyPerformance_Metcon.aspx")
-        .await?;
-
-    let Ok(result_table) = driver.find(By::ClassName("TableRecords")).await else {
-        return Ok(Err(UserError::ResultNotFound(
-            exec_action_event.action_event_id,
-        )));
+    let Some(metcon_result) = wodify.metcon_result(action_date).await? else {
+        return Ok(Err(UserError::ResultNotFound));
     };
-    let result_entry = result_table
-        .find(By::Tag("tbody"))
-        .await?
-        .find(By::Tag("tr"))
-        .await?
-        .find_all(By::Tag("td"))
-        .await?;
-
-    let date = result_entry[0].inner_html().await?;
-    if date != action_date_string {
-        return Ok(Err(UserError::ResultNotFound(
-            exec_action_event.action_event_id,
-        )));
-    }
-    let result = result_entry[6].inner_html().await?;
-    let rx = result_entry[7]
-        .find(By::ClassName("RxOnNoClick"))
-        .await
-        .is_ok();
-    let comments = parse_inner_html(&result_entry[9].inner_html().await?);
 
     let description = format!(
-        "{name}\n{content}\n\nResult: {result} {}{}",
-        if rx { "RX" } else { "Scaled" },
-        if !comments.is_empty() {
-            "\nComments: ".to_owned() + comments.as_str()
+        "{name}\n{content}\n\nResult: {} {}{}",
+        metcon_result.result,
+        if metcon_result.rx { "RX" } else { "Scaled" },
+        if !metcon_result.comments.is_empty() {
+            "\nComments: ".to_owned() + metcon_result.comments.as_str()
         } else {
             String::new()
         }
@@ -406,7 +198,7 @@ async fn try_create_wod(
 
     let response = client
         .post(route_max_version(&config.server_url, WOD, None))
-        .basic_auth(NAME, Some(&config.password))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
         .header(ID_HEADER, exec_action_event.user_id.0)
         .json(&wod)
         .send()
@@ -420,24 +212,410 @@ async fn try_create_wod(
         }
     }
 
-    if mode == Mode::Interactive {
-        time::sleep(StdDuration::from_secs(3)).await;
+    let metcon_name = format!("{name} ({action_date})");
+    let metcon = match find_metcon(
+        config,
+        client,
+        exec_action_event.user_id,
+        &[&name, &metcon_name],
+    )
+    .await?
+    {
+        Some(metcon) => Some(metcon),
+        None => {
+            create_metcon(
+                config,
+                client,
+                exec_action_event.user_id,
+                WodText {
+                    name: metcon_name,
+                    description: format!("{name}\n{content}"),
+                },
+            )
+            .await?
+        }
+    };
+
+    if let Some(metcon) = metcon {
+        create_metcon_session(config, client, exec_action_event, &metcon, &metcon_result).await?;
+    } else {
+        info!("result not saved as metcon session because there is no metcon");
+    }
+
+    Ok(Ok(None))
+}
+
+/// Find a metcon of the user or a public metcon by name.
+///
+/// The names are tried in order, so the first name has the highest priority.
+async fn find_metcon(
+    config: &Config<()>,
+    client: &Client,
+    user_id: UserId,
+    names: &[&str],
+) -> Result<Option<Metcon>> {
+    let metcons: Vec<Metcon> = client
+        .get(route_max_version(&config.server_url, METCON, None))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
+        .header(ID_HEADER, user_id.0)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let metcon = names.iter().find_map(|name| {
+        metcons
+            .iter()
+            .find(|metcon| !metcon.deleted && metcon.name == *name)
+    });
+    if let Some(metcon) = metcon {
+        info!("using existing metcon {}", metcon.name);
+    }
+
+    Ok(metcon.cloned())
+}
+
+/// Parse the wod into a metcon and create the metcon with its movements.
+///
+/// Wods that can not be parsed into a metcon are skipped.
+async fn create_metcon(
+    config: &Config<()>,
+    client: &Client,
+    user_id: UserId,
+    wod_text: WodText,
+) -> Result<Option<Metcon>> {
+    let response = client
+        .post(route_max_version(&config.server_url, METCON_DRAFT, None))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
+        .header(ID_HEADER, user_id.0)
+        .json(&wod_text)
+        .send()
+        .await?;
+
+    if response.status() == StatusCode::BAD_REQUEST {
+        info!("wod could not be parsed into a metcon");
+        return Ok(None);
+    }
+    let draft: MetconDraft = response.error_for_status()?.json().await?;
+    if !draft.unresolved.is_empty() {
+        info!("unresolved movements: {:?}", draft.unresolved);
+    }
+
+    let response = client
+        .post(route_max_version(&config.server_url, METCON, None))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
+        .header(ID_HEADER, user_id.0)
+        .json(&draft.metcon)
+        .send()
+        .await?;
+
+    match response.status() {
+        StatusCode::CONFLICT => {
+            info!("metcon already exists");
+            return find_metcon(config, client, user_id, &[&draft.metcon.name]).await;
+        }
+        StatusCode::OK => info!("new metcon created"),
+        _ => {
+            response.error_for_status()?; // this will always fail and return the error
+        }
+    }
+
+    if !draft.metcon_movements.is_empty() {
+        client
+            .post(route_max_version(&config.server_url, METCON_MOVEMENT, None))
+            .basic_auth(WodifyWod::NAME, Some(&config.password))
+            .header(ID_HEADER, user_id.0)
+            .json(&draft.metcon_movements)
+            .send()
+            .await?
+            .error_for_status()?;
+    }
+
+    Ok(Some(draft.metcon))
+}
+
+/// Save the result as metcon session of the metcon unless the user already has a session of the metcon at the time of the action event.
+///
+/// Results that can not be parsed or do not match the [`MetconType`] of the metcon are skipped.
+async fn create_metcon_session(
+    config: &Config<()>,
+    client: &Client,
+    exec_action_event: &ExecutableActionEvent,
+    metcon: &Metcon,
+    metcon_result: &MetconResult,
+) -> Result<()> {
+    let Some(score) = metcon_result.score() else {
+        info!("result {} could not be parsed", metcon_result.result);
+        return Ok(());
+    };
+
+    // plain reps are the reps of round 0 for metcons scored by rounds and reps
+    let rounds = match metcon.metcon_type {
+        MetconType::Amrap | MetconType::ForTime if score.time.is_none() => score.rounds.or(Some(0)),
+        _ => score.rounds,
+    };
+
+    let metcon_session = MetconSession {
+        id: MetconSessionId(rand::thread_rng().gen()),
+        user_id: exec_action_event.user_id,
+        metcon_id: metcon.id,
+        datetime: exec_action_event.datetime,
+        time: score.time,
+        rounds,
+        reps: score.reps,
+        rx: metcon_result.rx,
+        comments: (!metcon_result.comments.is_empty()).then(|| metcon_result.comments.clone()),
+        deleted: false,
+    };
+    if let Err(error) = metcon_session.validate(metcon) {
+        info!(
+            "result {} does not fit the metcon: {error}",
+            metcon_result.result
+        );
+        return Ok(());
+    }
+
+    let datetime = exec_action_event
+        .datetime
+        .to_rfc3339()
+        .replace("+00:00", "Z");
+    let metcon_sessions: Vec<MetconSession> = client
+        .get(route_max_version(
+            &config.server_url,
+            METCON_SESSION,
+            Some(&[("start", datetime.as_str()), ("end", datetime.as_str())]),
+        ))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
+        .header(ID_HEADER, exec_action_event.user_id.0)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if metcon_sessions
+        .iter()
+        .any(|existing| !existing.deleted && existing.metcon_id == metcon.id)
+    {
+        info!("metcon session already exists");
+        return Ok(());
     }
 
-    Ok(Ok(exec_action_event.action_event_id))
+    client
+        .post(route_max_version(&config.server_url, METCON_SESSION, None))
+        .basic_auth(WodifyWod::NAME, Some(&config.password))
+        .header(ID_HEADER, exec_action_event.user_id.0)
+        .json(&metcon_session)
+        .send()
+        .await?
+        .error_for_status()?;
+    info!("new metcon session created");
+
+    Ok(())
 }
 
-fn parse_inner_html(inner_html: &str) -> String {
-    //static re: LazyCell<Regex> = LazyCell::new(|| Regex::new(r"</*.+?>").unwrap()); // TODO needs LazyCell stabilization
-    let content = inner_html
-        .replace("<br>", "\n")
-        .replace("<p>", "\n")
-        .replace("&nbsp;", " ");
-    Regex::new(r"</*.+?>")
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use sport_log_ap_utils::mock_server::MockServer;
+    use sport_log_types::{
+        ActionEventId, ActionId, ActionProvider, ActionProviderId, ExecutableActionEvent, MetconId,
+        PlatformId,
+    };
+    use sport_log_wodify::{fake::FakeWodify, MetconResult, WodDescription};
+
+    use super::*;
+
+    const PASSWORD: &str = "wodify-wod-password";
+
+    /// Run the action event with a new client like [`WodifyWod::handle`].
+    async fn run_event(
+        config: &Config<()>,
+        wodify: &FakeWodify,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> UserResult<Option<String>> {
+        let (username, password) = credential(exec_action_event);
+        try_create_wod(
+            config,
+            &wodify.client(),
+            &Client::new(),
+            username,
+            password,
+            exec_action_event,
+        )
+        .await
         .unwrap()
-        .replace_all(&content, "")
-        .replace("\n\n", "\n")
-        .replace("\n ", "\n")
-        .trim()
-        .to_owned()
+    }
+
+    #[tokio::test]
+    async fn create_wod_from_wodify() {
+        let server = MockServer::start().await;
+        server.state().action_providers.push(ActionProvider {
+            id: ActionProviderId(1),
+            name: WodifyWod::NAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            description: None,
+            deleted: false,
+        });
+        let wodify = FakeWodify::new();
+        wodify.add_user("athlete", "password");
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        wodify.state().wods.push((
+            date,
+            WodDescription {
+                name: "Fran".to_owned(),
+                description: "21-15-9\nThrusters\nPull-Ups".to_owned(),
+            },
+        ));
+
+        let config = Config {
+            password: PASSWORD.to_owned(),
+            server_url: server.url().to_owned(),
+            run_interval_minutes: 60,
+            provider: (),
+        };
+        let exec_action_event = ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: "Metcon".to_owned(),
+            datetime: date.and_hms_opt(17, 0, 0).unwrap().and_utc(),
+            arguments: None,
+            attempts: 0,
+            time_zone: "UTC".to_owned(),
+            user_id: UserId(1),
+            username: Some("athlete".to_owned()),
+            password: Some("password".to_owned()),
+        };
+
+        // the result is entered later
+        let result = run_event(&config, &wodify, &exec_action_event).await;
+        assert!(matches!(result, Err(UserError::ResultNotFound)));
+        assert!(server.state().wods.is_empty());
+
+        wodify.state().metcon_results.push((
+            "athlete".to_owned(),
+            date,
+            MetconResult {
+                result: "4:12".to_owned(),
+                rx: true,
+                comments: "unbroken".to_owned(),
+            },
+        ));
+
+        let result = run_event(&config, &wodify, &exec_action_event).await;
+        assert!(result.is_ok());
+        {
+            let state = server.state();
+            assert_eq!(state.wods.len(), 1);
+            assert_eq!(state.wods[0].date, date);
+            assert_eq!(
+                state.wods[0].description.as_deref(),
+                Some("Fran\n21-15-9\nThrusters\nPull-Ups\n\nResult: 4:12 RX\nComments: unbroken")
+            );
+            assert_eq!(state.metcons.len(), 1);
+            assert_eq!(state.metcons[0].name, "Fran (2026-10-19)");
+            assert_eq!(state.metcon_sessions.len(), 1);
+            let metcon_session = &state.metcon_sessions[0];
+            assert_eq!(metcon_session.metcon_id, state.metcons[0].id);
+            assert_eq!(metcon_session.datetime, exec_action_event.datetime);
+            assert_eq!(metcon_session.time, Some(252_000));
+            assert_eq!(metcon_session.rounds, None);
+            assert!(metcon_session.rx);
+            assert_eq!(metcon_session.comments.as_deref(), Some("unbroken"));
+        }
+
+        // running the action again does not duplicate the metcon or the session
+        let result = run_event(&config, &wodify, &exec_action_event).await;
+        assert!(result.is_ok());
+        assert_eq!(server.state().metcons.len(), 1);
+        assert_eq!(server.state().metcon_sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn use_existing_metcon() {
+        let server = MockServer::start().await;
+        server.state().action_providers.push(ActionProvider {
+            id: ActionProviderId(1),
+            name: WodifyWod::NAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            description: None,
+            deleted: false,
+        });
+        let metcon = Metcon {
+            id: MetconId(1),
+            user_id: Some(UserId(1)),
+            name: "Cindy".to_owned(),
+            metcon_type: MetconType::Amrap,
+            rounds: None,
+            timecap: Some(1_200_000),
+            work_time: None,
+            rest_time: None,
+            rep_scheme: None,
+            description: None,
+            deleted: false,
+        };
+        server.state().metcons.push(metcon.clone());
+
+        let wodify = FakeWodify::new();
+        wodify.add_user("athlete", "password");
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        wodify.state().wods.push((
+            date,
+            WodDescription {
+                name: "Cindy".to_owned(),
+                description: "20 min AMRAP\n5 Pull-Ups\n10 Push-Ups\n15 Air Squats".to_owned(),
+            },
+        ));
+        wodify.state().metcon_results.push((
+            "athlete".to_owned(),
+            date,
+            MetconResult {
+                result: "18 rds + 7 reps".to_owned(),
+                rx: false,
+                comments: String::new(),
+            },
+        ));
+
+        let config = Config {
+            password: PASSWORD.to_owned(),
+            server_url: server.url().to_owned(),
+            run_interval_minutes: 60,
+            provider: (),
+        };
+        let exec_action_event = ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id: ActionId(1),
+            action_name: "Metcon".to_owned(),
+            // 01:00 on the day of the wod in Vienna
+            datetime: date
+                .pred_opt()
+                .unwrap()
+                .and_hms_opt(23, 0, 0)
+                .unwrap()
+                .and_utc(),
+            arguments: None,
+            attempts: 0,
+            time_zone: "Europe/Vienna".to_owned(),
+            user_id: UserId(1),
+            username: Some("athlete".to_owned()),
+            password: Some("password".to_owned()),
+        };
+
+        let result = run_event(&config, &wodify, &exec_action_event).await;
+        assert!(result.is_ok());
+
+        let state = server.state();
+        assert_eq!(state.metcons.len(), 1);
+        assert_eq!(state.metcon_sessions.len(), 1);
+        let metcon_session = &state.metcon_sessions[0];
+        assert_eq!(metcon_session.metcon_id, metcon.id);
+        assert_eq!(metcon_session.time, None);
+        assert_eq!(metcon_session.rounds, Some(18));
+        assert_eq!(metcon_session.reps, Some(7));
+        assert!(!metcon_session.rx);
+        assert_eq!(metcon_session.comments, None);
+    }
 }
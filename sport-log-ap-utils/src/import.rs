@@ -0,0 +1,407 @@
+//! Helpers for action providers that import sessions from other platforms.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use reqwest::{Client, Error};
+use sport_log_types::{
+    uri::{route_max_version, ACTIVITY_MAPPING, CARDIO_SESSION, MOVEMENT},
+    ActivityMapping, ActivityMappingId, CardioSession, Movement, MovementId, PlatformId, UserId,
+    ID_HEADER,
+};
+use tracing::info;
+
+/// Maps the activities of a platform to movements using the [`ActivityMappings`](ActivityMapping) of a user.
+#[derive(Debug)]
+pub struct ActivityMapper {
+    platform_id: PlatformId,
+    user_id: UserId,
+    activity_mappings: Vec<ActivityMapping>,
+    movements: Vec<Movement>,
+}
+
+impl ActivityMapper {
+    /// Fetch the activity mappings of the user for the platform and the movements of the user.
+    pub async fn new(
+        client: &Client,
+        base_url: &str,
+        name: &str,
+        password: &str,
+        platform_id: PlatformId,
+        user_id: UserId,
+    ) -> Result<Self, Error> {
+        let activity_mappings: Vec<ActivityMapping> = client
+            .get(route_max_version(base_url, ACTIVITY_MAPPING, None))
+            .basic_auth(name, Some(&password))
+            .header(ID_HEADER, user_id.0)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let movements: Vec<Movement> = client
+            .get(route_max_version(base_url, MOVEMENT, None))
+            .basic_auth(name, Some(&password))
+            .header(ID_HEADER, user_id.0)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Self {
+            platform_id,
+            user_id,
+            activity_mappings: activity_mappings
+                .into_iter()
+                .filter(|activity_mapping| {
+                    !activity_mapping.deleted && activity_mapping.platform_id == platform_id
+                })
+                .collect(),
+            movements,
+        })
+    }
+
+    /// Get the movement the user mapped `activity` to.
+    ///
+    /// If the activity has no mapping yet, a mapping is created so that the user can edit it.
+    /// The new mapping uses the movement with the same name as the activity (ignoring case, whitespace and dashes) if there is one
+    /// and is left unmapped otherwise.
+    pub async fn movement_id(
+        &mut self,
+        client: &Client,
+        base_url: &str,
+        name: &str,
+        password: &str,
+        activity: &str,
+    ) -> Result<Option<MovementId>, Error> {
+        if let Some(activity_mapping) = self
+            .activity_mappings
+            .iter()
+            .find(|activity_mapping| activity_mapping.activity == activity)
+        {
+            return Ok(activity_mapping.movement_id);
+        }
+
+        let activity_mapping = ActivityMapping {
+            id: ActivityMappingId(rand::thread_rng().gen()),
+            user_id: self.user_id,
+            platform_id: self.platform_id,
+            activity: activity.to_owned(),
+            movement_id: self
+                .movements
+                .iter()
+                .find(|movement| normalize_name(&movement.name) == normalize_name(activity))
+                .map(|movement| movement.id),
+            deleted: false,
+        };
+
+        client
+            .post(route_max_version(base_url, ACTIVITY_MAPPING, None))
+            .basic_auth(name, Some(&password))
+            .header(ID_HEADER, self.user_id.0)
+            .json(&activity_mapping)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        info!(
+            "activity mapping for {activity} created for user {}",
+            self.user_id.0
+        );
+
+        let movement_id = activity_mapping.movement_id;
+        self.activity_mappings.push(activity_mapping);
+        Ok(movement_id)
+    }
+
+    /// Get a movement of the user or a public movement.
+    pub fn movement(&self, movement_id: MovementId) -> Option<&Movement> {
+        self.movements
+            .iter()
+            .find(|movement| movement.id == movement_id)
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    let mut name = name.to_ascii_lowercase();
+    name.retain(|c| !c.is_whitespace() && c != '-');
+    name
+}
+
+fn end_time(cardio_session: &CardioSession) -> DateTime<Utc> {
+    cardio_session.datetime
+        + Duration::try_milliseconds(i64::from(cardio_session.time.unwrap_or(0))).unwrap()
+}
+
+/// Whether the two cardio sessions overlap in time.
+///
+/// Cardio sessions without a time are treated as a single point in time.
+fn overlaps(cardio_session1: &CardioSession, cardio_session2: &CardioSession) -> bool {
+    cardio_session1.datetime <= end_time(cardio_session2)
+        && cardio_session2.datetime <= end_time(cardio_session1)
+}
+
+/// Create the cardio session unless the user already has a cardio session that overlaps with it.
+///
+/// Returns whether the cardio session was created.
+pub async fn create_cardio_session(
+    client: &Client,
+    base_url: &str,
+    name: &str,
+    password: &str,
+    cardio_session: &CardioSession,
+) -> Result<bool, Error> {
+    let user_id = cardio_session.user_id.0;
+
+    // cardio sessions are filtered by their start, so sessions that started up to a day earlier are included
+    let start = (cardio_session.datetime - Duration::try_days(1).unwrap())
+        .to_rfc3339()
+        .replace("+00:00", "Z");
+    let end = end_time(cardio_session).to_rfc3339().replace("+00:00", "Z");
+    let cardio_sessions: Vec<CardioSession> = client
+        .get(route_max_version(
+            base_url,
+            CARDIO_SESSION,
+            Some(&[("start", start.as_str()), ("end", end.as_str())]),
+        ))
+        .basic_auth(name, Some(&password))
+        .header(ID_HEADER, user_id)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if cardio_sessions
+        .iter()
+        .any(|existing| overlaps(existing, cardio_session))
+    {
+        info!("cardio session already exists for user {user_id}");
+        return Ok(false);
+    }
+
+    client
+        .post(route_max_version(base_url, CARDIO_SESSION, None))
+        .basic_auth(name, Some(&password))
+        .header(ID_HEADER, user_id)
+        .json(cardio_session)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    info!("cardio session saved for user {user_id}");
+
+    Ok(true)
+}
+
+/// Convert samples of beats or steps per minute into the times in milliseconds since the start of every single beat or step
+/// like they are stored in `heart_rate` and `cadence` of [`CardioSession`].
+///
+/// `samples` are pairs of the time in milliseconds since the start and the rate.
+/// Every sample is valid until the next one, the last one until `total_time` (in milliseconds).
+pub fn rate_samples_to_times(samples: &[(i32, u32)], total_time: i32) -> Vec<i32> {
+    let mut times = vec![];
+    let mut time = 0.;
+    for (i, &(start, rate)) in samples.iter().enumerate() {
+        let end = samples.get(i + 1).map_or(total_time, |next| next.0);
+        time = f64::max(time, f64::from(start));
+        if rate == 0 {
+            continue;
+        }
+        let interval = 60_000. / f64::from(rate);
+        while time < f64::from(end) {
+            times.push(time as i32);
+            time += interval;
+        }
+    }
+    times
+}
+
+/// The average number of beats or steps per minute of the times returned by [`rate_samples_to_times`].
+pub fn average_rate(times: &[i32], total_time: i32) -> Option<i32> {
+    let rate = (times.len() as f64 / (f64::from(total_time) / 60_000.)).round() as i32;
+    (rate > 0).then_some(rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use sport_log_types::{
+        ActionProvider, ActionProviderId, CardioSessionId, CardioType, MovementDimension,
+    };
+
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    const NAME: &str = "import-ap";
+    const PASSWORD: &str = "import-ap-password";
+
+    async fn start_server() -> MockServer {
+        let server = MockServer::start().await;
+        server.state().action_providers.push(ActionProvider {
+            id: ActionProviderId(1),
+            name: NAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            platform_id: PlatformId(1),
+            description: None,
+            deleted: false,
+        });
+        server
+    }
+
+    #[tokio::test]
+    async fn map_activities_to_movements() {
+        let server = start_server().await;
+        server.add_movement(Movement {
+            id: MovementId(1),
+            user_id: None,
+            name: "Trail Running".to_owned(),
+            description: None,
+            movement_dimension: MovementDimension::Distance,
+            cardio: true,
+            deleted: false,
+        });
+        let client = Client::new();
+        let user_id = UserId(1);
+
+        // new activities are mapped by name or surfaced unmapped
+        let mut mapper = ActivityMapper::new(
+            &client,
+            server.url(),
+            NAME,
+            PASSWORD,
+            PlatformId(1),
+            user_id,
+        )
+        .await
+        .unwrap();
+        for (activity, movement_id) in [
+            ("trailrunning", Some(MovementId(1))),
+            ("biking", None),
+            ("99", None),
+        ] {
+            assert_eq!(
+                mapper
+                    .movement_id(&client, server.url(), NAME, PASSWORD, activity)
+                    .await
+                    .unwrap(),
+                movement_id
+            );
+        }
+        assert_eq!(server.state().activity_mappings.len(), 3);
+
+        // the user maps biking to trail running
+        server.state().activity_mappings[1].movement_id = Some(MovementId(1));
+
+        let mut mapper = ActivityMapper::new(
+            &client,
+            server.url(),
+            NAME,
+            PASSWORD,
+            PlatformId(1),
+            user_id,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            mapper
+                .movement_id(&client, server.url(), NAME, PASSWORD, "biking")
+                .await
+                .unwrap(),
+            Some(MovementId(1))
+        );
+        assert_eq!(server.state().activity_mappings.len(), 3);
+
+        // the user deletes the biking mapping to reset it
+        server.state().activity_mappings[1].deleted = true;
+
+        let mut mapper = ActivityMapper::new(
+            &client,
+            server.url(),
+            NAME,
+            PASSWORD,
+            PlatformId(1),
+            user_id,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            mapper
+                .movement_id(&client, server.url(), NAME, PASSWORD, "biking")
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(server.state().activity_mappings.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn skip_overlapping_cardio_sessions() {
+        let server = start_server().await;
+        let client = Client::new();
+
+        let cardio_session = CardioSession {
+            id: CardioSessionId(1),
+            user_id: UserId(1),
+            movement_id: MovementId(1),
+            cardio_type: CardioType::Training,
+            datetime: Utc::now(),
+            distance: None,
+            ascent: None,
+            descent: None,
+            time: Some(3_600_000),
+            calories: None,
+            track: None,
+            avg_cadence: None,
+            cadence: None,
+            avg_heart_rate: None,
+            heart_rate: None,
+            route_id: None,
+            comments: None,
+            deleted: false,
+        };
+        server.state().cardio_sessions.push(cardio_session.clone());
+
+        // recorded with another device, starts 10 s later and has no exactly matching datetime
+        let overlapping = CardioSession {
+            id: CardioSessionId(2),
+            datetime: cardio_session.datetime + Duration::try_seconds(10).unwrap(),
+            ..cardio_session.clone()
+        };
+        let created = create_cardio_session(&client, server.url(), NAME, PASSWORD, &overlapping)
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(server.state().cardio_sessions.len(), 1);
+
+        let later = CardioSession {
+            id: CardioSessionId(3),
+            datetime: cardio_session.datetime + Duration::try_hours(2).unwrap(),
+            ..cardio_session
+        };
+        let created = create_cardio_session(&client, server.url(), NAME, PASSWORD, &later)
+            .await
+            .unwrap();
+        assert!(created);
+        assert_eq!(server.state().cardio_sessions.len(), 2);
+    }
+
+    #[test]
+    fn rate_samples() {
+        // 60 s at 120 bpm and 60 s at 150 bpm
+        let times = rate_samples_to_times(&[(0, 120), (60_000, 150)], 120_000);
+        assert_eq!(times.len(), 270);
+        assert_eq!(times[..3], [0, 500, 1000]);
+        assert_eq!(times[120..123], [60_000, 60_400, 60_800]);
+        assert_eq!(average_rate(&times, 120_000), Some(135));
+
+        // no steps for the first 30 s and then 90 s at 160 spm
+        let times = rate_samples_to_times(&[(0, 0), (30_000, 160)], 120_000);
+        assert_eq!(times.len(), 240);
+        assert_eq!(times[..2], [30_000, 30_375]);
+        assert_eq!(average_rate(&times, 120_000), Some(120));
+
+        assert!(rate_samples_to_times(&[], 120_000).is_empty());
+        assert_eq!(average_rate(&[], 120_000), None);
+    }
+}
@@ -1,18 +1,38 @@
 //! Collection of functions for use in action providers.
+//!
+//! New action providers should implement [`Provider`] and use [`run`] instead of calling these functions directly.
+
+mod import;
+#[cfg(any(test, feature = "mock-server"))]
+pub mod mock_server;
+mod provider;
 
 use chrono::{Duration, SecondsFormat, Utc};
 use rand::Rng;
 use reqwest::{Client, Error, StatusCode};
 use sport_log_types::{
     uri::{
-        route_max_version, AP_ACTION, AP_ACTION_EVENT, AP_ACTION_PROVIDER,
-        AP_EXECUTABLE_ACTION_EVENT, AP_PLATFORM,
+        route_max_version, AP_ACTION, AP_ACTION_EVENT, AP_ACTION_EVENT_RESULT, AP_ACTION_PROVIDER,
+        AP_EXECUTABLE_ACTION_EVENT, AP_HEARTBEAT, AP_PLATFORM,
     },
-    Action, ActionEventId, ActionId, ActionProvider, ActionProviderId, ExecutableActionEvent,
-    Platform, PlatformId,
+    Action, ActionEventId, ActionEventResult, ActionEventResultId, ActionEventStatus, ActionId,
+    ActionProvider, ActionProviderHeartbeat, ActionProviderId, ExecutableActionEvent, Platform,
+    PlatformId,
 };
 use tracing::{debug, error, info};
 
+pub use crate::import::{
+    average_rate, create_cardio_session, rate_samples_to_times, ActivityMapper,
+};
+pub use crate::provider::{
+    credential, execute, init_tracing, run, setup_provider, Config, EventResult, NoArgs, Provider,
+};
+
+/// Register the platform, the action provider and its actions on the server.
+///
+/// Setup is idempotent and can be run again after the definitions changed:
+/// the `credential` flag of the platform is updated,
+/// new actions are created, changed actions are updated and actions that are no longer listed are deleted.
 #[allow(clippy::too_many_arguments)]
 pub async fn setup(
     server_url: &str,
@@ -21,9 +41,10 @@ pub async fn setup(
     description: &str,
     platform_name: &str,
     credential: bool,
-    actions: &[(&str, &str)],
+    actions: &[(&str, &str, Option<&str>)],
     create_before: Duration,
     delete_after: Duration,
+    max_attempts: i32,
 ) -> Result<(), Error> {
     let client = Client::new();
 
@@ -42,10 +63,10 @@ pub async fn setup(
         .send()
         .await?;
 
-    let platform_id = match response.status() {
+    let platform = match response.status() {
         StatusCode::OK => {
             info!("platform created");
-            platform.id
+            platform
         }
         StatusCode::CONFLICT => {
             info!("platform already exists");
@@ -55,19 +76,18 @@ pub async fn setup(
                 .await?
                 .json()
                 .await?;
-            let platform = platforms
+            platforms
                 .into_iter()
                 .find(|platform| platform.name == platform_name)
-                .expect("platform name already exists but server response contains no platform with this name");
-            platform.id
+                .expect("platform name already exists but server response contains no platform with this name")
         }
         StatusCode::FORBIDDEN => {
             error!("action provider self registration is disabled");
-            response.json::<Platform>().await?.id // this will always fail and return the error
+            response.json::<Platform>().await? // this will always fail and return the error
         }
         status => {
             error!("an error occurred (status {status})");
-            response.json::<Platform>().await?.id // this will always fail and return the error
+            response.json::<Platform>().await? // this will always fail and return the error
         }
     };
 
@@ -75,7 +95,7 @@ pub async fn setup(
         id: ActionProviderId(rng.gen()),
         name: name.to_owned(),
         password: password.to_owned(),
-        platform_id,
+        platform_id: platform.id,
         description: Some(description.to_owned()),
         deleted: false,
     };
@@ -99,6 +119,7 @@ pub async fn setup(
                 .basic_auth(name, Some(&password))
                 .send()
                 .await?
+                .error_for_status()?
                 .json()
                 .await?;
             action_provider.id
@@ -113,32 +134,103 @@ pub async fn setup(
         }
     };
 
-    let actions: Vec<Action> = actions
-        .iter()
-        .map(|action| Action {
+    if platform.credential != credential {
+        client
+            .put(route_max_version(server_url, AP_PLATFORM, None))
+            .basic_auth(name, Some(&password))
+            .json(&Platform {
+                credential,
+                ..platform
+            })
+            .send()
+            .await?
+            .error_for_status()?;
+        info!("platform updated");
+    }
+
+    let existing_actions: Vec<Action> = client
+        .get(route_max_version(server_url, AP_ACTION, None))
+        .basic_auth(name, Some(&password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let existing_actions: Vec<Action> = existing_actions
+        .into_iter()
+        .filter(|action| !action.deleted)
+        .collect();
+
+    let mut new_actions = vec![];
+    let mut changed_actions = vec![];
+    for &(action_name, action_description, argument_schema) in actions {
+        let action = Action {
             id: ActionId(rng.gen()),
-            name: action.0.to_owned(),
+            name: action_name.to_owned(),
             action_provider_id,
-            description: Some(action.1.to_owned()),
+            description: Some(action_description.to_owned()),
             create_before: create_before.num_milliseconds() as i32,
             delete_after: delete_after.num_milliseconds() as i32,
+            max_attempts,
+            argument_schema: argument_schema.map(ToOwned::to_owned),
             deleted: false,
-        })
+        };
+        match existing_actions.iter().find(|a| a.name == action.name) {
+            None => new_actions.push(action),
+            Some(existing) => {
+                if existing.description != action.description
+                    || existing.create_before != action.create_before
+                    || existing.delete_after != action.delete_after
+                    || existing.max_attempts != action.max_attempts
+                    || existing.argument_schema != action.argument_schema
+                {
+                    changed_actions.push(Action {
+                        id: existing.id,
+                        ..action
+                    });
+                }
+            }
+        }
+    }
+    let removed_action_ids: Vec<ActionId> = existing_actions
+        .iter()
+        .filter(|existing| !actions.iter().any(|action| action.0 == existing.name))
+        .map(|existing| existing.id)
         .collect();
 
-    match client
-        .post(route_max_version(server_url, AP_ACTION, None))
-        .basic_auth(name, Some(&password))
-        .json(&actions)
-        .send()
-        .await?
-        .status()
-    {
-        StatusCode::OK => info!("action created\nsetup successful"),
-        StatusCode::CONFLICT => info!("action already exists\nsetup successful"),
-        status => error!("an error occurred (status {status})"),
+    if !new_actions.is_empty() {
+        client
+            .post(route_max_version(server_url, AP_ACTION, None))
+            .basic_auth(name, Some(&password))
+            .json(&new_actions)
+            .send()
+            .await?
+            .error_for_status()?;
+        info!("{} actions created", new_actions.len());
+    }
+    if !changed_actions.is_empty() {
+        client
+            .put(route_max_version(server_url, AP_ACTION, None))
+            .basic_auth(name, Some(&password))
+            .json(&changed_actions)
+            .send()
+            .await?
+            .error_for_status()?;
+        info!("{} actions updated", changed_actions.len());
+    }
+    if !removed_action_ids.is_empty() {
+        client
+            .delete(route_max_version(server_url, AP_ACTION, None))
+            .basic_auth(name, Some(&password))
+            .json(&removed_action_ids)
+            .send()
+            .await?
+            .error_for_status()?;
+        info!("{} actions deleted", removed_action_ids.len());
     }
 
+    info!("setup successful");
+
     Ok(())
 }
 
@@ -193,3 +285,161 @@ pub async fn disable_events(
 
     Ok(())
 }
+
+/// Create the result of the execution of `exec_action_event` at the current time.
+pub fn action_event_result(
+    exec_action_event: &ExecutableActionEvent,
+    status: ActionEventStatus,
+    message: Option<String>,
+) -> ActionEventResult {
+    ActionEventResult {
+        id: ActionEventResultId(rand::thread_rng().gen()),
+        user_id: exec_action_event.user_id,
+        action_id: exec_action_event.action_id,
+        action_event_id: exec_action_event.action_event_id,
+        action_event_datetime: exec_action_event.datetime,
+        status,
+        message,
+        datetime: Utc::now(),
+        deleted: false,
+    }
+}
+
+pub async fn report_results(
+    client: &Client,
+    base_url: &str,
+    name: &str,
+    password: &str,
+    action_event_results: &[ActionEventResult],
+) -> Result<(), Error> {
+    debug!(
+        "reporting {} action event results",
+        action_event_results.len()
+    );
+
+    client
+        .post(route_max_version(base_url, AP_ACTION_EVENT_RESULT, None))
+        .basic_auth(name, Some(&password))
+        .json(action_event_results)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Send the heartbeat of the action provider with the stats of the current run.
+///
+/// `version` is the version of the action provider and `run_interval` the time between two of its runs.
+#[allow(clippy::too_many_arguments)]
+pub async fn heartbeat(
+    client: &Client,
+    base_url: &str,
+    name: &str,
+    password: &str,
+    version: &str,
+    run_interval: Duration,
+    action_event_results: &[ActionEventResult],
+) -> Result<(), Error> {
+    let action_provider: ActionProvider = client
+        .get(route_max_version(base_url, AP_ACTION_PROVIDER, None))
+        .basic_auth(name, Some(&password))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let count = |status| {
+        action_event_results
+            .iter()
+            .filter(|action_event_result| action_event_result.status == status)
+            .count() as i32
+    };
+    let heartbeat = ActionProviderHeartbeat {
+        action_provider_id: action_provider.id,
+        version: version.to_owned(),
+        datetime: Utc::now(),
+        run_interval: run_interval.num_milliseconds() as i32,
+        action_events: action_event_results.len() as i32,
+        successes: count(ActionEventStatus::Success),
+        user_errors: count(ActionEventStatus::UserError),
+        transient_errors: count(ActionEventStatus::TransientError),
+    };
+
+    debug!("sending heartbeat {:?}", heartbeat);
+
+    client
+        .post(route_max_version(base_url, AP_HEARTBEAT, None))
+        .basic_auth(name, Some(&password))
+        .json(&heartbeat)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[tokio::test]
+    async fn setup_syncs_actions() {
+        let server = MockServer::start().await;
+
+        let setup = |credential, actions| {
+            setup(
+                server.url(),
+                "setup-ap",
+                "setup-ap-password",
+                "Setup Test",
+                "setup-platform",
+                credential,
+                actions,
+                Duration::try_days(1).unwrap(),
+                Duration::try_hours(1).unwrap(),
+                3,
+            )
+        };
+
+        setup(false, &[("a", "Action A.", None), ("b", "Action B.", None)])
+            .await
+            .unwrap();
+        let action_a_id = server.state().actions[0].id;
+
+        setup(
+            true,
+            &[
+                ("a", "Action A changed.", Some(r#"{"type": "object"}"#)),
+                ("c", "Action C.", None),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let state = server.state();
+        assert_eq!(state.platforms.len(), 1);
+        assert!(state.platforms[0].credential);
+        assert_eq!(state.action_providers.len(), 1);
+
+        let action = |name: &str| {
+            state
+                .actions
+                .iter()
+                .find(|action| action.name == name)
+                .unwrap()
+        };
+        assert_eq!(state.actions.len(), 3);
+        assert_eq!(action("a").id, action_a_id);
+        assert_eq!(
+            action("a").description.as_deref(),
+            Some("Action A changed.")
+        );
+        assert!(action("a").argument_schema.is_some());
+        assert!(!action("a").deleted);
+        assert!(action("b").deleted);
+        assert!(!action("c").deleted);
+    }
+}
@@ -0,0 +1,839 @@
+//! In-process fake of the Sport Log server for tests of action providers.
+//!
+//! [`MockServer`] serves the routes used by action providers from memory:
+//! the action provider routes (`AP_PLATFORM`, `AP_ACTION_PROVIDER`, `AP_ACTION`, `AP_EXECUTABLE_ACTION_EVENT`,
+//! `AP_ACTION_EVENT`, `AP_ACTION_EVENT_RESULT` and `AP_HEARTBEAT`) and the user routes action providers read from and write to.
+//! Tests seed action events and movements and assert on the [`MockState`] after the action provider ran.
+//!
+//! Requests to all routes except `AP_PLATFORM` and the creation of the action provider have to be authenticated
+//! with the name and password of a registered action provider.
+//! Requests to user routes additionally need the [`ID_HEADER`].
+//! Deleted actions are kept with `deleted` set, like they are returned by the real server.
+//! Like the real server, strength sessions of unknown movements and strength sets with a duplicate set number
+//! are rejected with `409 Conflict`.
+//! Unlike the real server, the executable action events are not filtered by the actions of the action provider.
+//!
+//! Only available with the `mock-server` feature.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use axum_extra::{
+    headers::{authorization::Basic, Authorization},
+    TypedHeader,
+};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Deserialize;
+use sport_log_types::{
+    uri::{
+        ACTIVITY_MAPPING, AP_ACTION, AP_ACTION_EVENT, AP_ACTION_EVENT_RESULT, AP_ACTION_PROVIDER,
+        AP_EXECUTABLE_ACTION_EVENT, AP_HEARTBEAT, AP_PLATFORM, CARDIO_SESSION, MAX_VERSION, METCON,
+        METCON_DRAFT, METCON_MOVEMENT, METCON_SESSION, MOVEMENT, STRENGTH_SESSION, STRENGTH_SET,
+        USER, WOD,
+    },
+    wod_parser::{self, MetconDraft, WodText},
+    Action, ActionEventId, ActionEventResult, ActionId, ActionProvider, ActionProviderHeartbeat,
+    ActionProviderId, ActivityMapping, CardioSession, ExecutableActionEvent, Metcon,
+    MetconMovement, MetconSession, Movement, Platform, StrengthSession, StrengthSet, User, UserId,
+    Wod, ID_HEADER,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// Everything that has been created on the [`MockServer`].
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub users: Vec<User>,
+    pub platforms: Vec<Platform>,
+    pub action_providers: Vec<ActionProvider>,
+    pub actions: Vec<Action>,
+    pub executable_action_events: Vec<ExecutableActionEvent>,
+    pub disabled_action_event_ids: Vec<ActionEventId>,
+    pub action_event_results: Vec<ActionEventResult>,
+    pub heartbeats: Vec<ActionProviderHeartbeat>,
+    pub movements: Vec<Movement>,
+    pub activity_mappings: Vec<ActivityMapping>,
+    pub cardio_sessions: Vec<CardioSession>,
+    pub strength_sessions: Vec<StrengthSession>,
+    pub strength_sets: Vec<StrengthSet>,
+    pub wods: Vec<Wod>,
+    pub metcons: Vec<Metcon>,
+    pub metcon_movements: Vec<MetconMovement>,
+    pub metcon_sessions: Vec<MetconSession>,
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+/// A fake Sport Log server listening on a random local port.
+///
+/// The server is stopped when it is dropped.
+pub struct MockServer {
+    url: String,
+    state: SharedState,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let state = SharedState::default();
+        let router = router(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let task = tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Self { url, state, task }
+    }
+
+    /// The base url of the server that can be used as `server_url` in the config of an action provider.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn add_executable_action_event(&self, exec_action_event: ExecutableActionEvent) {
+        self.state()
+            .executable_action_events
+            .push(exec_action_event);
+    }
+
+    pub fn add_movement(&self, movement: Movement) {
+        self.state().movements.push(movement);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SingleOrVec<T> {
+    Single(T),
+    Vec(Vec<T>),
+}
+
+impl<T> SingleOrVec<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::Single(value) => vec![value],
+            Self::Vec(values) => values,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Period {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+type Auth = TypedHeader<Authorization<Basic>>;
+
+fn router(state: SharedState) -> Router {
+    let route = |route: &str| format!("/v{MAX_VERSION}{route}");
+
+    Router::new()
+        .route(
+            &route(AP_PLATFORM),
+            get(get_platforms)
+                .post(create_platform)
+                .put(update_platform),
+        )
+        .route(
+            &route(AP_ACTION_PROVIDER),
+            get(get_action_provider).post(create_action_provider),
+        )
+        .route(
+            &route(AP_ACTION),
+            get(get_actions)
+                .post(create_actions)
+                .put(update_actions)
+                .delete(delete_actions),
+        )
+        .route(
+            &route(AP_EXECUTABLE_ACTION_EVENT),
+            get(get_executable_action_events),
+        )
+        .route(&route(AP_ACTION_EVENT), delete(disable_action_events))
+        .route(
+            &route(AP_ACTION_EVENT_RESULT),
+            post(create_action_event_results),
+        )
+        .route(&route(AP_HEARTBEAT), post(create_heartbeat))
+        .route(&route(USER), get(get_user))
+        .route(&route(MOVEMENT), get(get_movements))
+        .route(
+            &route(ACTIVITY_MAPPING),
+            get(get_activity_mappings).post(create_activity_mappings),
+        )
+        .route(
+            &route(CARDIO_SESSION),
+            get(get_cardio_sessions).post(create_cardio_sessions),
+        )
+        .route(
+            &route(STRENGTH_SESSION),
+            get(get_strength_sessions).post(create_strength_sessions),
+        )
+        .route(&route(STRENGTH_SET), post(create_strength_sets))
+        .route(&route(WOD), post(create_wods))
+        .route(&route(METCON_DRAFT), post(parse_metcon_draft))
+        .route(&route(METCON), get(get_metcons).post(create_metcons))
+        .route(&route(METCON_MOVEMENT), post(create_metcon_movements))
+        .route(
+            &route(METCON_SESSION),
+            get(get_metcon_sessions).post(create_metcon_sessions),
+        )
+        .with_state(state)
+}
+
+fn authenticate(
+    state: &MockState,
+    TypedHeader(auth): &Auth,
+) -> Result<ActionProviderId, StatusCode> {
+    state
+        .action_providers
+        .iter()
+        .find(|action_provider| {
+            action_provider.name == auth.username() && action_provider.password == auth.password()
+        })
+        .map(|action_provider| action_provider.id)
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+/// Authenticate the action provider and get the user it acts on behalf of.
+fn authenticate_user(
+    state: &MockState,
+    auth: &Auth,
+    headers: &HeaderMap,
+) -> Result<UserId, StatusCode> {
+    authenticate(state, auth)?;
+    headers
+        .get(ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.parse().ok())
+        .map(UserId)
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+async fn get_user(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+) -> Result<Json<User>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    state
+        .users
+        .iter()
+        .find(|user| user.id == user_id)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn get_platforms(State(state): State<SharedState>) -> Json<Vec<Platform>> {
+    Json(state.lock().unwrap().platforms.clone())
+}
+
+async fn create_platform(
+    State(state): State<SharedState>,
+    Json(platform): Json<Platform>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    if state.platforms.iter().any(|p| p.name == platform.name) {
+        return StatusCode::CONFLICT;
+    }
+    state.platforms.push(platform);
+    StatusCode::OK
+}
+
+async fn update_platform(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(platform): Json<Platform>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let action_provider_id = match authenticate(&state, &auth) {
+        Ok(action_provider_id) => action_provider_id,
+        Err(status) => return status,
+    };
+    let own_platform = state
+        .action_providers
+        .iter()
+        .any(|ap| ap.id == action_provider_id && ap.platform_id == platform.id);
+    if !own_platform {
+        return StatusCode::FORBIDDEN;
+    }
+    match state.platforms.iter_mut().find(|p| p.id == platform.id) {
+        Some(p) => {
+            p.credential = platform.credential;
+            StatusCode::OK
+        }
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+async fn get_action_provider(
+    State(state): State<SharedState>,
+    auth: Auth,
+) -> Result<Json<ActionProvider>, StatusCode> {
+    let state = state.lock().unwrap();
+    let action_provider_id = authenticate(&state, &auth)?;
+    state
+        .action_providers
+        .iter()
+        .find(|action_provider| action_provider.id == action_provider_id)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn create_action_provider(
+    State(state): State<SharedState>,
+    Json(action_provider): Json<ActionProvider>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    if state
+        .action_providers
+        .iter()
+        .any(|ap| ap.name == action_provider.name)
+    {
+        return StatusCode::CONFLICT;
+    }
+    state.action_providers.push(action_provider);
+    StatusCode::OK
+}
+
+async fn create_actions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(actions): Json<SingleOrVec<Action>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let action_provider_id = match authenticate(&state, &auth) {
+        Ok(action_provider_id) => action_provider_id,
+        Err(status) => return status,
+    };
+    let actions = actions.into_vec();
+    if actions
+        .iter()
+        .any(|action| action.action_provider_id != action_provider_id)
+    {
+        return StatusCode::FORBIDDEN;
+    }
+    if actions.iter().any(|action| {
+        state.actions.iter().any(|a| {
+            a.action_provider_id == action_provider_id && a.name == action.name && !a.deleted
+        })
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.actions.extend(actions);
+    StatusCode::OK
+}
+
+async fn get_actions(
+    State(state): State<SharedState>,
+    auth: Auth,
+) -> Result<Json<Vec<Action>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let action_provider_id = authenticate(&state, &auth)?;
+    Ok(Json(
+        state
+            .actions
+            .iter()
+            .filter(|action| action.action_provider_id == action_provider_id)
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn update_actions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(actions): Json<SingleOrVec<Action>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let action_provider_id = match authenticate(&state, &auth) {
+        Ok(action_provider_id) => action_provider_id,
+        Err(status) => return status,
+    };
+    let actions = actions.into_vec();
+    if actions.iter().any(|action| {
+        action.action_provider_id != action_provider_id
+            || !state
+                .actions
+                .iter()
+                .any(|a| a.id == action.id && a.action_provider_id == action_provider_id)
+    }) {
+        return StatusCode::FORBIDDEN;
+    }
+    for action in actions {
+        if let Some(a) = state.actions.iter_mut().find(|a| a.id == action.id) {
+            *a = action;
+        }
+    }
+    StatusCode::OK
+}
+
+async fn delete_actions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(action_ids): Json<Vec<ActionId>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let action_provider_id = match authenticate(&state, &auth) {
+        Ok(action_provider_id) => action_provider_id,
+        Err(status) => return status,
+    };
+    if action_ids.iter().any(|id| {
+        !state
+            .actions
+            .iter()
+            .any(|a| a.id == *id && a.action_provider_id == action_provider_id)
+    }) {
+        return StatusCode::FORBIDDEN;
+    }
+    for action in &mut state.actions {
+        if action_ids.contains(&action.id) {
+            action.deleted = true;
+        }
+    }
+    StatusCode::OK
+}
+
+async fn get_executable_action_events(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Query(period): Query<Period>,
+) -> Result<Json<Vec<ExecutableActionEvent>>, StatusCode> {
+    let state = state.lock().unwrap();
+    authenticate(&state, &auth)?;
+    Ok(Json(
+        state
+            .executable_action_events
+            .iter()
+            .filter(|event| event.datetime >= period.start && event.datetime <= period.end)
+            .filter(|event| {
+                !state
+                    .disabled_action_event_ids
+                    .contains(&event.action_event_id)
+            })
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn disable_action_events(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(action_event_ids): Json<Vec<ActionEventId>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    if let Err(status) = authenticate(&state, &auth) {
+        return status;
+    }
+    state.disabled_action_event_ids.extend(action_event_ids);
+    StatusCode::OK
+}
+
+async fn create_action_event_results(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(action_event_results): Json<SingleOrVec<ActionEventResult>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    if let Err(status) = authenticate(&state, &auth) {
+        return status;
+    }
+    state
+        .action_event_results
+        .extend(action_event_results.into_vec());
+    StatusCode::OK
+}
+
+async fn create_heartbeat(
+    State(state): State<SharedState>,
+    auth: Auth,
+    Json(heartbeat): Json<ActionProviderHeartbeat>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    match authenticate(&state, &auth) {
+        Ok(action_provider_id) if action_provider_id == heartbeat.action_provider_id => {
+            state.heartbeats.push(heartbeat);
+            StatusCode::OK
+        }
+        Ok(_) => StatusCode::FORBIDDEN,
+        Err(status) => status,
+    }
+}
+
+fn user_movements(state: &MockState, user_id: UserId) -> Vec<Movement> {
+    state
+        .movements
+        .iter()
+        .filter(|movement| movement.user_id.is_none_or(|id| id == user_id))
+        .cloned()
+        .collect()
+}
+
+async fn get_movements(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Movement>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(user_movements(&state, user_id)))
+}
+
+async fn get_activity_mappings(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ActivityMapping>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(
+        state
+            .activity_mappings
+            .iter()
+            .filter(|activity_mapping| activity_mapping.user_id == user_id)
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn create_activity_mappings(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(activity_mappings): Json<SingleOrVec<ActivityMapping>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let activity_mappings = activity_mappings.into_vec();
+    if activity_mappings.iter().any(|a| a.user_id != user_id) {
+        return StatusCode::FORBIDDEN;
+    }
+    if activity_mappings.iter().any(|a| {
+        state.activity_mappings.iter().any(|m| {
+            !m.deleted
+                && m.user_id == a.user_id
+                && m.platform_id == a.platform_id
+                && m.activity == a.activity
+        })
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.activity_mappings.extend(activity_mappings);
+    StatusCode::OK
+}
+
+async fn get_cardio_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Query(period): Query<Period>,
+) -> Result<Json<Vec<CardioSession>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(
+        state
+            .cardio_sessions
+            .iter()
+            .filter(|cardio_session| cardio_session.user_id == user_id)
+            .filter(|cardio_session| {
+                cardio_session.datetime >= period.start && cardio_session.datetime <= period.end
+            })
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn create_cardio_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(cardio_sessions): Json<SingleOrVec<CardioSession>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let cardio_sessions = cardio_sessions.into_vec();
+    if cardio_sessions.iter().any(|c| c.user_id != user_id) {
+        return StatusCode::FORBIDDEN;
+    }
+    if cardio_sessions
+        .iter()
+        .any(|c| state.cardio_sessions.iter().any(|s| s.id == c.id))
+    {
+        return StatusCode::CONFLICT;
+    }
+    state.cardio_sessions.extend(cardio_sessions);
+    StatusCode::OK
+}
+
+async fn get_strength_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Query(period): Query<Period>,
+) -> Result<Json<Vec<StrengthSession>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(
+        state
+            .strength_sessions
+            .iter()
+            .filter(|strength_session| strength_session.user_id == user_id)
+            .filter(|strength_session| {
+                strength_session.datetime >= period.start && strength_session.datetime <= period.end
+            })
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn create_strength_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(strength_sessions): Json<SingleOrVec<StrengthSession>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let strength_sessions = strength_sessions.into_vec();
+    if strength_sessions.iter().any(|s| s.user_id != user_id) {
+        return StatusCode::FORBIDDEN;
+    }
+    if strength_sessions.iter().any(|s| {
+        state.strength_sessions.iter().any(|t| t.id == s.id)
+            || !state.movements.iter().any(|m| m.id == s.movement_id)
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.strength_sessions.extend(strength_sessions);
+    StatusCode::OK
+}
+
+async fn create_strength_sets(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(strength_sets): Json<SingleOrVec<StrengthSet>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let strength_sets = strength_sets.into_vec();
+    if strength_sets.iter().any(|set| {
+        !state
+            .strength_sessions
+            .iter()
+            .any(|s| s.id == set.strength_session_id && s.user_id == user_id)
+    }) {
+        return StatusCode::FORBIDDEN;
+    }
+    if strength_sets.iter().any(|set| {
+        state.strength_sets.iter().any(|s| {
+            s.id == set.id
+                || (s.strength_session_id == set.strength_session_id
+                    && s.set_number == set.set_number)
+        })
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.strength_sets.extend(strength_sets);
+    StatusCode::OK
+}
+
+async fn create_wods(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(wods): Json<SingleOrVec<Wod>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let wods = wods.into_vec();
+    if wods.iter().any(|wod| wod.user_id != user_id) {
+        return StatusCode::FORBIDDEN;
+    }
+    if wods.iter().any(|wod| {
+        state
+            .wods
+            .iter()
+            .any(|w| w.id == wod.id || (w.user_id == wod.user_id && w.date == wod.date))
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.wods.extend(wods);
+    StatusCode::OK
+}
+
+async fn parse_metcon_draft(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(wod_text): Json<WodText>,
+) -> Result<Json<MetconDraft>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    let movements = user_movements(&state, user_id);
+    wod_parser::parse_wod(&wod_text, user_id, &movements, || rand::thread_rng().gen())
+        .map(Json)
+        .map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+async fn get_metcons(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Metcon>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(
+        state
+            .metcons
+            .iter()
+            .filter(|metcon| metcon.user_id.is_none_or(|id| id == user_id))
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn create_metcons(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(metcons): Json<SingleOrVec<Metcon>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let metcons = metcons.into_vec();
+    if metcons.iter().any(|metcon| metcon.user_id != Some(user_id)) {
+        return StatusCode::FORBIDDEN;
+    }
+    if metcons.iter().any(|metcon| {
+        state
+            .metcons
+            .iter()
+            .any(|m| m.id == metcon.id || (m.user_id == metcon.user_id && m.name == metcon.name))
+    }) {
+        return StatusCode::CONFLICT;
+    }
+    state.metcons.extend(metcons);
+    StatusCode::OK
+}
+
+async fn create_metcon_movements(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(metcon_movements): Json<SingleOrVec<MetconMovement>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let metcon_movements = metcon_movements.into_vec();
+    if metcon_movements.iter().any(|metcon_movement| {
+        !state
+            .metcons
+            .iter()
+            .any(|m| m.id == metcon_movement.metcon_id && m.user_id == Some(user_id))
+    }) {
+        return StatusCode::FORBIDDEN;
+    }
+    state.metcon_movements.extend(metcon_movements);
+    StatusCode::OK
+}
+
+async fn get_metcon_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Query(period): Query<Period>,
+) -> Result<Json<Vec<MetconSession>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let user_id = authenticate_user(&state, &auth, &headers)?;
+    Ok(Json(
+        state
+            .metcon_sessions
+            .iter()
+            .filter(|metcon_session| metcon_session.user_id == user_id)
+            .filter(|metcon_session| {
+                metcon_session.datetime >= period.start && metcon_session.datetime <= period.end
+            })
+            .cloned()
+            .collect(),
+    ))
+}
+
+async fn create_metcon_sessions(
+    State(state): State<SharedState>,
+    auth: Auth,
+    headers: HeaderMap,
+    Json(metcon_sessions): Json<SingleOrVec<MetconSession>>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+    let user_id = match authenticate_user(&state, &auth, &headers) {
+        Ok(user_id) => user_id,
+        Err(status) => return status,
+    };
+    let metcon_sessions = metcon_sessions.into_vec();
+    if metcon_sessions.iter().any(|m| m.user_id != user_id) {
+        return StatusCode::FORBIDDEN;
+    }
+    for metcon_session in &metcon_sessions {
+        let Some(metcon) = state
+            .metcons
+            .iter()
+            .find(|m| m.id == metcon_session.metcon_id && m.user_id.is_none_or(|id| id == user_id))
+        else {
+            return StatusCode::FORBIDDEN;
+        };
+        if metcon_session.validate(metcon).is_err() {
+            return StatusCode::BAD_REQUEST;
+        }
+    }
+    if metcon_sessions
+        .iter()
+        .any(|m| state.metcon_sessions.iter().any(|s| s.id == m.id))
+    {
+        return StatusCode::CONFLICT;
+    }
+    state.metcon_sessions.extend(metcon_sessions);
+    StatusCode::OK
+}
@@ -0,0 +1,594 @@
+//! Framework for action providers.
+//!
+//! An action provider implements [`Provider`] and calls [`run`] in its `main` function.
+//! [`run`] takes care of the config, the command line arguments, the setup of the action provider,
+//! fetching the executable action events, disabling them and reporting their results, sending heartbeats
+//! and logging.
+//! The [`Provider`] itself only has to describe its actions and handle a single action event.
+
+use std::{env, fmt::Display, fs, future::Future, process::ExitCode, sync::Arc};
+
+use chrono::Duration;
+use clap::{Args as ClapArgs, CommandFactory, FromArgMatches, Parser};
+use reqwest::{Client, Error as ReqwestError};
+use serde::{de::DeserializeOwned, Deserialize};
+use sport_log_types::{ActionEventResult, ActionEventStatus, ExecutableActionEvent};
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+use crate::{action_event_result, disable_events, get_events, heartbeat, report_results, setup};
+
+/// The config of an action provider.
+///
+/// The name of the config file is specified in [`Provider::CONFIG_FILE`].
+///
+/// `password` is the password of the action provider.
+///
+/// `server_url` is the left part of the URL (everything before `/<version>/...`)
+///
+/// `run_interval_minutes` is the time between two runs of the action provider, used by the server to detect missed runs.
+///
+/// All other keys of the config file are deserialized into `provider`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config<C> {
+    pub password: String,
+    pub server_url: String,
+    #[serde(default = "default_run_interval_minutes")]
+    pub run_interval_minutes: i64,
+    #[serde(flatten)]
+    pub provider: C,
+}
+
+fn default_run_interval_minutes() -> i64 {
+    60
+}
+
+#[derive(Parser, Debug)]
+struct Args<A: ClapArgs> {
+    /// create own actions
+    #[arg(short, long)]
+    setup: bool,
+
+    #[command(flatten)]
+    provider: A,
+}
+
+/// Command line arguments of action providers without provider specific arguments.
+#[derive(ClapArgs, Debug)]
+pub struct NoArgs {}
+
+/// The result of handling a single action event.
+///
+/// The outer error is a transient error (network problems, unexpected responses, ...) and the action event will be retried.
+/// The inner error is a user error (wrong credentials, invalid arguments, ...) and the action event will be disabled.
+/// On success an optional message for the user can be returned, which is reported like the error messages.
+pub type EventResult<U, E> = Result<Result<Option<String>, U>, E>;
+
+/// An action provider that can be executed by [`run`].
+pub trait Provider: Sized + Send + Sync + 'static {
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+    const PLATFORM_NAME: &'static str;
+    /// Whether the platform requires credentials.
+    ///
+    /// If `true`, action events without credentials are rejected with a user error and never passed to [`Provider::handle`].
+    const CREDENTIAL: bool;
+    /// The name, description and argument schema of every action.
+    const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)];
+    const MAX_ATTEMPTS: i32 = 3;
+    /// The maximal number of action events that are handled at the same time.
+    const MAX_CONCURRENT_EVENTS: usize = 8;
+    const CONFIG_FILE: &'static str;
+    const VERSION: &'static str;
+
+    /// Provider specific part of the config file.
+    type Config: DeserializeOwned + Send + Sync;
+    /// Provider specific command line arguments.
+    type Args: ClapArgs + Send + Sync;
+    type Error: Display + From<ReqwestError> + Send + 'static;
+    type UserError: Display + Send + 'static;
+
+    fn create_before() -> Duration;
+
+    fn delete_after() -> Duration;
+
+    /// The start and end offset relative to now of the action events that are handled.
+    fn event_window() -> (Duration, Duration);
+
+    /// Create the action provider.
+    ///
+    /// This is only called if there are action events to handle.
+    fn new(
+        config: &Config<Self::Config>,
+        args: &Self::Args,
+    ) -> impl Future<Output = Result<Self, Self::Error>> + Send;
+
+    fn handle(
+        &self,
+        exec_action_event: &ExecutableActionEvent,
+    ) -> impl Future<Output = EventResult<Self::UserError, Self::Error>> + Send;
+
+    /// Whether the action event should be retried like after a transient error.
+    fn is_transient(_error: &Self::UserError) -> bool {
+        false
+    }
+
+    /// Clean up after all action events have been handled.
+    fn shutdown(self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// Get the username and password of an action event.
+///
+/// # Panics
+///
+/// Panics if the action event has no credentials.
+/// This can not happen in [`Provider::handle`] if [`Provider::CREDENTIAL`] is `true`.
+pub fn credential(exec_action_event: &ExecutableActionEvent) -> (&str, &str) {
+    match (&exec_action_event.username, &exec_action_event.password) {
+        (Some(username), Some(password)) => (username, password),
+        _ => panic!("action event has no credentials"),
+    }
+}
+
+/// Initialize the logging of the action provider `crate_name`.
+///
+/// If `RUST_LOG` is not set, the action provider logs at `debug` level in debug builds and at `info` level otherwise.
+pub fn init_tracing(crate_name: &str) {
+    if env::var("RUST_LOG").is_err() {
+        if cfg!(debug_assertions) {
+            env::set_var("RUST_LOG", format!("info,{crate_name}=debug"));
+        } else {
+            env::set_var("RUST_LOG", format!("warn,{crate_name}=info"));
+        }
+    }
+
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+}
+
+/// Parse the command line arguments and the config and then either set up the action provider or handle its action events.
+pub async fn run<P: Provider>() -> ExitCode {
+    let matches = Args::<P::Args>::command()
+        .name(P::NAME)
+        .about(P::DESCRIPTION)
+        .get_matches();
+    let args = match Args::<P::Args>::from_arg_matches(&matches) {
+        Ok(args) => args,
+        Err(error) => error.exit(),
+    };
+
+    let config_file = match fs::read_to_string(P::CONFIG_FILE) {
+        Ok(file) => file,
+        Err(error) => {
+            error!("failed to read {}: {error}", P::CONFIG_FILE);
+            return ExitCode::FAILURE;
+        }
+    };
+    let config: Config<P::Config> = match toml::from_str(&config_file) {
+        Ok(config) => config,
+        Err(error) => {
+            error!("failed to parse {}: {error}", P::CONFIG_FILE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.setup {
+        if let Err(error) = setup_provider::<P>(&config).await {
+            warn!("setup failed: {error}");
+        }
+    } else if let Err(error) = execute::<P>(&config, &args.provider).await {
+        warn!("handling action events failed: {error}");
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Create the platform, the action provider and its actions.
+pub async fn setup_provider<P: Provider>(config: &Config<P::Config>) -> Result<(), ReqwestError> {
+    setup(
+        &config.server_url,
+        P::NAME,
+        &config.password,
+        P::DESCRIPTION,
+        P::PLATFORM_NAME,
+        P::CREDENTIAL,
+        P::ACTIONS,
+        P::create_before(),
+        P::delete_after(),
+        P::MAX_ATTEMPTS,
+    )
+    .await
+}
+
+/// Handle all executable action events, disable the finished ones, report the results and send a heartbeat.
+///
+/// The provider is always shut down and the heartbeat is always sent, even if an earlier step failed.
+/// The first error is returned afterwards.
+pub async fn execute<P: Provider>(
+    config: &Config<P::Config>,
+    args: &P::Args,
+) -> Result<(), P::Error> {
+    let client = Client::new();
+
+    let mut action_event_results = vec![];
+    let result = async {
+        let (start_offset, end_offset) = P::event_window();
+        let exec_action_events = get_events(
+            &client,
+            &config.server_url,
+            P::NAME,
+            &config.password,
+            start_offset,
+            end_offset,
+        )
+        .await?;
+
+        if exec_action_events.is_empty() {
+            return Ok(());
+        }
+
+        let provider = Arc::new(P::new(config, args).await?);
+
+        action_event_results = handle_events(&provider, exec_action_events).await;
+
+        let disable_action_event_ids: Vec<_> = action_event_results
+            .iter()
+            .filter(|action_event_result| {
+                action_event_result.status != ActionEventStatus::TransientError
+            })
+            .map(|action_event_result| action_event_result.action_event_id)
+            .collect();
+        let disable_result = if disable_action_event_ids.is_empty() {
+            Ok(())
+        } else {
+            disable_events(
+                &client,
+                &config.server_url,
+                P::NAME,
+                &config.password,
+                &disable_action_event_ids,
+            )
+            .await
+        };
+
+        let report_result = report_results(
+            &client,
+            &config.server_url,
+            P::NAME,
+            &config.password,
+            &action_event_results,
+        )
+        .await;
+
+        let shutdown_result = match Arc::into_inner(provider) {
+            Some(provider) => provider.shutdown().await,
+            None => Ok(()),
+        };
+
+        disable_result
+            .and(report_result)
+            .map_err(Into::into)
+            .and(shutdown_result)
+    }
+    .await;
+
+    let heartbeat_result = heartbeat(
+        &client,
+        &config.server_url,
+        P::NAME,
+        &config.password,
+        P::VERSION,
+        Duration::try_minutes(config.run_interval_minutes).unwrap(),
+        &action_event_results,
+    )
+    .await;
+
+    result.and(heartbeat_result.map_err(Into::into))
+}
+
+/// Handle the action events concurrently and classify their results.
+async fn handle_events<P: Provider>(
+    provider: &Arc<P>,
+    exec_action_events: Vec<ExecutableActionEvent>,
+) -> Vec<ActionEventResult> {
+    let semaphore = Arc::new(Semaphore::new(P::MAX_CONCURRENT_EVENTS));
+
+    let mut tasks = vec![];
+    for exec_action_event in exec_action_events {
+        if P::CREDENTIAL
+            && (exec_action_event.username.is_none() || exec_action_event.password.is_none())
+        {
+            tasks.push((exec_action_event, None));
+            continue;
+        }
+
+        let provider = provider.clone();
+        let semaphore = semaphore.clone();
+        let task_exec_action_event = exec_action_event.clone();
+        let task = tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            debug!("processing {:#?}", task_exec_action_event);
+            provider.handle(&task_exec_action_event).await
+        });
+        tasks.push((exec_action_event, Some(task)));
+    }
+
+    let mut action_event_results = vec![];
+    for (exec_action_event, task) in tasks {
+        let action_event_id = exec_action_event.action_event_id.0;
+        let (status, message) = match task {
+            None => {
+                let message = "can not log in: no credentials provided".to_owned();
+                info!("action event {action_event_id}: {message}");
+                (ActionEventStatus::UserError, Some(message))
+            }
+            Some(task) => {
+                match task.await {
+                    Ok(Ok(Ok(message))) => {
+                        debug!("action event {action_event_id} handled successfully");
+                        (ActionEventStatus::Success, message)
+                    }
+                    Ok(Ok(Err(error))) if P::is_transient(&error) => {
+                        info!("action event {action_event_id}: {error} (trying again on next invocation)");
+                        (ActionEventStatus::TransientError, Some(error.to_string()))
+                    }
+                    Ok(Ok(Err(error))) => {
+                        info!("action event {action_event_id}: {error}");
+                        (ActionEventStatus::UserError, Some(error.to_string()))
+                    }
+                    Ok(Err(error)) => {
+                        warn!("action event {action_event_id}: {error}");
+                        (ActionEventStatus::TransientError, Some(error.to_string()))
+                    }
+                    Err(error) => {
+                        warn!("action event {action_event_id}: {error}");
+                        (ActionEventStatus::TransientError, Some(error.to_string()))
+                    }
+                }
+            }
+        };
+        action_event_results.push(action_event_result(&exec_action_event, status, message));
+    }
+
+    action_event_results
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use chrono::Utc;
+    use rand::Rng;
+    use sport_log_types::{
+        uri::{route_max_version, WOD},
+        ActionEventId, UserId, Wod, WodId, ID_HEADER,
+    };
+
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[derive(Debug)]
+    struct TestError(String);
+
+    impl Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl From<ReqwestError> for TestError {
+        fn from(error: ReqwestError) -> Self {
+            Self(error.to_string())
+        }
+    }
+
+    struct TestProvider {
+        client: Client,
+        config: Config<()>,
+        /// Set by action events with the argument `shutdown error`.
+        shutdown_error: AtomicBool,
+    }
+
+    impl Provider for TestProvider {
+        const NAME: &'static str = "test-provider";
+        const DESCRIPTION: &'static str = "Test Provider creates wods.";
+        const PLATFORM_NAME: &'static str = "test-platform";
+        const CREDENTIAL: bool = true;
+        const ACTIONS: &'static [(&'static str, &'static str, Option<&'static str>)] =
+            &[("wod", "Create a wod.", None)];
+        const CONFIG_FILE: &'static str = "test-provider.toml";
+        const VERSION: &'static str = "1.0.0";
+
+        type Config = ();
+        type Args = NoArgs;
+        type Error = TestError;
+        type UserError = String;
+
+        fn create_before() -> Duration {
+            Duration::try_hours(1).unwrap()
+        }
+
+        fn delete_after() -> Duration {
+            Duration::zero()
+        }
+
+        fn event_window() -> (Duration, Duration) {
+            (
+                Duration::try_hours(-1).unwrap(),
+                Duration::try_hours(1).unwrap(),
+            )
+        }
+
+        async fn new(config: &Config<()>, _args: &NoArgs) -> Result<Self, TestError> {
+            Ok(Self {
+                client: Client::new(),
+                config: config.clone(),
+                shutdown_error: AtomicBool::new(false),
+            })
+        }
+
+        async fn handle(
+            &self,
+            exec_action_event: &ExecutableActionEvent,
+        ) -> EventResult<String, TestError> {
+            match exec_action_event.arguments.as_deref() {
+                Some("user error") => return Ok(Err("invalid arguments".to_owned())),
+                Some("transient error") => return Err(TestError("timeout".to_owned())),
+                Some("shutdown error") => self.shutdown_error.store(true, Ordering::Relaxed),
+                _ => {}
+            }
+
+            let wod = Wod {
+                id: WodId(rand::thread_rng().gen()),
+                user_id: exec_action_event.user_id,
+                date: exec_action_event.datetime.date_naive(),
+                description: Some(exec_action_event.action_name.clone()),
+                deleted: false,
+            };
+            self.client
+                .post(route_max_version(&self.config.server_url, WOD, None))
+                .basic_auth(Self::NAME, Some(&self.config.password))
+                .header(ID_HEADER, exec_action_event.user_id.0)
+                .json(&wod)
+                .send()
+                .await?
+                .error_for_status()?;
+
+            Ok(Ok(Some("wod created".to_owned())))
+        }
+
+        async fn shutdown(self) -> Result<(), TestError> {
+            if self.shutdown_error.load(Ordering::Relaxed) {
+                return Err(TestError("shutdown failed".to_owned()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_provider() {
+        let server = MockServer::start().await;
+        let config = Config {
+            password: "TestProvider-passwd1".to_owned(),
+            server_url: server.url().to_owned(),
+            run_interval_minutes: 60,
+            provider: (),
+        };
+
+        setup_provider::<TestProvider>(&config).await.unwrap();
+        let action_id = server.state().actions[0].id;
+
+        let exec_action_event =
+            |id, arguments: Option<&str>, credential: bool| ExecutableActionEvent {
+                action_event_id: ActionEventId(id),
+                action_id,
+                action_name: "wod".to_owned(),
+                datetime: Utc::now(),
+                arguments: arguments.map(ToOwned::to_owned),
+                attempts: 0,
+                time_zone: "UTC".to_owned(),
+                user_id: UserId(1),
+                username: credential.then(|| "user".to_owned()),
+                password: credential.then(|| "password".to_owned()),
+            };
+        server.add_executable_action_event(exec_action_event(1, None, true));
+        server.add_executable_action_event(exec_action_event(2, Some("user error"), true));
+        server.add_executable_action_event(exec_action_event(3, Some("transient error"), true));
+        server.add_executable_action_event(exec_action_event(4, None, false));
+
+        execute::<TestProvider>(&config, &NoArgs {}).await.unwrap();
+
+        {
+            let state = server.state();
+            assert_eq!(state.wods.len(), 1);
+            assert_eq!(state.wods[0].user_id, UserId(1));
+
+            let mut disabled_action_event_ids = state.disabled_action_event_ids.clone();
+            disabled_action_event_ids.sort_by_key(|action_event_id| action_event_id.0);
+            assert_eq!(
+                disabled_action_event_ids,
+                [ActionEventId(1), ActionEventId(2), ActionEventId(4)]
+            );
+
+            let status = |id| {
+                state
+                    .action_event_results
+                    .iter()
+                    .find(|result| result.action_event_id == ActionEventId(id))
+                    .map(|result| result.status)
+            };
+            assert_eq!(status(1), Some(ActionEventStatus::Success));
+            assert_eq!(status(2), Some(ActionEventStatus::UserError));
+            assert_eq!(status(3), Some(ActionEventStatus::TransientError));
+            assert_eq!(status(4), Some(ActionEventStatus::UserError));
+            assert_eq!(
+                state
+                    .action_event_results
+                    .iter()
+                    .find(|result| result.action_event_id == ActionEventId(1))
+                    .and_then(|result| result.message.as_deref()),
+                Some("wod created")
+            );
+
+            assert_eq!(state.heartbeats.len(), 1);
+            assert_eq!(state.heartbeats[0].action_events, 4);
+            assert_eq!(state.heartbeats[0].successes, 1);
+            assert_eq!(state.heartbeats[0].user_errors, 2);
+            assert_eq!(state.heartbeats[0].transient_errors, 1);
+        }
+
+        // only the action event with the transient error is handled again
+        execute::<TestProvider>(&config, &NoArgs {}).await.unwrap();
+
+        let state = server.state();
+        assert_eq!(state.wods.len(), 1);
+        assert_eq!(state.action_event_results.len(), 5);
+        assert_eq!(
+            state.action_event_results[4].action_event_id,
+            ActionEventId(3)
+        );
+        assert_eq!(state.heartbeats.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn execute_provider_shutdown_error() {
+        let server = MockServer::start().await;
+        let config = Config {
+            password: "TestProvider-passwd1".to_owned(),
+            server_url: server.url().to_owned(),
+            run_interval_minutes: 60,
+            provider: (),
+        };
+
+        setup_provider::<TestProvider>(&config).await.unwrap();
+        let action_id = server.state().actions[0].id;
+
+        server.add_executable_action_event(ExecutableActionEvent {
+            action_event_id: ActionEventId(1),
+            action_id,
+            action_name: "wod".to_owned(),
+            datetime: Utc::now(),
+            arguments: Some("shutdown error".to_owned()),
+            attempts: 0,
+            time_zone: "UTC".to_owned(),
+            user_id: UserId(1),
+            username: Some("user".to_owned()),
+            password: Some("password".to_owned()),
+        });
+
+        let result = execute::<TestProvider>(&config, &NoArgs {}).await;
+        assert_eq!(result.unwrap_err().0, "shutdown failed");
+
+        // the results are reported and the heartbeat is sent nevertheless
+        let state = server.state();
+        assert_eq!(state.disabled_action_event_ids, [ActionEventId(1)]);
+        assert_eq!(state.action_event_results.len(), 1);
+        assert_eq!(state.heartbeats.len(), 1);
+        assert_eq!(state.heartbeats[0].successes, 1);
+    }
+}
@@ -801,6 +801,29 @@ pub(crate) fn impl_verify_for_action_provider_with_db(db_type: &Ident) -> TokenS
                 }
             }
         }
+
+        impl crate::db::VerifyMultipleForActionProviderWithDb for crate::db::Unverified<Vec<<#db_type as crate::db::Db>::Type>> {
+            type Type = <#db_type as crate::db::Db>::Type;
+
+            fn verify_ap(
+                self,
+                auth: crate::auth::AuthAP,
+                db: &mut diesel::pg::PgConnection,
+            ) -> Result<Vec<Self::Type>, axum::http::StatusCode> {
+                use crate::db::CheckAPId;
+
+                let values = self.0;
+                let ids: Vec<_> = values.iter().map(|value| value.id).collect();
+                if values.iter().all(|value| value.action_provider_id == *auth)
+                    && crate::db::#db_type::check_ap_ids(&ids, *auth, db)
+                    .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?
+                {
+                    Ok(values)
+                } else {
+                    Err(axum::http::StatusCode::FORBIDDEN)
+                }
+            }
+        }
     }
     .into()
 }
@@ -5,6 +5,9 @@
 //!
 //! [`ActionEvents`](sport_log_types::ActionEvent) are only created from enabled [`ActionRules`](sport_log_types::ActionRule).
 //!
+//! The datetimes of the [`ActionEvents`](sport_log_types::ActionEvent) are computed in the time zone of the [`ActionRule`](sport_log_types::ActionRule),
+//! so their local time does not change across daylight saving time transitions.
+//!
 //! The timespan they are created before their `datetime` is determined by the `create_before` field of the corresponding [`Action`](sport_log_types::Action).
 //!
 //! Similarly the timespan they are deleted after their `datetime` is determined by the `delete_after` field of the corresponding [`Action`](sport_log_types::Action).
@@ -13,17 +16,27 @@
 //!
 //! # Usage
 //!
-//! The **Sport Log Scheduler** has do be executed periodically, preferably as a cron job every hour.
+//! By default the **Sport Log Scheduler** runs only once and has do be executed periodically, preferably as a cron job every hour.
+//!
+//! With `--daemon` it keeps running and wakes up exactly when the next [`ActionEvent`](sport_log_types::ActionEvent) has to be created or deleted,
+//! but at least every `max_sleep_minutes`.
+//! If the server can not be reached it retries with an exponential backoff.
+//! The daemon shuts down gracefully on `SIGTERM` and `SIGINT`.
 //!
 //! # Config
 //!
 //! The config file must be called `sport-log-scheduler.toml` and must be deserializable to a [`Config`].
 
-use std::{env, fs, process::ExitCode};
+use std::{collections::HashMap, env, fs, process::ExitCode};
 
-use chrono::{DateTime, Datelike, Days, Duration, Utc};
+use chrono::{
+    DateTime, Datelike, Days, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone,
+    Utc,
+};
+use chrono_tz::Tz;
+use clap::Parser;
 use rand::Rng;
-use reqwest::{blocking::Client, Error as ReqwestError};
+use reqwest::{Client, Error as ReqwestError};
 use serde::Deserialize;
 use sport_log_types::{
     uri::{
@@ -32,11 +45,23 @@ use sport_log_types::{
     },
     ActionEvent, ActionEventId, CreatableActionRule, DeletableActionEvent, ADMIN_USERNAME,
 };
-use tracing::{debug, error, info};
+use tokio::{
+    signal::{
+        self,
+        unix::{self as unix_signal, SignalKind},
+    },
+    time,
+};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
 pub const CONFIG_FILE: &str = "sport-log-scheduler.toml";
 
+/// The first delay before retrying if the server can not be reached.
+const MIN_BACKOFF_SECONDS: i64 = 10;
+/// The maximum delay before retrying if the server can not be reached.
+const MAX_BACKOFF_SECONDS: i64 = 15 * 60;
+
 /// The config for [`sport-log-scheduler`](crate).
 ///
 /// The name of the config file is specified in [`CONFIG_FILE`].
@@ -47,14 +72,41 @@ pub const CONFIG_FILE: &str = "sport-log-scheduler.toml";
 ///
 /// `garbage_collection_min_days` is the number of days for which an entry has to been deleted and not changed in order to get hard deleted.
 /// If set to `0` garbage collection is disabled.
+///
+/// `max_sleep_minutes` is the maximum time the daemon sleeps between two runs.
+/// It bounds the delay until new or changed action rules are taken into account.
+///
+/// `garbage_collection_interval_hours` is the interval in which the daemon runs the garbage collection.
 #[derive(Deserialize)]
 struct Config {
     admin_password: String,
     server_url: String,
     garbage_collection_min_days: u32,
+    #[serde(default = "default_max_sleep_minutes")]
+    max_sleep_minutes: u32,
+    #[serde(default = "default_garbage_collection_interval_hours")]
+    garbage_collection_interval_hours: u32,
 }
 
-fn main() -> ExitCode {
+fn default_max_sleep_minutes() -> u32 {
+    60
+}
+
+fn default_garbage_collection_interval_hours() -> u32 {
+    24
+}
+
+/// Sport Log Scheduler
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+struct Args {
+    /// keep running and schedule the next run instead of running only once
+    #[arg(short, long)]
+    daemon: bool,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
     if env::var("RUST_LOG").is_err() {
         if cfg!(debug_assertions) {
             env::set_var("RUST_LOG", "warn,sport_log_scheduler=debug");
@@ -68,6 +120,8 @@ fn main() -> ExitCode {
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
+    let args = Args::parse();
+
     let config_file = match fs::read_to_string(CONFIG_FILE) {
         Ok(file) => file,
         Err(error) => {
@@ -84,16 +138,36 @@ fn main() -> ExitCode {
     };
 
     let client = Client::new();
-    if let Err(error) = create_action_events(&client, &config) {
+    if args.daemon {
+        return daemon(&client, &config).await;
+    }
+
+    let now = Utc::now();
+    let creatable_action_rules = match get_creatable_action_rules(&client, &config).await {
+        Ok(creatable_action_rules) => creatable_action_rules,
+        Err(error) => {
+            error!("failed to get creatable action rules: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = create_action_events(&client, &config, &creatable_action_rules, now).await {
         error!("failed to create new action events: {error}");
         return ExitCode::FAILURE;
     };
-    if let Err(error) = delete_action_events(&client, &config) {
+    let deletable_action_events = match get_deletable_action_events(&client, &config).await {
+        Ok(deletable_action_events) => deletable_action_events,
+        Err(error) => {
+            error!("failed to get deletable action events: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = delete_action_events(&client, &config, &deletable_action_events, now).await
+    {
         error!("failed to delete old action events: {error}");
         return ExitCode::FAILURE;
     }
 
-    if let Err(error) = garbage_collection(&client, &config) {
+    if let Err(error) = garbage_collection(&client, &config).await {
         error!("failed to run garbage collection: {error}");
         return ExitCode::FAILURE;
     }
@@ -101,7 +175,108 @@ fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
-fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestError> {
+async fn daemon(client: &Client, config: &Config) -> ExitCode {
+    let mut terminate = match unix_signal::signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(error) => {
+            error!("failed to register SIGTERM handler: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut last_garbage_collection = None;
+    let mut backoff = Duration::try_seconds(MIN_BACKOFF_SECONDS).unwrap();
+    loop {
+        let next_run = match run(client, config, &mut last_garbage_collection).await {
+            Ok(next_run) => {
+                backoff = Duration::try_seconds(MIN_BACKOFF_SECONDS).unwrap();
+                next_run
+            }
+            Err(error) => {
+                warn!(
+                    "failed to reach the server: {error}; retrying in {} seconds",
+                    backoff.num_seconds()
+                );
+                let next_run = Utc::now() + backoff;
+                backoff = (backoff * 2).min(Duration::try_seconds(MAX_BACKOFF_SECONDS).unwrap());
+                next_run
+            }
+        };
+
+        info!("next run at {next_run}");
+        let sleep = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            () = time::sleep(sleep) => {}
+            _ = terminate.recv() => {
+                info!("received SIGTERM, shutting down");
+                return ExitCode::SUCCESS;
+            }
+            _ = signal::ctrl_c() => {
+                info!("received SIGINT, shutting down");
+                return ExitCode::SUCCESS;
+            }
+        }
+    }
+}
+
+/// Create and delete action events and run the garbage collection if it is due.
+///
+/// Returns the datetime of the next run.
+async fn run(
+    client: &Client,
+    config: &Config,
+    last_garbage_collection: &mut Option<DateTime<Utc>>,
+) -> Result<DateTime<Utc>, ReqwestError> {
+    let now = Utc::now();
+    let max_next_run = now + Duration::try_minutes(config.max_sleep_minutes as i64).unwrap();
+
+    let creatable_action_rules = get_creatable_action_rules(client, config).await?;
+    create_action_events(client, config, &creatable_action_rules, now).await?;
+
+    let deletable_action_events = get_deletable_action_events(client, config).await?;
+    delete_action_events(client, config, &deletable_action_events, now).await?;
+
+    let garbage_collection_interval =
+        Duration::try_hours(config.garbage_collection_interval_hours as i64).unwrap();
+    let next_garbage_collection = match *last_garbage_collection {
+        Some(last_garbage_collection)
+            if now < last_garbage_collection + garbage_collection_interval =>
+        {
+            last_garbage_collection + garbage_collection_interval
+        }
+        _ => {
+            garbage_collection(client, config).await?;
+            *last_garbage_collection = Some(now);
+            now + garbage_collection_interval
+        }
+    };
+
+    let time_zones = load_time_zones(&creatable_action_rules);
+    let next_creation = creatable_action_rules
+        .iter()
+        .filter_map(|creatable_action_rule| {
+            let &tz = time_zones.get(&creatable_action_rule.time_zone)?;
+            next_creation_for_rule(creatable_action_rule, tz, now, max_next_run)
+        })
+        .min();
+    let next_deletion = deletable_action_events
+        .iter()
+        .map(deletion_datetime)
+        .filter(|datetime| *datetime > now)
+        .min();
+
+    Ok(
+        [next_creation, next_deletion, Some(next_garbage_collection)]
+            .into_iter()
+            .flatten()
+            .fold(max_next_run, DateTime::min),
+    )
+}
+
+async fn get_creatable_action_rules(
+    client: &Client,
+    config: &Config,
+) -> Result<Vec<CreatableActionRule>, ReqwestError> {
     let creatable_action_rules: Vec<CreatableActionRule> = client
         .get(route_max_version(
             &config.server_url,
@@ -109,9 +284,11 @@ fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
             None,
         ))
         .basic_auth(ADMIN_USERNAME, Some(&config.admin_password))
-        .send()?
+        .send()
+        .await?
         .error_for_status()?
-        .json()?;
+        .json()
+        .await?;
 
     info!(
         "got {} creatable action events",
@@ -119,11 +296,46 @@ fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
     );
     debug!("{:#?}", creatable_action_rules);
 
+    Ok(creatable_action_rules)
+}
+
+/// Load the time zones of the rules.
+///
+/// Time zones that can not be loaded are logged and skipped.
+fn load_time_zones(creatable_action_rules: &[CreatableActionRule]) -> HashMap<String, Tz> {
+    let mut time_zones = HashMap::new();
+    for creatable_action_rule in creatable_action_rules {
+        if time_zones.contains_key(&creatable_action_rule.time_zone) {
+            continue;
+        }
+        match creatable_action_rule.time_zone.parse::<Tz>() {
+            Ok(tz) => {
+                time_zones.insert(creatable_action_rule.time_zone.clone(), tz);
+            }
+            Err(error) => error!(
+                "skipping action rule {:?} with time zone {}: {error}",
+                creatable_action_rule.action_rule_id, creatable_action_rule.time_zone
+            ),
+        }
+    }
+    time_zones
+}
+
+async fn create_action_events(
+    client: &Client,
+    config: &Config,
+    creatable_action_rules: &[CreatableActionRule],
+    now: DateTime<Utc>,
+) -> Result<(), ReqwestError> {
     let mut rng = rand::thread_rng();
 
+    let time_zones = load_time_zones(creatable_action_rules);
     let mut action_events = vec![];
     for creatable_action_rule in creatable_action_rules {
-        for datetime in datetimes_for_rule(&creatable_action_rule) {
+        let Some(&tz) = time_zones.get(&creatable_action_rule.time_zone) else {
+            continue;
+        };
+        for datetime in datetimes_for_rule_from_start(creatable_action_rule, tz, now) {
             action_events.push(ActionEvent {
                 id: ActionEventId(rng.gen()),
                 user_id: creatable_action_rule.user_id,
@@ -131,6 +343,9 @@ fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
                 datetime,
                 arguments: creatable_action_rule.arguments.clone(),
                 enabled: true,
+                attempts: 0,
+                retry_after: None,
+                time_zone: creatable_action_rule.time_zone.clone(),
                 deleted: false,
             });
         }
@@ -147,7 +362,8 @@ fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
         ))
         .basic_auth(ADMIN_USERNAME, Some(&config.admin_password))
         .json(&action_events)
-        .send()?
+        .send()
+        .await?
         .error_for_status()?;
 
     info!("creation of action events successful");
@@ -155,46 +371,105 @@ fn create_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
     Ok(())
 }
 
-fn datetimes_for_rule(creatable_action_rule: &CreatableActionRule) -> Vec<DateTime<Utc>> {
-    datetimes_for_rule_from_start(creatable_action_rule, Utc::now())
+/// The datetimes of the events of the rule within its `create_before` window starting at `start`.
+fn datetimes_for_rule_from_start(
+    creatable_action_rule: &CreatableActionRule,
+    tz: Tz,
+    start: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    datetimes_for_rule_between(
+        creatable_action_rule,
+        tz,
+        start,
+        start + Duration::try_milliseconds(creatable_action_rule.create_before as i64).unwrap(),
+    )
 }
 
-fn datetimes_for_rule_from_start(
+/// The datetime at which the next event of the rule enters its `create_before` window if this is before `until`.
+fn next_creation_for_rule(
+    creatable_action_rule: &CreatableActionRule,
+    tz: Tz,
+    now: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let create_before =
+        Duration::try_milliseconds(creatable_action_rule.create_before as i64).unwrap();
+    datetimes_for_rule_between(
+        creatable_action_rule,
+        tz,
+        now + create_before + Duration::try_milliseconds(1).unwrap(),
+        until + create_before,
+    )
+    .first()
+    .map(|datetime| *datetime - create_before)
+}
+
+/// Convert the local time `local` in the time zone `tz` to UTC.
+///
+/// If the local time occurs twice because the clocks are set back the earlier one is used.
+/// If the local time does not exist because the clocks are set forward it is shifted forward by the length of the gap.
+fn from_local(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => {
+            datetime.with_timezone(&Utc)
+        }
+        LocalResult::None => {
+            let offset_before = tz
+                .offset_from_utc_datetime(&(local - Duration::try_days(1).unwrap()))
+                .fix();
+            (local - Duration::try_seconds(offset_before.local_minus_utc().into()).unwrap())
+                .and_utc()
+        }
+    }
+}
+
+/// The datetimes of the events of the rule between `start` and `end` (both inclusive).
+fn datetimes_for_rule_between(
     creatable_action_rule: &CreatableActionRule,
-    mut start: DateTime<Utc>,
+    tz: Tz,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
 ) -> Vec<DateTime<Utc>> {
-    if start.time() > creatable_action_rule.time.time() {
-        start += Duration::try_days(1).unwrap();
+    fn week_start(date: NaiveDate) -> NaiveDate {
+        date - Days::new(date.weekday().num_days_from_monday() as u64)
     }
-    let first_datetime = DateTime::from_naive_utc_and_offset(
-        start
-            .date_naive()
-            .and_time(creatable_action_rule.time.time())
-            + Days::new(
-                (creatable_action_rule.weekday.to_u32() as i64
-                    - start.weekday().num_days_from_monday() as i64)
-                    .rem_euclid(7) as u64,
-            ),
-        Utc,
-    );
+
+    let reference = creatable_action_rule.time.with_timezone(&tz).naive_local();
+    let reference_week = week_start(reference.date());
+    let week_interval = creatable_action_rule.week_interval.max(1) as i64;
 
     let mut datetimes = vec![];
-    for weeks in 0.. {
-        let datetime = first_datetime + Duration::try_weeks(weeks).unwrap();
-        if datetime
-            <= start
-                + Duration::try_milliseconds(creatable_action_rule.create_before as i64).unwrap()
+    let mut date = start.with_timezone(&tz).date_naive();
+    loop {
+        let datetime = from_local(tz, date.and_time(reference.time()));
+        if datetime > end {
+            break;
+        }
+
+        let weekday = date.weekday().num_days_from_monday();
+        let weeks = (week_start(date) - reference_week).num_days() / 7;
+        if datetime >= start
+            && (creatable_action_rule.weekday.to_u32() == weekday
+                || creatable_action_rule
+                    .additional_weekdays
+                    .iter()
+                    .any(|additional_weekday| additional_weekday.to_u32() == weekday))
+            && weeks.rem_euclid(week_interval) == 0
+            && !creatable_action_rule.exclusion_dates.contains(&date)
         {
             datetimes.push(datetime);
-        } else {
-            break;
         }
+
+        date = date + Days::new(1);
     }
 
     datetimes
 }
 
-fn delete_action_events(client: &Client, config: &Config) -> Result<(), ReqwestError> {
+async fn get_deletable_action_events(
+    client: &Client,
+    config: &Config,
+) -> Result<Vec<DeletableActionEvent>, ReqwestError> {
     let deletable_action_events: Vec<DeletableActionEvent> = client
         .get(route_max_version(
             &config.server_url,
@@ -202,9 +477,11 @@ fn delete_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
             None,
         ))
         .basic_auth(ADMIN_USERNAME, Some(&config.admin_password))
-        .send()?
+        .send()
+        .await?
         .error_for_status()?
-        .json()?;
+        .json()
+        .await?;
 
     info!(
         "got {} deletable action events",
@@ -212,15 +489,25 @@ fn delete_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
     );
     debug!("{:#?}", deletable_action_events);
 
-    let mut action_event_ids = vec![];
-    for deletable_action_event in deletable_action_events {
-        if Utc::now()
-            >= deletable_action_event.datetime
-                + Duration::try_milliseconds(deletable_action_event.delete_after as i64).unwrap()
-        {
-            action_event_ids.push(deletable_action_event.action_event_id);
-        }
-    }
+    Ok(deletable_action_events)
+}
+
+fn deletion_datetime(deletable_action_event: &DeletableActionEvent) -> DateTime<Utc> {
+    deletable_action_event.datetime
+        + Duration::try_milliseconds(deletable_action_event.delete_after as i64).unwrap()
+}
+
+async fn delete_action_events(
+    client: &Client,
+    config: &Config,
+    deletable_action_events: &[DeletableActionEvent],
+    now: DateTime<Utc>,
+) -> Result<(), ReqwestError> {
+    let action_event_ids: Vec<_> = deletable_action_events
+        .iter()
+        .filter(|deletable_action_event| now >= deletion_datetime(deletable_action_event))
+        .map(|deletable_action_event| deletable_action_event.action_event_id)
+        .collect();
 
     info!("deleting {} action events", action_event_ids.len());
     debug!("{action_event_ids:#?}");
@@ -233,7 +520,8 @@ fn delete_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
         ))
         .basic_auth(ADMIN_USERNAME, Some(&config.admin_password))
         .json(&action_event_ids)
-        .send()?
+        .send()
+        .await?
         .error_for_status()?;
 
     info!("action events have been successfully deleted");
@@ -241,7 +529,7 @@ fn delete_action_events(client: &Client, config: &Config) -> Result<(), ReqwestE
     Ok(())
 }
 
-fn garbage_collection(client: &Client, config: &Config) -> Result<(), ReqwestError> {
+async fn garbage_collection(client: &Client, config: &Config) -> Result<(), ReqwestError> {
     if config.garbage_collection_min_days > 0 {
         info!(
             "deleting if older than {} days",
@@ -260,7 +548,8 @@ fn garbage_collection(client: &Client, config: &Config) -> Result<(), ReqwestErr
                 )]),
             ))
             .basic_auth(ADMIN_USERNAME, Some(&config.admin_password))
-            .send()?
+            .send()
+            .await?
             .error_for_status()?;
 
         info!("old data has been successfully deleted");
@@ -273,28 +562,39 @@ fn garbage_collection(client: &Client, config: &Config) -> Result<(), ReqwestErr
 mod tests {
     use std::str::FromStr;
 
-    use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+    use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+    use chrono_tz::{Europe::Vienna, Tz};
     use sport_log_types::{ActionId, ActionRuleId, CreatableActionRule, UserId, Weekday};
 
+    use super::{datetimes_for_rule_from_start, next_creation_for_rule};
+
     fn datetime(datetime: &str) -> DateTime<Utc> {
         DateTime::from_naive_utc_and_offset(NaiveDateTime::from_str(datetime).unwrap(), Utc)
     }
 
-    #[test]
-    fn datetimes_for_rule_from_start() {
-        use super::datetimes_for_rule_from_start;
-        let rule = CreatableActionRule {
+    fn rule(weekday: Weekday, time: &str, time_zone: &str) -> CreatableActionRule {
+        CreatableActionRule {
             action_rule_id: ActionRuleId(1),
             user_id: UserId(1),
             action_id: ActionId(1),
-            weekday: Weekday::Monday,
-            time: datetime("2000-01-01T12:00:00"),
+            weekday,
+            time: datetime(time),
+            time_zone: time_zone.to_owned(),
+            additional_weekdays: vec![],
+            week_interval: 1,
+            exclusion_dates: vec![],
             arguments: None,
             create_before: Duration::try_days(14).unwrap().num_milliseconds() as i32,
-        };
+        }
+    }
+
+    #[test]
+    fn utc() {
+        let rule = rule(Weekday::Monday, "2000-01-01T12:00:00", "UTC");
 
         // next day and in 8 days
-        let datetimes = datetimes_for_rule_from_start(&rule, datetime("2023-01-01T00:00:00"));
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Tz::UTC, datetime("2023-01-01T00:00:00"));
         // 2023-01-01 is Sunday
         assert_eq!(
             datetimes,
@@ -305,7 +605,8 @@ mod tests {
         );
 
         // not on same day but in 7 and 14 days (but earlier time)
-        let datetimes = datetimes_for_rule_from_start(&rule, datetime("2023-01-02T12:00:01"));
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Tz::UTC, datetime("2023-01-02T12:00:01"));
         // 2023-01-02 is Monday
         assert_eq!(
             datetimes,
@@ -315,4 +616,100 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn dst_end() {
+        // 17:00 in Vienna, the clocks are set back on 2026-10-25
+        let rule = rule(Weekday::Monday, "2026-10-19T15:00:00", "Europe/Vienna");
+
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Vienna, datetime("2026-10-19T00:00:00"));
+        assert_eq!(
+            datetimes,
+            [
+                datetime("2026-10-19T15:00:00"),
+                datetime("2026-10-26T16:00:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn dst_start() {
+        // 17:00 in Vienna, the clocks are set forward on 2026-03-29
+        let rule = rule(Weekday::Monday, "2026-01-05T16:00:00", "Europe/Vienna");
+
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Vienna, datetime("2026-03-23T00:00:00"));
+        assert_eq!(
+            datetimes,
+            [
+                datetime("2026-03-23T16:00:00"),
+                datetime("2026-03-30T15:00:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nonexistent_local_time() {
+        // 02:30 in Vienna does not exist on 2026-03-29 and is shifted to 03:30
+        let rule = rule(Weekday::Sunday, "2026-01-04T01:30:00", "Europe/Vienna");
+
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Vienna, datetime("2026-03-22T12:00:00"));
+        assert_eq!(
+            datetimes,
+            [
+                datetime("2026-03-29T01:30:00"),
+                datetime("2026-04-05T00:30:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekdays_interval_and_exclusions() {
+        let mut rule = rule(Weekday::Monday, "2026-10-19T15:00:00", "Europe/Vienna");
+        rule.additional_weekdays = vec![Weekday::Wednesday, Weekday::Friday];
+        rule.week_interval = 2;
+        rule.exclusion_dates = vec![NaiveDate::from_ymd_opt(2026, 10, 21).unwrap()];
+        rule.create_before = Duration::try_days(21).unwrap().num_milliseconds() as i32;
+
+        let datetimes =
+            datetimes_for_rule_from_start(&rule, Vienna, datetime("2026-10-19T00:00:00"));
+        assert_eq!(
+            datetimes,
+            [
+                datetime("2026-10-19T15:00:00"),
+                datetime("2026-10-23T15:00:00"),
+                datetime("2026-11-02T16:00:00"),
+                datetime("2026-11-04T16:00:00"),
+                datetime("2026-11-06T16:00:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_creation() {
+        // 17:00 in Vienna with a window of 14 days
+        let rule = rule(Weekday::Monday, "2026-10-19T15:00:00", "Europe/Vienna");
+
+        // the event on 2026-11-02 enters the window 14 days before, the earlier ones have already been created
+        assert_eq!(
+            next_creation_for_rule(
+                &rule,
+                Vienna,
+                datetime("2026-10-19T12:00:00"),
+                datetime("2026-10-20T12:00:00"),
+            ),
+            Some(datetime("2026-10-19T16:00:00"))
+        );
+        assert_eq!(
+            next_creation_for_rule(
+                &rule,
+                Vienna,
+                datetime("2026-10-19T12:00:00"),
+                datetime("2026-10-19T13:00:00"),
+            ),
+            None
+        );
+    }
 }
@@ -83,7 +83,15 @@ where
 /// In order to do so, the username must be `admin`, the password must be the `admin_password` as configured in `sport-log-server.toml`
 /// and a `id` header must be preset that is set to the id of the user the admin wants to authenticate as.
 #[derive(Debug, Clone, Copy)]
-pub struct AuthUserOrAP(UserId);
+pub struct AuthUserOrAP(UserId, Option<ActionProviderId>);
+
+impl AuthUserOrAP {
+    /// The id of the [`ActionProvider`](sport_log_types::ActionProvider) that authenticated as the user
+    /// or `None` if the user or the admin authenticated.
+    pub fn action_provider_id(&self) -> Option<ActionProviderId> {
+        self.1
+    }
+}
 
 impl Deref for AuthUserOrAP {
     type Target = UserId;
@@ -113,20 +121,22 @@ where
         let mut db = db_pool.get()?;
 
         if let Ok(id) = UserDb::auth(username, password, &mut db) {
-            return Ok(Self(id));
+            return Ok(Self(id, None));
         }
 
         let user_id = parse_id_header(parts, UserId)?;
         if let Ok(auth) = ActionProviderDb::auth_as_user(username, password, user_id, &mut db) {
             match auth {
-                AuthApForUser::Allowed(_) => return Ok(Self(user_id)),
+                AuthApForUser::Allowed(action_provider_id) => {
+                    return Ok(Self(user_id, Some(action_provider_id)))
+                }
                 AuthApForUser::Forbidden => return Err(StatusCode::FORBIDDEN.into()),
             }
         }
 
         let admin_password = &config.admin_password;
         if AdminDb::auth(username, password, admin_password).is_ok() {
-            return Ok(Self(user_id));
+            return Ok(Self(user_id, None));
         }
         Err(StatusCode::UNAUTHORIZED.into())
     }
@@ -9,6 +9,10 @@ use serde::{Deserialize, Serialize};
 /// `user_self_registration` determines if users can register themselves or if only the admin can create new users.
 ///
 /// `ap_self_registration` determines if action providers can register themselves or if only the admin can create new action provider.
+///
+/// `catalogue_file` is the path to a TOML file containing the [`Catalogue`](crate::db::Catalogue) of predefined movements and metcons which is applied on startup if its version is newer than the current one.
+///
+/// `user_action_providers` are the names of the action providers that can get the [`User`](sport_log_types::User), including the email address, of the users they have an enabled [`ActionEvent`](sport_log_types::ActionEvent) for.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
     pub admin_password: String,
@@ -18,4 +22,7 @@ pub struct Config {
     pub release_address: SocketAddr,
     pub debug_address: SocketAddr,
     pub app_dir: Option<PathBuf>,
+    pub catalogue_file: Option<PathBuf>,
+    #[serde(default)]
+    pub user_action_providers: Vec<String>,
 }
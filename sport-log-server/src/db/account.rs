@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
-use diesel::{PgConnection, QueryResult};
-use sport_log_types::{AccountData, UserId};
+use diesel::{prelude::*, PgConnection, QueryResult};
+use sport_log_types::{
+    schema::{metcon, metcon_session},
+    AccountData, MetconId, MetconSessionId, MetconType, UserId,
+};
 
 use crate::db::*;
 
@@ -8,7 +11,7 @@ pub struct AccountDataDb;
 
 impl AccountDataDb {
     pub fn get_by_user(user_id: UserId, db: &mut PgConnection) -> QueryResult<AccountData> {
-        Ok(AccountData {
+        let mut account_data = AccountData {
             user: Some(UserDb::get_by_id(user_id, db)?),
             diaries: DiaryDb::get_by_user(user_id, db)?,
             wods: WodDb::get_by_user(user_id, db)?,
@@ -16,18 +19,27 @@ impl AccountDataDb {
             movement_muscles: MovementMuscleDb::get_by_user(user_id, db)?,
             strength_sessions: StrengthSessionDb::get_by_user(user_id, db)?,
             strength_sets: StrengthSetDb::get_by_user(user_id, db)?,
+            strength_supersets: StrengthSupersetDb::get_by_user(user_id, db)?,
             metcons: MetconDb::get_by_user(user_id, db)?,
             metcon_sessions: MetconSessionDb::get_by_user(user_id, db)?,
             metcon_movements: MetconMovementDb::get_by_user(user_id, db)?,
+            metcon_splits: MetconSplitDb::get_by_user(user_id, db)?,
             cardio_sessions: CardioSessionDb::get_by_user(user_id, db)?,
             routes: RouteDb::get_by_user(user_id, db)?,
+            training_plans: TrainingPlanDb::get_by_user(user_id, db)?,
+            training_plan_entries: TrainingPlanEntryDb::get_by_user(user_id, db)?,
+            planned_sessions: PlannedSessionDb::get_by_user(user_id, db)?,
             platforms: PlatformDb::get_all(db)?,
             platform_credentials: PlatformCredentialDb::get_by_user(user_id, db)?,
+            activity_mappings: ActivityMappingDb::get_by_user(user_id, db)?,
             action_providers: ActionProviderDb::get_all(db)?,
             actions: ActionDb::get_all(db)?,
             action_rules: ActionRuleDb::get_by_user(user_id, db)?,
             action_events: ActionEventDb::get_by_user(user_id, db)?,
-        })
+            action_event_results: ActionEventResultDb::get_by_user(user_id, db)?,
+        };
+        Self::remove_unsupported_metcons(&mut account_data, user_id, db)?;
+        Ok(account_data)
     }
 
     pub fn get_by_user_and_last_sync(
@@ -35,7 +47,7 @@ impl AccountDataDb {
         last_sync: DateTime<Utc>,
         db: &mut PgConnection,
     ) -> QueryResult<AccountData> {
-        Ok(AccountData {
+        let mut account_data = AccountData {
             user: UserDb::get_by_id_and_last_sync(user_id, last_sync, db)?,
             diaries: DiaryDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             wods: WodDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
@@ -45,19 +57,77 @@ impl AccountDataDb {
                 user_id, last_sync, db,
             )?,
             strength_sets: StrengthSetDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
+            strength_supersets: StrengthSupersetDb::get_by_user_and_last_sync(
+                user_id, last_sync, db,
+            )?,
             metcons: MetconDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             metcon_sessions: MetconSessionDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             metcon_movements: MetconMovementDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
+            metcon_splits: MetconSplitDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             cardio_sessions: CardioSessionDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             routes: RouteDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
+            training_plans: TrainingPlanDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
+            training_plan_entries: TrainingPlanEntryDb::get_by_user_and_last_sync(
+                user_id, last_sync, db,
+            )?,
+            planned_sessions: PlannedSessionDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             platforms: PlatformDb::get_by_last_sync(last_sync, db)?,
             platform_credentials: PlatformCredentialDb::get_by_user_and_last_sync(
                 user_id, last_sync, db,
             )?,
+            activity_mappings: ActivityMappingDb::get_by_user_and_last_sync(
+                user_id, last_sync, db,
+            )?,
             action_providers: ActionProviderDb::get_by_last_sync(last_sync, db)?,
             actions: ActionDb::get_by_last_sync(last_sync, db)?,
             action_rules: ActionRuleDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
             action_events: ActionEventDb::get_by_user_and_last_sync(user_id, last_sync, db)?,
-        })
+            action_event_results: ActionEventResultDb::get_by_user_and_last_sync(
+                user_id, last_sync, db,
+            )?,
+        };
+        Self::remove_unsupported_metcons(&mut account_data, user_id, db)?;
+        Ok(account_data)
+    }
+
+    /// Removes metcons the app cannot display yet together with their movements, sessions and splits.
+    ///
+    /// Remove this once the app supports [`MetconType::Tabata`] and [`MetconType::Intervals`].
+    fn remove_unsupported_metcons(
+        account_data: &mut AccountData,
+        user_id: UserId,
+        db: &mut PgConnection,
+    ) -> QueryResult<()> {
+        let metcon_ids: Vec<MetconId> = metcon::table
+            .filter(
+                metcon::columns::user_id
+                    .eq(user_id)
+                    .or(metcon::columns::user_id.is_null()),
+            )
+            .filter(
+                metcon::columns::metcon_type.eq_any([MetconType::Tabata, MetconType::Intervals]),
+            )
+            .select(metcon::columns::id)
+            .get_results(db)?;
+        let metcon_session_ids: Vec<MetconSessionId> = metcon_session::table
+            .filter(metcon_session::columns::user_id.eq(user_id))
+            .filter(metcon_session::columns::metcon_id.eq_any(&metcon_ids))
+            .select(metcon_session::columns::id)
+            .get_results(db)?;
+
+        account_data
+            .metcons
+            .retain(|metcon| !metcon_ids.contains(&metcon.id));
+        account_data
+            .metcon_movements
+            .retain(|metcon_movement| !metcon_ids.contains(&metcon_movement.metcon_id));
+        account_data
+            .metcon_sessions
+            .retain(|metcon_session| !metcon_ids.contains(&metcon_session.metcon_id));
+        account_data
+            .metcon_splits
+            .retain(|metcon_split| !metcon_session_ids.contains(&metcon_split.metcon_session_id));
+
+        Ok(())
     }
 }
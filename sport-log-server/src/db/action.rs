@@ -1,12 +1,18 @@
+use std::collections::HashMap;
+
 use argon2::{password_hash::SaltString, PasswordHash, PasswordHasher, PasswordVerifier};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::{prelude::*, result::Error};
 use rand_core::OsRng;
 use sport_log_derive::*;
 use sport_log_types::{
-    schema::{action, action_event, action_provider, action_rule, platform_credential},
-    Action, ActionEvent, ActionEventId, ActionProviderId, ActionRuleId, CreatableActionRule,
-    DeletableActionEvent, ExecutableActionEvent, UserId,
+    schema::{
+        action, action_event, action_provider, action_provider_heartbeat, action_rule,
+        platform_credential,
+    },
+    Action, ActionEvent, ActionEventId, ActionEventResult, ActionEventStatus, ActionId,
+    ActionProviderHealth, ActionProviderHeartbeat, ActionProviderId, ActionRuleId,
+    CreatableActionRule, DeletableActionEvent, ExecutableActionEvent, UserId,
 };
 
 use crate::{auth::*, db::*};
@@ -130,17 +136,114 @@ impl ActionProviderDb {
     }
 }
 
+pub struct ActionProviderHeartbeatDb;
+
+impl VerifyForActionProviderWithoutDb for Unverified<ActionProviderHeartbeat> {
+    type Type = ActionProviderHeartbeat;
+
+    fn verify_ap_without_db(self, auth: AuthAP) -> Result<Self::Type, StatusCode> {
+        let heartbeat = self.0;
+        if heartbeat.action_provider_id == *auth {
+            Ok(heartbeat)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl ActionProviderHeartbeatDb {
+    /// Create or replace the heartbeat of the action provider.
+    pub fn upsert(
+        heartbeat: &ActionProviderHeartbeat,
+        db: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::insert_into(action_provider_heartbeat::table)
+            .values(heartbeat)
+            .on_conflict(action_provider_heartbeat::columns::action_provider_id)
+            .do_update()
+            .set(heartbeat)
+            .execute(db)
+    }
+
+    pub fn get_health(
+        now: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<ActionProviderHealth>> {
+        Self::get_health_filtered(None, now, db)
+    }
+
+    /// Get the health of the action providers of the actions the user has action rules for.
+    pub fn get_health_by_user(
+        user_id: UserId,
+        now: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<ActionProviderHealth>> {
+        Self::get_health_filtered(Some(user_id), now, db)
+    }
+
+    fn get_health_filtered(
+        user_id: Option<UserId>,
+        now: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<ActionProviderHealth>> {
+        let mut query = action_provider::table
+            .left_outer_join(action_provider_heartbeat::table)
+            .filter(action_provider::columns::deleted.eq(false))
+            .select((
+                action_provider::columns::id,
+                action_provider::columns::name,
+                (
+                    action_provider_heartbeat::columns::action_provider_id,
+                    action_provider_heartbeat::columns::version,
+                    action_provider_heartbeat::columns::datetime,
+                    action_provider_heartbeat::columns::run_interval,
+                    action_provider_heartbeat::columns::action_events,
+                    action_provider_heartbeat::columns::successes,
+                    action_provider_heartbeat::columns::user_errors,
+                    action_provider_heartbeat::columns::transient_errors,
+                )
+                    .nullable(),
+            ))
+            .order_by(action_provider::columns::name)
+            .into_boxed();
+        if let Some(user_id) = user_id {
+            query = query.filter(
+                action_provider::columns::id.eq_any(
+                    action_rule::table
+                        .inner_join(action::table)
+                        .filter(action_rule::columns::user_id.eq(user_id))
+                        .filter(action_rule::columns::deleted.eq(false))
+                        .select(action::columns::action_provider_id),
+                ),
+            );
+        }
+
+        query
+            .get_results::<(ActionProviderId, String, Option<ActionProviderHeartbeat>)>(db)
+            .map(|health| {
+                health
+                    .into_iter()
+                    .map(|(id, name, heartbeat)| {
+                        ActionProviderHealth::new(id, name, heartbeat, now)
+                    })
+                    .collect()
+            })
+    }
+}
+
 #[derive(
     Db,
     DbWithApId,
     ModifiableDb,
     VerifyIdUnchecked,
     VerifyIdForActionProvider,
+    VerifyIdsForActionProvider,
     Create,
     GetById,
     GetByIds,
     GetAll,
     GetBySync,
+    Update,
     HardDelete,
     CheckAPId,
     VerifyForActionProviderWithDb,
@@ -158,6 +261,12 @@ impl ActionDb {
             .select(Action::as_select())
             .get_results(db)
     }
+
+    pub fn delete_multiple(action_ids: Vec<ActionId>, db: &mut PgConnection) -> QueryResult<usize> {
+        diesel::update(action::table.filter(action::columns::id.eq_any(action_ids)))
+            .set(action::columns::deleted.eq(true))
+            .execute(db)
+    }
 }
 
 #[derive(
@@ -178,6 +287,12 @@ impl ActionDb {
 )]
 pub struct ActionRuleDb;
 
+/// The delay in minutes before the first retry of a transiently failed [`ActionEvent`].
+///
+/// The delay doubles with every further failed attempt up to [`MAX_RETRY_DELAY_MINUTES`].
+const RETRY_DELAY_MINUTES: i64 = 10;
+const MAX_RETRY_DELAY_MINUTES: i64 = 6 * 60;
+
 #[derive(
     Db,
     DbWithUserId,
@@ -252,6 +367,46 @@ impl ActionEventDb {
         .execute(db)
     }
 
+    /// Count a failed attempt for each action event and postpone its next execution with an exponential backoff.
+    ///
+    /// Action events which reached the maximum number of attempts of their action are disabled.
+    pub fn register_failed_attempts(
+        action_event_ids: &[ActionEventId],
+        now: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<()> {
+        let attempts: Vec<(ActionEventId, i32, i32)> = action_event::table
+            .inner_join(action::table)
+            .filter(action_event::columns::id.eq_any(action_event_ids))
+            .select((
+                action_event::columns::id,
+                action_event::columns::attempts,
+                action::columns::max_attempts,
+            ))
+            .get_results(db)?;
+
+        for (action_event_id, attempts, max_attempts) in attempts {
+            let attempts = attempts + 1;
+            let retry_delay = Duration::try_minutes(
+                (RETRY_DELAY_MINUTES << (attempts.clamp(1, 16) - 1)).min(MAX_RETRY_DELAY_MINUTES),
+            )
+            .unwrap();
+            diesel::update(action_event::table.find(action_event_id))
+                .set((
+                    action_event::columns::attempts.eq(attempts),
+                    action_event::columns::retry_after.eq(now + retry_delay),
+                ))
+                .execute(db)?;
+            if attempts >= max_attempts {
+                diesel::update(action_event::table.find(action_event_id))
+                    .set(action_event::columns::enabled.eq(false))
+                    .execute(db)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn delete_multiple(
         action_event_ids: Vec<ActionEventId>,
         db: &mut PgConnection,
@@ -264,6 +419,96 @@ impl ActionEventDb {
     }
 }
 
+#[derive(
+    Db,
+    DbWithUserId,
+    ModifiableDb,
+    VerifyIdForUser,
+    Create,
+    GetById,
+    GetByIds,
+    GetByUser,
+    GetByUserSync,
+    HardDelete,
+    CheckUserId,
+)]
+pub struct ActionEventResultDb;
+
+impl ActionEventResultDb {
+    /// Create the action event results and register a failed attempt for every transiently failed action event.
+    pub fn create_multiple_and_register_failed_attempts(
+        action_event_results: &[ActionEventResult],
+        now: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        let retry_action_event_ids: Vec<_> = action_event_results
+            .iter()
+            .filter(|action_event_result| {
+                action_event_result.status == ActionEventStatus::TransientError
+            })
+            .map(|action_event_result| action_event_result.action_event_id)
+            .collect();
+
+        db.transaction(|db| {
+            let count = Self::create_multiple(action_event_results, db)?;
+            if !retry_action_event_ids.is_empty() {
+                ActionEventDb::register_failed_attempts(&retry_action_event_ids, now, db)?;
+            }
+            Ok(count)
+        })
+    }
+}
+
+/// Action providers may only report results for their own action events.
+///
+/// The user, action and datetime of the result must match the action event.
+impl VerifyMultipleForActionProviderWithDb for Unverified<Vec<ActionEventResult>> {
+    type Type = ActionEventResult;
+
+    fn verify_ap(self, auth: AuthAP, db: &mut PgConnection) -> Result<Vec<Self::Type>, StatusCode> {
+        let action_event_results = self.0;
+        let action_event_ids: Vec<_> = action_event_results
+            .iter()
+            .map(|action_event_result| action_event_result.action_event_id)
+            .collect();
+        if !ActionEventDb::check_ap_ids(&action_event_ids, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        let action_events: HashMap<_, _> = ActionEventDb::get_by_ids(&action_event_ids, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_iter()
+            .map(|action_event| (action_event.id, action_event))
+            .collect();
+        let matches_action_event = |action_event_result: &ActionEventResult| {
+            action_events
+                .get(&action_event_result.action_event_id)
+                .is_some_and(|action_event| {
+                    action_event.user_id == action_event_result.user_id
+                        && action_event.action_id == action_event_result.action_id
+                        && action_event.datetime == action_event_result.action_event_datetime
+                })
+        };
+        if action_event_results.iter().all(matches_action_event) {
+            Ok(action_event_results)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyForActionProviderWithDb for Unverified<ActionEventResult> {
+    type Type = ActionEventResult;
+
+    fn verify_ap(self, auth: AuthAP, db: &mut PgConnection) -> Result<Self::Type, StatusCode> {
+        Unverified(vec![self.0])
+            .verify_ap(auth, db)
+            .map(|mut action_event_results| action_event_results.remove(0))
+    }
+}
+
 pub struct CreatableActionRuleDb;
 
 impl Db for CreatableActionRuleDb {
@@ -292,6 +537,10 @@ impl GetAll for CreatableActionRuleDb {
                 action_rule::columns::action_id,
                 action_rule::columns::weekday,
                 action_rule::columns::time,
+                action_rule::columns::time_zone,
+                action_rule::columns::additional_weekdays,
+                action_rule::columns::week_interval,
+                action_rule::columns::exclusion_dates,
                 action_rule::columns::arguments,
                 action::columns::create_before,
             ))
@@ -330,11 +579,19 @@ impl ExecutableActionEventDb {
             .filter(action_provider::columns::id.eq(action_provider_id))
             .filter(action_event::columns::enabled.eq(true))
             .filter(action_event::columns::deleted.eq(false))
+            .filter(
+                action_event::columns::retry_after
+                    .is_null()
+                    .or(action_event::columns::retry_after.le(Utc::now())),
+            )
             .select((
                 action_event::columns::id,
+                action::columns::id,
                 action::columns::name,
                 action_event::columns::datetime,
                 action_event::columns::arguments,
+                action_event::columns::attempts,
+                action_event::columns::time_zone,
                 action_event::columns::user_id,
                 platform_credential::columns::username.nullable(),
                 platform_credential::columns::password.nullable(),
@@ -358,12 +615,20 @@ impl ExecutableActionEventDb {
             .filter(action_provider::columns::id.eq(action_provider_id))
             .filter(action_event::columns::enabled.eq(true))
             .filter(action_event::columns::deleted.eq(false))
+            .filter(
+                action_event::columns::retry_after
+                    .is_null()
+                    .or(action_event::columns::retry_after.le(Utc::now())),
+            )
             .filter(action_event::columns::datetime.between(start_datetime, end_datetime))
             .select((
                 action_event::columns::id,
+                action::columns::id,
                 action::columns::name,
                 action_event::columns::datetime,
                 action_event::columns::arguments,
+                action_event::columns::attempts,
+                action_event::columns::time_zone,
                 action_event::columns::user_id,
                 platform_credential::columns::username.nullable(),
                 platform_credential::columns::password.nullable(),
@@ -0,0 +1,74 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{prelude::*, upsert::excluded};
+use rand_core::{OsRng, RngCore};
+use sport_log_types::{
+    schema::{action, action_event, calendar_token, wod},
+    CalendarActionEvent, CalendarToken, UserId, Wod,
+};
+
+pub struct CalendarDb;
+
+impl CalendarDb {
+    /// Create a new calendar token for the user which replaces the previous one.
+    pub fn create_token(user_id: UserId, db: &mut PgConnection) -> QueryResult<CalendarToken> {
+        let mut bytes = [0; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+
+        diesel::insert_into(calendar_token::table)
+            .values(&CalendarToken { user_id, token })
+            .on_conflict(calendar_token::columns::user_id)
+            .do_update()
+            .set(calendar_token::columns::token.eq(excluded(calendar_token::columns::token)))
+            .returning(CalendarToken::as_returning())
+            .get_result(db)
+    }
+
+    pub fn delete_token(user_id: UserId, db: &mut PgConnection) -> QueryResult<usize> {
+        diesel::delete(calendar_token::table.find(user_id)).execute(db)
+    }
+
+    pub fn get_user_id_by_token(token: &str, db: &mut PgConnection) -> QueryResult<UserId> {
+        calendar_token::table
+            .filter(calendar_token::columns::token.eq(token))
+            .select(calendar_token::columns::user_id)
+            .get_result(db)
+    }
+
+    /// Get the enabled action events of the user starting at or after `start_datetime`.
+    pub fn get_action_events(
+        user_id: UserId,
+        start_datetime: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<CalendarActionEvent>> {
+        action_event::table
+            .inner_join(action::table)
+            .filter(action_event::columns::user_id.eq(user_id))
+            .filter(action_event::columns::enabled.eq(true))
+            .filter(action_event::columns::deleted.eq(false))
+            .filter(action_event::columns::datetime.ge(start_datetime))
+            .select((
+                action_event::columns::id,
+                action::columns::name,
+                action_event::columns::datetime,
+                action_event::columns::arguments,
+            ))
+            .order_by(action_event::columns::datetime)
+            .get_results(db)
+    }
+
+    /// Get the wods of the user dated at or after `start_date`.
+    pub fn get_wods(
+        user_id: UserId,
+        start_date: NaiveDate,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<Wod>> {
+        wod::table
+            .filter(wod::columns::user_id.eq(user_id))
+            .filter(wod::columns::deleted.eq(false))
+            .filter(wod::columns::date.ge(start_date))
+            .select(Wod::as_select())
+            .order_by(wod::columns::date)
+            .get_results(db)
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+use diesel::{dsl::exists, prelude::*, result::Error as DieselError, upsert::excluded};
+use serde::Deserialize;
+use sport_log_types::{
+    schema::{
+        activity_mapping, cardio_session, catalogue_version, metcon, metcon_movement,
+        metcon_session, movement, movement_muscle, muscle_group, strength_session,
+        training_plan_entry,
+    },
+    DistanceUnit, Metcon, MetconId, MetconMovement, MetconMovementId, MetconPart, MetconType,
+    Movement, MovementDimension, MovementId, MovementMuscle, MovementMuscleId, MuscleGroup,
+    MuscleGroupId,
+};
+use tracing::{info, warn};
+
+use crate::db::*;
+
+/// A versioned set of predefined [`MuscleGroup`]s, [`Movement`]s, [`MovementMuscle`]s, [`Metcon`]s and [`MetconMovement`]s.
+///
+/// All entries are identified by their `id` which therefore has to stay the same across versions.
+/// Predefined entries that are no longer part of the catalogue are soft deleted when it is applied.
+#[derive(Debug, Deserialize)]
+pub struct Catalogue {
+    pub version: i32,
+    #[serde(default)]
+    pub muscle_groups: Vec<CatalogueMuscleGroup>,
+    #[serde(default)]
+    pub movements: Vec<CatalogueMovement>,
+    #[serde(default)]
+    pub movement_muscles: Vec<CatalogueMovementMuscle>,
+    #[serde(default)]
+    pub metcons: Vec<CatalogueMetcon>,
+    #[serde(default)]
+    pub metcon_movements: Vec<CatalogueMetconMovement>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogueMuscleGroup {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogueMovement {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub movement_dimension: MovementDimension,
+    pub cardio: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogueMovementMuscle {
+    pub id: i64,
+    pub movement_id: i64,
+    pub muscle_group_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogueMetcon {
+    pub id: i64,
+    pub name: String,
+    pub metcon_type: MetconType,
+    pub rounds: Option<i32>,
+    pub timecap: Option<i32>,
+    pub work_time: Option<i32>,
+    pub rest_time: Option<i32>,
+    pub rep_scheme: Option<Vec<i32>>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogueMetconMovement {
+    pub id: i64,
+    pub metcon_id: i64,
+    pub movement_id: i64,
+    pub distance_unit: Option<DistanceUnit>,
+    #[serde(default)]
+    pub metcon_part: MetconPart,
+    pub movement_number: i32,
+    pub count: i32,
+    pub male_weight: Option<f32>,
+    pub female_weight: Option<f32>,
+}
+
+impl From<&CatalogueMuscleGroup> for MuscleGroup {
+    fn from(muscle_group: &CatalogueMuscleGroup) -> Self {
+        MuscleGroup {
+            id: MuscleGroupId(muscle_group.id),
+            name: muscle_group.name.clone(),
+            description: muscle_group.description.clone(),
+        }
+    }
+}
+
+impl From<&CatalogueMovement> for Movement {
+    fn from(movement: &CatalogueMovement) -> Self {
+        Movement {
+            id: MovementId(movement.id),
+            user_id: None,
+            name: movement.name.clone(),
+            description: movement.description.clone(),
+            movement_dimension: movement.movement_dimension,
+            cardio: movement.cardio,
+            deleted: false,
+        }
+    }
+}
+
+impl From<&CatalogueMovementMuscle> for MovementMuscle {
+    fn from(movement_muscle: &CatalogueMovementMuscle) -> Self {
+        MovementMuscle {
+            id: MovementMuscleId(movement_muscle.id),
+            movement_id: MovementId(movement_muscle.movement_id),
+            muscle_group_id: MuscleGroupId(movement_muscle.muscle_group_id),
+            deleted: false,
+        }
+    }
+}
+
+impl From<&CatalogueMetcon> for Metcon {
+    fn from(metcon: &CatalogueMetcon) -> Self {
+        Metcon {
+            id: MetconId(metcon.id),
+            user_id: None,
+            name: metcon.name.clone(),
+            metcon_type: metcon.metcon_type,
+            rounds: metcon.rounds,
+            timecap: metcon.timecap,
+            work_time: metcon.work_time,
+            rest_time: metcon.rest_time,
+            rep_scheme: metcon.rep_scheme.clone(),
+            description: metcon.description.clone(),
+            deleted: false,
+        }
+    }
+}
+
+impl From<&CatalogueMetconMovement> for MetconMovement {
+    fn from(metcon_movement: &CatalogueMetconMovement) -> Self {
+        MetconMovement {
+            id: MetconMovementId(metcon_movement.id),
+            metcon_id: MetconId(metcon_movement.metcon_id),
+            movement_id: MovementId(metcon_movement.movement_id),
+            distance_unit: metcon_movement.distance_unit,
+            metcon_part: metcon_movement.metcon_part,
+            movement_number: metcon_movement.movement_number,
+            count: metcon_movement.count,
+            male_weight: metcon_movement.male_weight,
+            female_weight: metcon_movement.female_weight,
+            deleted: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CatalogueError {
+    /// The catalogue version is not newer than the version that has already been applied.
+    Outdated {
+        version: i32,
+        current_version: i32,
+    },
+    /// The catalogue contains duplicate ids, invalid metcons or references entries which are not part of it.
+    Invalid(String),
+    Database(DieselError),
+}
+
+impl Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Outdated {
+                version,
+                current_version,
+            } => write!(
+                f,
+                "catalogue version {version} is not newer than the current version {current_version}"
+            ),
+            Self::Invalid(error) => write!(f, "invalid catalogue: {error}"),
+            Self::Database(error) => write!(f, "failed to apply catalogue: {error}"),
+        }
+    }
+}
+
+impl From<DieselError> for CatalogueError {
+    fn from(error: DieselError) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl Catalogue {
+    fn validate(&self) -> Result<(), CatalogueError> {
+        fn unique_ids<T>(
+            name: &str,
+            entries: &[T],
+            id: impl Fn(&T) -> i64,
+        ) -> Result<HashSet<i64>, CatalogueError> {
+            let mut ids = HashSet::new();
+            for entry in entries {
+                if !ids.insert(id(entry)) {
+                    return Err(CatalogueError::Invalid(format!(
+                        "duplicate {name} id {}",
+                        id(entry)
+                    )));
+                }
+            }
+            Ok(ids)
+        }
+
+        fn check_reference(name: &str, id: i64, ids: &HashSet<i64>) -> Result<(), CatalogueError> {
+            if ids.contains(&id) {
+                Ok(())
+            } else {
+                Err(CatalogueError::Invalid(format!("unknown {name} id {id}")))
+            }
+        }
+
+        if self.version < 1 {
+            return Err(CatalogueError::Invalid(
+                "version must be at least 1".to_owned(),
+            ));
+        }
+
+        let muscle_group_ids = unique_ids("muscle group", &self.muscle_groups, |m| m.id)?;
+        let movement_ids = unique_ids("movement", &self.movements, |m| m.id)?;
+        unique_ids("movement muscle", &self.movement_muscles, |m| m.id)?;
+        let metcon_ids = unique_ids("metcon", &self.metcons, |m| m.id)?;
+        unique_ids("metcon movement", &self.metcon_movements, |m| m.id)?;
+
+        for movement_muscle in &self.movement_muscles {
+            check_reference("movement", movement_muscle.movement_id, &movement_ids)?;
+            check_reference(
+                "muscle group",
+                movement_muscle.muscle_group_id,
+                &muscle_group_ids,
+            )?;
+        }
+        for metcon in &self.metcons {
+            Metcon::from(metcon).validate().map_err(|error| {
+                CatalogueError::Invalid(format!("metcon {}: {error}", metcon.id))
+            })?;
+        }
+        for metcon_movement in &self.metcon_movements {
+            check_reference("metcon", metcon_movement.metcon_id, &metcon_ids)?;
+            check_reference("movement", metcon_movement.movement_id, &movement_ids)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The changes that are necessary to bring the predefined entries of one table in line with the catalogue.
+struct Changes<T> {
+    /// Entries which do not exist yet.
+    create: Vec<T>,
+    /// Entries which exist but differ from the catalogue.
+    update: Vec<T>,
+    /// Entries which have been soft deleted by an earlier version but are part of the catalogue again.
+    restore: Vec<T>,
+    /// Entries which are not part of the catalogue anymore.
+    remove: Vec<T>,
+}
+
+impl<T: PartialEq> Changes<T> {
+    fn new<I: Eq + Hash>(
+        existing: Vec<T>,
+        entries: Vec<T>,
+        id: impl Fn(&T) -> I,
+        deleted: impl Fn(&T) -> bool,
+    ) -> Self {
+        let mut existing: HashMap<_, _> = existing.into_iter().map(|e| (id(&e), e)).collect();
+        let mut changes = Changes {
+            create: vec![],
+            update: vec![],
+            restore: vec![],
+            remove: vec![],
+        };
+        for entry in entries {
+            match existing.remove(&id(&entry)) {
+                None => changes.create.push(entry),
+                Some(old) if deleted(&old) => changes.restore.push(entry),
+                Some(old) if old != entry => changes.update.push(entry),
+                Some(_) => {}
+            }
+        }
+        changes.remove = existing.into_values().filter(|e| !deleted(e)).collect();
+        changes
+    }
+}
+
+pub struct CatalogueDb;
+
+impl CatalogueDb {
+    /// Get the version of the most recently applied [`Catalogue`].
+    pub fn get_version(db: &mut PgConnection) -> QueryResult<Option<i32>> {
+        catalogue_version::table
+            .select(diesel::dsl::max(catalogue_version::columns::version))
+            .get_result(db)
+    }
+
+    /// Apply the `catalogue` if its version is newer than the current one.
+    ///
+    /// Entries are created or updated by id, changed entries get a new `last_change` so that clients receive them during the next synchronization.
+    /// Removed entries are soft deleted unless they are still referenced by user data.
+    pub fn apply(catalogue: &Catalogue, db: &mut PgConnection) -> Result<(), CatalogueError> {
+        catalogue.validate()?;
+
+        db.transaction(|db| {
+            if let Some(current_version) = Self::get_version(db)? {
+                if catalogue.version <= current_version {
+                    return Err(CatalogueError::Outdated {
+                        version: catalogue.version,
+                        current_version,
+                    });
+                }
+            }
+
+            Self::upsert_muscle_groups(catalogue, db)?;
+            let movements = Self::upsert_movements(catalogue, db)?;
+            let movement_muscles = Self::upsert_movement_muscles(catalogue, db)?;
+            let metcons = Self::upsert_metcons(catalogue, db)?;
+            let metcon_movements = Self::upsert_metcon_movements(catalogue, db)?;
+
+            // remove children first so that they do not count as references of their parents
+            let movement_muscles: Vec<_> = movement_muscles
+                .into_iter()
+                .map(|m| MovementMuscle { deleted: true, ..m })
+                .collect();
+            MovementMuscleDb::update_multiple(&movement_muscles, db)?;
+            let metcon_movements: Vec<_> = metcon_movements
+                .into_iter()
+                .map(|m| MetconMovement { deleted: true, ..m })
+                .collect();
+            MetconMovementDb::update_multiple(&metcon_movements, db)?;
+            let metcons: Vec<_> = Self::filter_unreferenced_metcons(metcons, db)?
+                .into_iter()
+                .map(|m| Metcon { deleted: true, ..m })
+                .collect();
+            MetconDb::update_multiple(&metcons, db)?;
+            let movements: Vec<_> = Self::filter_unreferenced_movements(movements, db)?
+                .into_iter()
+                .map(|m| Movement { deleted: true, ..m })
+                .collect();
+            MovementDb::update_multiple(&movements, db)?;
+
+            diesel::insert_into(catalogue_version::table)
+                .values(catalogue_version::columns::version.eq(catalogue.version))
+                .execute(db)?;
+
+            info!("applied catalogue version {}", catalogue.version);
+
+            Ok(())
+        })
+    }
+
+    fn upsert_muscle_groups(catalogue: &Catalogue, db: &mut PgConnection) -> QueryResult<usize> {
+        let muscle_groups: Vec<MuscleGroup> =
+            catalogue.muscle_groups.iter().map(Into::into).collect();
+        diesel::insert_into(muscle_group::table)
+            .values(&muscle_groups)
+            .on_conflict(muscle_group::columns::id)
+            .do_update()
+            .set((
+                muscle_group::columns::name.eq(excluded(muscle_group::columns::name)),
+                muscle_group::columns::description.eq(excluded(muscle_group::columns::description)),
+            ))
+            .execute(db)
+    }
+
+    fn upsert_movements(
+        catalogue: &Catalogue,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<Movement>> {
+        let existing = movement::table
+            .filter(movement::columns::user_id.is_null())
+            .select(Movement::as_select())
+            .get_results(db)?;
+        let entries = catalogue.movements.iter().map(Into::into).collect();
+        let changes = Changes::new(existing, entries, |m: &Movement| m.id, |m| m.deleted);
+
+        // restored entries are removed from the archive and created again
+        let restore_ids: Vec<_> = changes.restore.iter().map(|m| m.id).collect();
+        diesel::delete(movement::table.filter(movement::columns::id.eq_any(restore_ids)))
+            .execute(db)?;
+        MovementDb::create_multiple(&changes.create, db)?;
+        MovementDb::create_multiple(&changes.restore, db)?;
+        MovementDb::update_multiple(&changes.update, db)?;
+
+        Ok(changes.remove)
+    }
+
+    fn upsert_movement_muscles(
+        catalogue: &Catalogue,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<MovementMuscle>> {
+        let existing = movement_muscle::table
+            .filter(
+                movement_muscle::columns::movement_id.eq_any(
+                    movement::table
+                        .filter(movement::columns::user_id.is_null())
+                        .select(movement::columns::id),
+                ),
+            )
+            .select(MovementMuscle::as_select())
+            .get_results(db)?;
+        let entries = catalogue.movement_muscles.iter().map(Into::into).collect();
+        let changes = Changes::new(existing, entries, |m: &MovementMuscle| m.id, |m| m.deleted);
+
+        let restore_ids: Vec<_> = changes.restore.iter().map(|m| m.id).collect();
+        diesel::delete(
+            movement_muscle::table.filter(movement_muscle::columns::id.eq_any(restore_ids)),
+        )
+        .execute(db)?;
+        MovementMuscleDb::create_multiple(&changes.create, db)?;
+        MovementMuscleDb::create_multiple(&changes.restore, db)?;
+        MovementMuscleDb::update_multiple(&changes.update, db)?;
+
+        Ok(changes.remove)
+    }
+
+    fn upsert_metcons(catalogue: &Catalogue, db: &mut PgConnection) -> QueryResult<Vec<Metcon>> {
+        let existing = metcon::table
+            .filter(metcon::columns::user_id.is_null())
+            .select(Metcon::as_select())
+            .get_results(db)?;
+        let entries = catalogue.metcons.iter().map(Into::into).collect();
+        let changes = Changes::new(existing, entries, |m: &Metcon| m.id, |m| m.deleted);
+
+        let restore_ids: Vec<_> = changes.restore.iter().map(|m| m.id).collect();
+        diesel::delete(metcon::table.filter(metcon::columns::id.eq_any(restore_ids)))
+            .execute(db)?;
+        MetconDb::create_multiple(&changes.create, db)?;
+        MetconDb::create_multiple(&changes.restore, db)?;
+        MetconDb::update_multiple(&changes.update, db)?;
+
+        Ok(changes.remove)
+    }
+
+    fn upsert_metcon_movements(
+        catalogue: &Catalogue,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<MetconMovement>> {
+        let existing = metcon_movement::table
+            .filter(
+                metcon_movement::columns::metcon_id.eq_any(
+                    metcon::table
+                        .filter(metcon::columns::user_id.is_null())
+                        .select(metcon::columns::id),
+                ),
+            )
+            .select(MetconMovement::as_select())
+            .get_results(db)?;
+        let entries = catalogue.metcon_movements.iter().map(Into::into).collect();
+        let changes = Changes::new(existing, entries, |m: &MetconMovement| m.id, |m| m.deleted);
+
+        let restore_ids: Vec<_> = changes.restore.iter().map(|m| m.id).collect();
+        diesel::delete(
+            metcon_movement::table.filter(metcon_movement::columns::id.eq_any(restore_ids)),
+        )
+        .execute(db)?;
+        MetconMovementDb::create_multiple(&changes.create, db)?;
+        MetconMovementDb::create_multiple(&changes.restore, db)?;
+        MetconMovementDb::update_multiple(&changes.update, db)?;
+
+        Ok(changes.remove)
+    }
+
+    /// Deleting a movement cascades to all sessions, training plan entries and activity mappings which use it, so movements that are still in use are kept.
+    fn filter_unreferenced_movements(
+        movements: Vec<Movement>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<Movement>> {
+        let mut unreferenced = vec![];
+        for movement in movements {
+            let referenced = diesel::select(
+                exists(
+                    strength_session::table
+                        .filter(strength_session::columns::movement_id.eq(movement.id))
+                        .filter(strength_session::columns::deleted.eq(false)),
+                )
+                .or(exists(
+                    cardio_session::table
+                        .filter(cardio_session::columns::movement_id.eq(movement.id))
+                        .filter(cardio_session::columns::deleted.eq(false)),
+                ))
+                .or(exists(
+                    metcon_movement::table
+                        .filter(metcon_movement::columns::movement_id.eq(movement.id))
+                        .filter(metcon_movement::columns::deleted.eq(false)),
+                ))
+                .or(exists(
+                    training_plan_entry::table
+                        .filter(training_plan_entry::columns::movement_id.eq(movement.id))
+                        .filter(training_plan_entry::columns::deleted.eq(false)),
+                ))
+                .or(exists(
+                    activity_mapping::table
+                        .filter(activity_mapping::columns::movement_id.eq(movement.id))
+                        .filter(activity_mapping::columns::deleted.eq(false)),
+                )),
+            )
+            .get_result(db)?;
+            if referenced {
+                warn!(
+                    "movement {} is still in use and will not be deleted",
+                    movement.id.0
+                );
+            } else {
+                unreferenced.push(movement);
+            }
+        }
+        Ok(unreferenced)
+    }
+
+    /// Deleting a metcon cascades to all metcon sessions and training plan entries which use it, so metcons that are still in use are kept.
+    fn filter_unreferenced_metcons(
+        metcons: Vec<Metcon>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<Metcon>> {
+        let mut unreferenced = vec![];
+        for metcon in metcons {
+            let referenced = diesel::select(
+                exists(
+                    metcon_session::table
+                        .filter(metcon_session::columns::metcon_id.eq(metcon.id))
+                        .filter(metcon_session::columns::deleted.eq(false)),
+                )
+                .or(exists(
+                    training_plan_entry::table
+                        .filter(training_plan_entry::columns::metcon_id.eq(metcon.id))
+                        .filter(training_plan_entry::columns::deleted.eq(false)),
+                )),
+            )
+            .get_result(db)?;
+            if referenced {
+                warn!(
+                    "metcon {} is still in use and will not be deleted",
+                    metcon.id.0
+                );
+            } else {
+                unreferenced.push(metcon);
+            }
+        }
+        Ok(unreferenced)
+    }
+}
@@ -3,8 +3,8 @@ use chrono::{DateTime, Utc};
 use diesel::{prelude::*, PgConnection, QueryResult};
 use sport_log_derive::*;
 use sport_log_types::{
-    schema::{metcon, metcon_movement},
-    Metcon, MetconId, MetconMovement, MetconMovementId, UserId,
+    schema::{metcon, metcon_movement, metcon_session, metcon_split},
+    Metcon, MetconId, MetconMovement, MetconMovementId, MetconSessionId, MetconSplit, UserId,
 };
 
 use crate::{auth::*, db::*};
@@ -373,3 +373,155 @@ impl VerifyMultipleForUserOrAPCreate for Unverified<Vec<MetconMovement>> {
     VerifyForUserOrAPWithoutDb,
 )]
 pub struct MetconSessionDb;
+
+#[derive(Db, ModifiableDb, VerifyIdForUserOrAP, Create, GetById, GetByIds, Update, HardDelete)]
+pub struct MetconSplitDb;
+
+impl GetByUser for MetconSplitDb {
+    fn get_by_user(user_id: UserId, db: &mut PgConnection) -> QueryResult<Vec<<Self as Db>::Type>> {
+        metcon_split::table
+            .filter(
+                metcon_split::columns::metcon_session_id.eq_any(
+                    metcon_session::table
+                        .filter(metcon_session::columns::user_id.eq(user_id))
+                        .select(metcon_session::columns::id),
+                ),
+            )
+            .select(MetconSplit::as_select())
+            .get_results(db)
+    }
+}
+
+impl GetByUserSync for MetconSplitDb {
+    fn get_by_user_and_last_sync(
+        user_id: UserId,
+        last_sync: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<<Self as Db>::Type>>
+    where
+        Self: Sized,
+    {
+        metcon_split::table
+            .filter(
+                metcon_split::columns::metcon_session_id.eq_any(
+                    metcon_session::table
+                        .filter(metcon_session::columns::user_id.eq(user_id))
+                        .select(metcon_session::columns::id),
+                ),
+            )
+            .filter(metcon_split::columns::last_change.ge(last_sync))
+            .select(MetconSplit::as_select())
+            .get_results(db)
+    }
+}
+
+impl CheckUserId for MetconSplitDb {
+    fn check_user_id(id: Self::Id, user_id: UserId, db: &mut PgConnection) -> QueryResult<bool> {
+        metcon_split::table
+            .inner_join(metcon_session::table)
+            .filter(metcon_split::columns::id.eq(id))
+            .select(metcon_session::columns::user_id.eq(user_id))
+            .get_result(db)
+            .optional()
+            .map(|eq| eq.unwrap_or(false))
+    }
+
+    fn check_user_ids(
+        ids: &[Self::Id],
+        user_id: UserId,
+        db: &mut PgConnection,
+    ) -> QueryResult<bool> {
+        metcon_split::table
+            .inner_join(metcon_session::table)
+            .filter(metcon_split::columns::id.eq_any(ids))
+            .select(metcon_session::columns::user_id.eq(user_id))
+            .get_results(db)
+            .map(|eqs: Vec<bool>| eqs.into_iter().all(|eq| eq))
+    }
+}
+
+impl VerifyForUserOrAPWithDb for Unverified<MetconSplit> {
+    type Type = MetconSplit;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Self::Type, StatusCode> {
+        let metcon_split = self.0;
+        if MetconSplitDb::check_user_id(metcon_split.id, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(metcon_split)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyMultipleForUserOrAPWithDb for Unverified<Vec<MetconSplit>> {
+    type Type = MetconSplit;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Vec<Self::Type>, StatusCode> {
+        let metcon_splits = self.0;
+        let metcon_split_ids: Vec<_> = metcon_splits
+            .iter()
+            .map(|metcon_split| metcon_split.id)
+            .collect();
+        if MetconSplitDb::check_user_ids(&metcon_split_ids, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(metcon_splits)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyForUserOrAPCreate for Unverified<MetconSplit> {
+    type Type = MetconSplit;
+
+    fn verify_user_ap_create(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Self::Type, StatusCode> {
+        let metcon_split = self.0;
+        if MetconSessionDb::check_user_id(metcon_split.metcon_session_id, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(metcon_split)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyMultipleForUserOrAPCreate for Unverified<Vec<MetconSplit>> {
+    type Type = MetconSplit;
+
+    fn verify_user_ap_create(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Vec<Self::Type>, StatusCode> {
+        let metcon_splits = self.0;
+        let mut metcon_session_ids: Vec<MetconSessionId> = metcon_splits
+            .iter()
+            .map(|metcon_split| metcon_split.metcon_session_id)
+            .collect();
+        metcon_session_ids.sort_unstable();
+        metcon_session_ids.dedup();
+        if MetconSessionDb::check_user_ids(&metcon_session_ids, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(metcon_splits)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
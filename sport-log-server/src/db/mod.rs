@@ -10,25 +10,31 @@ use sport_log_types::{ActionProviderId, UserId};
 mod account;
 mod action;
 mod admin;
+mod calendar;
 mod cardio;
+mod catalogue;
 mod diary_wod;
 mod metcon;
 mod movement;
 mod platform;
 mod sharing;
 mod strength;
+mod training_plan;
 mod user;
 
 pub use account::*;
 pub use action::*;
 pub use admin::*;
+pub use calendar::*;
 pub use cardio::*;
+pub use catalogue::*;
 pub use diary_wod::*;
 pub use metcon::*;
 pub use movement::*;
 pub use platform::*;
 pub use sharing::*;
 pub use strength::*;
+pub use training_plan::*;
 pub use user::*;
 
 use crate::auth::*;
@@ -425,6 +431,12 @@ pub trait VerifyForActionProviderWithDb {
     fn verify_ap(self, auth: AuthAP, db: &mut PgConnection) -> Result<Self::Type, StatusCode>;
 }
 
+pub trait VerifyMultipleForActionProviderWithDb {
+    type Type;
+
+    fn verify_ap(self, auth: AuthAP, db: &mut PgConnection) -> Result<Vec<Self::Type>, StatusCode>;
+}
+
 pub trait VerifyForActionProviderWithoutDb {
     type Type;
 
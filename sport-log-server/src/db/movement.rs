@@ -186,6 +186,8 @@ impl VerifyForUserOrAPWithDb for Unverified<MovementMuscle> {
         let movement_muscle = self.0;
         if MovementMuscleDb::check_user_id(movement_muscle.id, *auth, db)
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && MovementDb::check_user_id(movement_muscle.movement_id, *auth, db)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         {
             Ok(movement_muscle)
         } else {
@@ -205,10 +207,18 @@ impl VerifyMultipleForUserOrAPWithDb for Unverified<Vec<MovementMuscle>> {
         let movement_muscle = self.0;
         let movement_muscle_ids: Vec<_> = movement_muscle
             .iter()
-            .map(|metcon_movement| metcon_movement.id)
+            .map(|movement_muscle| movement_muscle.id)
+            .collect();
+        let mut movement_ids: Vec<_> = movement_muscle
+            .iter()
+            .map(|movement_muscle| movement_muscle.movement_id)
             .collect();
+        movement_ids.sort_unstable();
+        movement_ids.dedup();
         if MovementMuscleDb::check_user_ids(&movement_muscle_ids, *auth, db)
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && MovementDb::check_user_ids(&movement_ids, *auth, db)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         {
             Ok(movement_muscle)
         } else {
@@ -247,7 +257,7 @@ impl VerifyMultipleForUserOrAPCreate for Unverified<Vec<MovementMuscle>> {
         let movement_muscle = self.0;
         let mut movement_ids: Vec<_> = movement_muscle
             .iter()
-            .map(|metcon_movement| metcon_movement.movement_id)
+            .map(|movement_muscle| movement_muscle.movement_id)
             .collect();
         movement_ids.sort_unstable();
         movement_ids.dedup();
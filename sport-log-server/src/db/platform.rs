@@ -1,4 +1,9 @@
+use axum::http::StatusCode;
+use diesel::{prelude::*, PgConnection, QueryResult};
 use sport_log_derive::*;
+use sport_log_types::{schema::platform, Platform, PlatformId};
+
+use crate::{auth::AuthAP, db::*};
 
 #[derive(
     Db,
@@ -16,6 +21,35 @@ use sport_log_derive::*;
 )]
 pub struct PlatformDb;
 
+impl PlatformDb {
+    /// Update only the `credential` flag, so action providers can not rename the platform.
+    pub fn update_credential(
+        platform_id: PlatformId,
+        credential: bool,
+        db: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::update(platform::table.find(platform_id))
+            .set(platform::columns::credential.eq(credential))
+            .execute(db)
+    }
+}
+
+/// Action providers may only update the platform they belong to and can not delete it.
+impl VerifyForActionProviderWithDb for Unverified<Platform> {
+    type Type = Platform;
+
+    fn verify_ap(self, auth: AuthAP, db: &mut PgConnection) -> Result<Self::Type, StatusCode> {
+        let platform = self.0;
+        let action_provider = ActionProviderDb::get_by_id(*auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if platform.id == action_provider.platform_id && !platform.deleted {
+            Ok(platform)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
 #[derive(
     Db,
     ModifiableDb,
@@ -33,3 +67,21 @@ pub struct PlatformDb;
     VerifyForUserWithoutDb,
 )]
 pub struct PlatformCredentialDb;
+
+#[derive(
+    Db,
+    DbWithUserId,
+    ModifiableDb,
+    VerifyIdForUserOrAP,
+    Create,
+    GetById,
+    GetByIds,
+    GetByUser,
+    GetByUserSync,
+    Update,
+    HardDelete,
+    CheckUserId,
+    VerifyForUserOrAPWithDb,
+    VerifyForUserOrAPWithoutDb,
+)]
+pub struct ActivityMappingDb;
@@ -1,10 +1,12 @@
+use std::collections::HashMap;
+
 use axum::http::StatusCode;
 use chrono::{DateTime, Utc};
 use diesel::{prelude::*, PgConnection, QueryResult};
 use sport_log_derive::*;
 use sport_log_types::{
-    schema::{strength_session, strength_set},
-    StrengthSessionId, StrengthSet, UserId,
+    schema::{eorm, movement, strength_session, strength_set},
+    MovementDimension, MovementId, StrengthSessionId, StrengthSessionUpdate, StrengthSet, UserId,
 };
 
 use crate::{auth::*, db::*};
@@ -21,7 +23,6 @@ use crate::{auth::*, db::*};
     GetByUser,
     GetByUserTimespan,
     GetByUserSync,
-    Update,
     HardDelete,
     CheckUserId,
     VerifyForUserOrAPWithDb,
@@ -29,6 +30,179 @@ use crate::{auth::*, db::*};
 )]
 pub struct StrengthSessionDb;
 
+/// The superset of a session is only changed by [`StrengthSessionDb::update_from_client`]
+/// because clients that do not know about supersets omit `strength_superset_id` and `superset_position`.
+impl Update for StrengthSessionDb {
+    fn update(value: &Self::Type, db: &mut PgConnection) -> QueryResult<usize> {
+        diesel::update(strength_session::table.find(value.id))
+            .set((
+                strength_session::columns::user_id.eq(value.user_id),
+                strength_session::columns::datetime.eq(value.datetime),
+                strength_session::columns::movement_id.eq(value.movement_id),
+                strength_session::columns::interval.eq(value.interval),
+                strength_session::columns::comments.eq(&value.comments),
+                strength_session::columns::deleted.eq(value.deleted),
+            ))
+            .execute(db)
+    }
+
+    fn update_multiple(values: &[Self::Type], db: &mut PgConnection) -> QueryResult<usize> {
+        db.transaction(|db| {
+            for value in values {
+                Self::update(value, db)?;
+            }
+            Ok(values.len())
+        })
+    }
+}
+
+impl StrengthSessionDb {
+    /// Update the sessions and also their superset if it is part of the update.
+    pub fn update_from_client(
+        strength_session_updates: &[StrengthSessionUpdate],
+        db: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        db.transaction(|db| {
+            for strength_session_update in strength_session_updates {
+                let strength_session = &strength_session_update.strength_session;
+                Self::update(strength_session, db)?;
+                if strength_session_update.update_superset {
+                    diesel::update(strength_session::table.find(strength_session.id))
+                        .set((
+                            strength_session::columns::strength_superset_id
+                                .eq(strength_session.strength_superset_id),
+                            strength_session::columns::superset_position
+                                .eq(strength_session.superset_position),
+                        ))
+                        .execute(db)?;
+                }
+            }
+            Ok(strength_session_updates.len())
+        })
+    }
+
+    /// Get the [`MovementDimension`] of the movement of each of the strength sessions.
+    pub fn get_movement_dimensions(
+        strength_session_ids: &[StrengthSessionId],
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<(StrengthSessionId, MovementDimension)>> {
+        strength_session::table
+            .inner_join(movement::table)
+            .filter(strength_session::columns::id.eq_any(strength_session_ids))
+            .select((
+                strength_session::columns::id,
+                movement::columns::movement_dimension,
+            ))
+            .get_results(db)
+    }
+
+    /// Estimate the one rep max of the user for each of the movements based on the [`Eorm`](sport_log_types::Eorm) table.
+    ///
+    /// Movements without a weighted set with a rep count contained in the table are omitted.
+    pub fn get_estimated_one_rep_maxes(
+        user_id: UserId,
+        movement_ids: &[MovementId],
+        db: &mut PgConnection,
+    ) -> QueryResult<HashMap<MovementId, f32>> {
+        let sets: Vec<(MovementId, f32, f32)> = strength_set::table
+            .inner_join(strength_session::table)
+            .inner_join(eorm::table.on(eorm::columns::reps.eq(strength_set::columns::count)))
+            .filter(strength_session::columns::user_id.eq(user_id))
+            .filter(strength_session::columns::movement_id.eq_any(movement_ids))
+            .filter(strength_session::columns::deleted.eq(false))
+            .filter(strength_set::columns::deleted.eq(false))
+            .filter(strength_set::columns::weight.is_not_null())
+            .select((
+                strength_session::columns::movement_id,
+                strength_set::columns::weight.assume_not_null(),
+                eorm::columns::percentage,
+            ))
+            .get_results(db)?;
+
+        let mut one_rep_maxes = HashMap::new();
+        for (movement_id, weight, percentage) in sets {
+            let one_rep_max = weight / percentage;
+            one_rep_maxes
+                .entry(movement_id)
+                .and_modify(|max: &mut f32| *max = max.max(one_rep_max))
+                .or_insert(one_rep_max);
+        }
+        Ok(one_rep_maxes)
+    }
+}
+
+impl VerifyForUserOrAPWithDb for Unverified<StrengthSessionUpdate> {
+    type Type = StrengthSessionUpdate;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Self::Type, StatusCode> {
+        let StrengthSessionUpdate {
+            strength_session,
+            update_superset,
+        } = self.0;
+        let strength_session = Unverified(strength_session).verify_user_ap(auth, db)?;
+        Ok(StrengthSessionUpdate {
+            strength_session,
+            update_superset,
+        })
+    }
+}
+
+impl VerifyMultipleForUserOrAPWithDb for Unverified<Vec<StrengthSessionUpdate>> {
+    type Type = StrengthSessionUpdate;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Vec<Self::Type>, StatusCode> {
+        let (strength_sessions, update_supersets): (Vec<_>, Vec<_>) = self
+            .0
+            .into_iter()
+            .map(|strength_session_update| {
+                (
+                    strength_session_update.strength_session,
+                    strength_session_update.update_superset,
+                )
+            })
+            .unzip();
+        let strength_sessions = Unverified(strength_sessions).verify_user_ap(auth, db)?;
+        Ok(strength_sessions
+            .into_iter()
+            .zip(update_supersets)
+            .map(
+                |(strength_session, update_superset)| StrengthSessionUpdate {
+                    strength_session,
+                    update_superset,
+                },
+            )
+            .collect())
+    }
+}
+
+#[derive(
+    Db,
+    DbWithUserId,
+    DbWithDateTime,
+    ModifiableDb,
+    VerifyIdForUserOrAP,
+    Create,
+    GetById,
+    GetByIds,
+    GetByUser,
+    GetByUserTimespan,
+    GetByUserSync,
+    Update,
+    HardDelete,
+    CheckUserId,
+    VerifyForUserOrAPWithDb,
+    VerifyForUserOrAPWithoutDb,
+)]
+pub struct StrengthSupersetDb;
+
 #[derive(Db, ModifiableDb, VerifyIdForUserOrAP, Create, GetById, GetByIds, Update, HardDelete)]
 pub struct StrengthSetDb;
 
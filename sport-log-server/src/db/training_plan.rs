@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use diesel::{prelude::*, PgConnection, QueryResult};
+use rand_core::{OsRng, RngCore};
+use sport_log_derive::*;
+use sport_log_types::{
+    schema::{planned_session, training_plan, training_plan_entry},
+    PlannedSession, PlannedSessionId, TrainingPlanAdherence, TrainingPlanEntry, TrainingPlanId,
+    TrainingPlanStart, UserId,
+};
+
+use crate::{auth::*, db::*};
+
+#[derive(
+    Db,
+    DbWithUserId,
+    ModifiableDb,
+    VerifyIdForUserOrAP,
+    Create,
+    GetById,
+    GetByIds,
+    GetByUser,
+    GetByUserSync,
+    Update,
+    HardDelete,
+    CheckUserId,
+    VerifyForUserOrAPWithDb,
+    VerifyForUserOrAPWithoutDb,
+)]
+pub struct TrainingPlanDb;
+
+impl TrainingPlanDb {
+    /// Create a [`PlannedSession`] for every entry of the training plan starting at `start_date`.
+    ///
+    /// The weight of strength entries with a percentage is calculated from the estimated one rep max of the user.
+    ///
+    /// Returns `None` without creating any planned session if the date of an entry is out of range.
+    pub fn start(
+        user_id: UserId,
+        training_plan_start: &TrainingPlanStart,
+        db: &mut PgConnection,
+    ) -> QueryResult<Option<Vec<PlannedSession>>> {
+        db.transaction(|db| {
+            let entries: Vec<TrainingPlanEntry> = training_plan_entry::table
+                .filter(
+                    training_plan_entry::columns::training_plan_id
+                        .eq(training_plan_start.training_plan_id),
+                )
+                .filter(training_plan_entry::columns::deleted.eq(false))
+                .select(TrainingPlanEntry::as_select())
+                .get_results(db)?;
+
+            let movement_ids: Vec<_> = entries
+                .iter()
+                .filter(|entry| entry.percentage.is_some())
+                .filter_map(|entry| entry.movement_id)
+                .collect();
+            let one_rep_maxes =
+                StrengthSessionDb::get_estimated_one_rep_maxes(user_id, &movement_ids, db)?;
+
+            let planned_sessions: Option<Vec<_>> = entries
+                .iter()
+                .map(|entry| {
+                    let date = entry
+                        .week
+                        .checked_mul(7)
+                        .and_then(|days| days.checked_add(entry.day))
+                        .and_then(|days| u64::try_from(days).ok())
+                        .and_then(|days| {
+                            training_plan_start
+                                .start_date
+                                .checked_add_days(Days::new(days))
+                        })?;
+                    Some(PlannedSession {
+                        id: PlannedSessionId(OsRng.next_u64() as i64),
+                        user_id,
+                        training_plan_entry_id: entry.id,
+                        date,
+                        weight: entry
+                            .percentage
+                            .zip(
+                                entry
+                                    .movement_id
+                                    .and_then(|movement_id| one_rep_maxes.get(&movement_id)),
+                            )
+                            .map(|(percentage, one_rep_max)| percentage * one_rep_max),
+                        strength_session_id: None,
+                        metcon_session_id: None,
+                        cardio_session_id: None,
+                        deleted: false,
+                    })
+                })
+                .collect();
+            let Some(planned_sessions) = planned_sessions else {
+                return Ok(None);
+            };
+            PlannedSessionDb::create_multiple(&planned_sessions, db)?;
+
+            Ok(Some(planned_sessions))
+        })
+    }
+}
+
+#[derive(Db, ModifiableDb, VerifyIdForUserOrAP, Create, GetById, GetByIds, Update, HardDelete)]
+pub struct TrainingPlanEntryDb;
+
+impl GetByUser for TrainingPlanEntryDb {
+    fn get_by_user(user_id: UserId, db: &mut PgConnection) -> QueryResult<Vec<<Self as Db>::Type>> {
+        training_plan_entry::table
+            .filter(
+                training_plan_entry::columns::training_plan_id.eq_any(
+                    training_plan::table
+                        .filter(training_plan::columns::user_id.eq(user_id))
+                        .select(training_plan::columns::id),
+                ),
+            )
+            .select(TrainingPlanEntry::as_select())
+            .get_results(db)
+    }
+}
+
+impl GetByUserSync for TrainingPlanEntryDb {
+    fn get_by_user_and_last_sync(
+        user_id: UserId,
+        last_sync: DateTime<Utc>,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<<Self as Db>::Type>>
+    where
+        Self: Sized,
+    {
+        training_plan_entry::table
+            .filter(
+                training_plan_entry::columns::training_plan_id.eq_any(
+                    training_plan::table
+                        .filter(training_plan::columns::user_id.eq(user_id))
+                        .select(training_plan::columns::id),
+                ),
+            )
+            .filter(training_plan_entry::columns::last_change.ge(last_sync))
+            .select(TrainingPlanEntry::as_select())
+            .get_results(db)
+    }
+}
+
+impl CheckUserId for TrainingPlanEntryDb {
+    fn check_user_id(id: Self::Id, user_id: UserId, db: &mut PgConnection) -> QueryResult<bool> {
+        training_plan_entry::table
+            .inner_join(training_plan::table)
+            .filter(training_plan_entry::columns::id.eq(id))
+            .select(training_plan::columns::user_id.eq(user_id))
+            .get_result(db)
+            .optional()
+            .map(|eq| eq.unwrap_or(false))
+    }
+
+    fn check_user_ids(
+        ids: &[Self::Id],
+        user_id: UserId,
+        db: &mut PgConnection,
+    ) -> QueryResult<bool> {
+        training_plan_entry::table
+            .inner_join(training_plan::table)
+            .filter(training_plan_entry::columns::id.eq_any(ids))
+            .select(training_plan::columns::user_id.eq(user_id))
+            .get_results(db)
+            .map(|eqs: Vec<bool>| eqs.into_iter().all(|eq| eq))
+    }
+}
+
+/// Check that the movements and metcons referenced by the entries belong to the user or are public.
+fn check_training_plan_entry_references(
+    training_plan_entries: &[TrainingPlanEntry],
+    user_id: UserId,
+    db: &mut PgConnection,
+) -> QueryResult<bool> {
+    let movement_ids: Vec<_> = training_plan_entries
+        .iter()
+        .filter_map(|training_plan_entry| training_plan_entry.movement_id)
+        .collect();
+    let metcon_ids: Vec<_> = training_plan_entries
+        .iter()
+        .filter_map(|training_plan_entry| training_plan_entry.metcon_id)
+        .collect();
+    Ok(
+        MovementDb::check_optional_user_ids(&movement_ids, user_id, db)?
+            && MetconDb::check_optional_user_ids(&metcon_ids, user_id, db)?,
+    )
+}
+
+impl VerifyForUserOrAPWithDb for Unverified<TrainingPlanEntry> {
+    type Type = TrainingPlanEntry;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Self::Type, StatusCode> {
+        let training_plan_entry = self.0;
+        if TrainingPlanEntryDb::check_user_id(training_plan_entry.id, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && check_training_plan_entry_references(
+                std::slice::from_ref(&training_plan_entry),
+                *auth,
+                db,
+            )
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(training_plan_entry)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyMultipleForUserOrAPWithDb for Unverified<Vec<TrainingPlanEntry>> {
+    type Type = TrainingPlanEntry;
+
+    fn verify_user_ap(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Vec<Self::Type>, StatusCode> {
+        let training_plan_entries = self.0;
+        let training_plan_entry_ids: Vec<_> = training_plan_entries
+            .iter()
+            .map(|training_plan_entry| training_plan_entry.id)
+            .collect();
+        if TrainingPlanEntryDb::check_user_ids(&training_plan_entry_ids, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && check_training_plan_entry_references(&training_plan_entries, *auth, db)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(training_plan_entries)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyForUserOrAPCreate for Unverified<TrainingPlanEntry> {
+    type Type = TrainingPlanEntry;
+
+    fn verify_user_ap_create(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Self::Type, StatusCode> {
+        let training_plan_entry = self.0;
+        if TrainingPlanDb::check_user_id(training_plan_entry.training_plan_id, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && check_training_plan_entry_references(
+                std::slice::from_ref(&training_plan_entry),
+                *auth,
+                db,
+            )
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(training_plan_entry)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+impl VerifyMultipleForUserOrAPCreate for Unverified<Vec<TrainingPlanEntry>> {
+    type Type = TrainingPlanEntry;
+
+    fn verify_user_ap_create(
+        self,
+        auth: AuthUserOrAP,
+        db: &mut PgConnection,
+    ) -> Result<Vec<Self::Type>, StatusCode> {
+        let training_plan_entries = self.0;
+        let mut training_plan_ids: Vec<TrainingPlanId> = training_plan_entries
+            .iter()
+            .map(|training_plan_entry| training_plan_entry.training_plan_id)
+            .collect();
+        training_plan_ids.sort_unstable();
+        training_plan_ids.dedup();
+        if TrainingPlanDb::check_user_ids(&training_plan_ids, *auth, db)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            && check_training_plan_entry_references(&training_plan_entries, *auth, db)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        {
+            Ok(training_plan_entries)
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+#[derive(
+    Db,
+    DbWithUserId,
+    ModifiableDb,
+    VerifyIdForUserOrAP,
+    Create,
+    GetById,
+    GetByIds,
+    GetByUser,
+    GetByUserSync,
+    Update,
+    HardDelete,
+    CheckUserId,
+    VerifyForUserOrAPWithDb,
+)]
+pub struct PlannedSessionDb;
+
+impl PlannedSessionDb {
+    /// Get the [`TrainingPlanAdherence`] for all started training plans of the user or only for the given one.
+    pub fn get_adherence(
+        user_id: UserId,
+        training_plan_id: Option<TrainingPlanId>,
+        today: NaiveDate,
+        db: &mut PgConnection,
+    ) -> QueryResult<Vec<TrainingPlanAdherence>> {
+        let mut query = planned_session::table
+            .inner_join(training_plan_entry::table)
+            .filter(planned_session::columns::user_id.eq(user_id))
+            .filter(planned_session::columns::deleted.eq(false))
+            .select((
+                training_plan_entry::columns::training_plan_id,
+                PlannedSession::as_select(),
+            ))
+            .into_boxed();
+        if let Some(training_plan_id) = training_plan_id {
+            query =
+                query.filter(training_plan_entry::columns::training_plan_id.eq(training_plan_id));
+        }
+        let planned_sessions: Vec<(TrainingPlanId, PlannedSession)> = query.get_results(db)?;
+
+        let mut adherences = BTreeMap::new();
+        for (training_plan_id, planned_session) in planned_sessions {
+            let adherence =
+                adherences
+                    .entry(training_plan_id)
+                    .or_insert_with(|| TrainingPlanAdherence {
+                        training_plan_id,
+                        planned: 0,
+                        completed: 0,
+                        missed: 0,
+                        adherence: 1.,
+                    });
+            adherence.planned += 1;
+            if planned_session.is_completed() {
+                adherence.completed += 1;
+            } else if planned_session.date < today {
+                adherence.missed += 1;
+            }
+        }
+
+        Ok(adherences
+            .into_values()
+            .map(|mut adherence| {
+                let due = adherence.completed + adherence.missed;
+                if due > 0 {
+                    adherence.adherence = adherence.completed as f64 / due as f64;
+                }
+                adherence
+            })
+            .collect())
+    }
+}
@@ -13,14 +13,19 @@ use hyper::{
 };
 use r2d2::Error as R2D2Error;
 use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use sport_log_types::argument_schema::ArgumentError;
 use tracing::info;
 
+use crate::db::CatalogueError;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ErrorMessage {
     PrimaryKeyViolation { table: String },
     ForeignKeyViolation { table: String, column: String },
     UniqueViolation { table: String, columns: Vec<String> },
+    InvalidArgumentSchema { errors: Vec<ArgumentError> },
+    InvalidArguments { errors: Vec<ArgumentError> },
     Other { error: String },
 }
 
@@ -173,6 +178,26 @@ impl From<DieselError> for HandlerError {
     }
 }
 
+impl From<CatalogueError> for HandlerError {
+    fn from(error: CatalogueError) -> Self {
+        match error {
+            CatalogueError::Outdated { .. } => HandlerError::from((
+                StatusCode::CONFLICT,
+                ErrorMessage::Other {
+                    error: error.to_string(),
+                },
+            )),
+            CatalogueError::Invalid(_) => HandlerError::from((
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_string(),
+                },
+            )),
+            CatalogueError::Database(error) => error.into(),
+        }
+    }
+}
+
 impl From<Infallible> for HandlerError {
     fn from(_: Infallible) -> Self {
         unreachable!()
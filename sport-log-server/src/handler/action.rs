@@ -1,11 +1,17 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Query, State},
     http::StatusCode,
     Json,
 };
+use chrono::Utc;
+use diesel::{PgConnection, QueryResult};
 use sport_log_types::{
-    Action, ActionEvent, ActionEventId, ActionId, ActionProvider, ActionProviderId, ActionRule,
-    ActionRuleId, CreatableActionRule, DeletableActionEvent, ExecutableActionEvent,
+    argument_schema::ArgumentSchema, Action, ActionEvent, ActionEventId, ActionEventResult,
+    ActionEventResultId, ActionId, ActionProvider, ActionProviderHealth, ActionProviderHeartbeat,
+    ActionProviderId, ActionRule, ActionRuleId, CreatableActionRule, DeletableActionEvent,
+    ExecutableActionEvent,
 };
 
 use crate::{
@@ -104,6 +110,106 @@ pub async fn get_action_providers(
     .map_err(Into::into)
 }
 
+pub async fn ap_create_heartbeat(
+    auth: AuthAP,
+    mut db: DbConn,
+    Json(heartbeat): Json<Unverified<ActionProviderHeartbeat>>,
+) -> HandlerResult<StatusCode> {
+    let mut heartbeat = heartbeat.verify_ap_without_db(auth)?;
+    heartbeat.validate().map_err(|error| {
+        HandlerError::from((
+            StatusCode::BAD_REQUEST,
+            ErrorMessage::Other {
+                error: error.to_owned(),
+            },
+        ))
+    })?;
+    heartbeat.datetime = Utc::now();
+    ActionProviderHeartbeatDb::upsert(&heartbeat, &mut db)
+        .map(|_| StatusCode::OK)
+        .map_err(Into::into)
+}
+
+pub async fn adm_get_action_provider_health(
+    _auth: AuthAdmin,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<ActionProviderHealth>>> {
+    ActionProviderHeartbeatDb::get_health(Utc::now(), &mut db)
+        .map(Json)
+        .map_err(Into::into)
+}
+
+/// Get the health of the action providers the user has action rules for.
+pub async fn get_action_provider_health(
+    auth: AuthUser,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<ActionProviderHealth>>> {
+    ActionProviderHeartbeatDb::get_health_by_user(*auth, Utc::now(), &mut db)
+        .map(Json)
+        .map_err(Into::into)
+}
+
+fn check_actions(actions: &[Action]) -> Result<(), (StatusCode, ErrorMessage)> {
+    for action in actions {
+        action.validate().map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+        })?;
+        if let Some(argument_schema) = &action.argument_schema {
+            ArgumentSchema::parse(argument_schema).map_err(|errors| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    ErrorMessage::InvalidArgumentSchema { errors },
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Validate the arguments against the argument schemas of their actions.
+fn check_arguments(
+    arguments: &[(ActionId, Option<&str>)],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let action_ids: Vec<_> = arguments.iter().map(|(action_id, _)| *action_id).collect();
+    let argument_schemas: HashMap<_, _> = ActionDb::get_by_ids(&action_ids, db)
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::Other {
+                    error: error.to_string(),
+                },
+            )
+        })?
+        .into_iter()
+        .map(|action| (action.id, action.argument_schema))
+        .collect();
+
+    for (action_id, arguments) in arguments {
+        let Some(Some(argument_schema)) = argument_schemas.get(action_id) else {
+            continue;
+        };
+        let argument_schema = ArgumentSchema::parse(argument_schema).map_err(|errors| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::InvalidArgumentSchema { errors },
+            )
+        })?;
+        argument_schema.validate(*arguments).map_err(|errors| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::InvalidArguments { errors },
+            )
+        })?;
+    }
+    Ok(())
+}
+
 pub async fn ap_create_actions(
     auth: AuthAP,
     mut db: DbConn,
@@ -112,10 +218,12 @@ pub async fn ap_create_actions(
     match actions {
         UnverifiedSingleOrVec::Single(action) => {
             let action = action.verify_ap_without_db(auth)?;
+            check_actions(std::slice::from_ref(&action))?;
             ActionDb::create(&action, &mut db)
         }
         UnverifiedSingleOrVec::Vec(actions) => {
             let actions = actions.verify_ap_without_db(auth)?;
+            check_actions(&actions)?;
             ActionDb::create_multiple(&actions, &mut db)
         }
     }
@@ -123,6 +231,41 @@ pub async fn ap_create_actions(
     .map_err(Into::into)
 }
 
+pub async fn ap_update_actions(
+    auth: AuthAP,
+    mut db: DbConn,
+    Json(actions): Json<UnverifiedSingleOrVec<Action>>,
+) -> HandlerResult<StatusCode> {
+    match actions {
+        UnverifiedSingleOrVec::Single(action) => {
+            let action = action.verify_ap(auth, &mut db)?;
+            check_actions(std::slice::from_ref(&action))?;
+            ActionDb::update(&action, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(actions) => {
+            let actions = actions.verify_ap(auth, &mut db)?;
+            check_actions(&actions)?;
+            ActionDb::update_multiple(&actions, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+/// Soft delete actions of the action provider together with their action rules and action events.
+pub async fn ap_delete_actions(
+    auth: AuthAP,
+    mut db: DbConn,
+    Json(ids): Json<UnverifiedIds<ActionId>>,
+) -> HandlerResult<StatusCode> {
+    ActionDb::delete_multiple(
+        ids.verify_ap(auth, &mut db).map_err(HandlerError::from)?,
+        &mut db,
+    )
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
 pub async fn ap_get_actions(
     auth: AuthAP,
     Query(IdOption { id }): Query<IdOption<UnverifiedId<ActionId>>>,
@@ -155,6 +298,75 @@ pub async fn get_actions(
     .map_err(Into::into)
 }
 
+fn check_action_rules(
+    action_rules: &[ActionRule],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    for action_rule in action_rules {
+        action_rule.validate().map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+        })?;
+    }
+    let arguments: Vec<_> = action_rules
+        .iter()
+        .map(|action_rule| (action_rule.action_id, action_rule.arguments.as_deref()))
+        .collect();
+    check_arguments(&arguments, db)
+}
+
+fn check_action_events(
+    action_events: &[ActionEvent],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    for action_event in action_events {
+        action_event.validate().map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+        })?;
+    }
+    let arguments: Vec<_> = action_events
+        .iter()
+        .map(|action_event| (action_event.action_id, action_event.arguments.as_deref()))
+        .collect();
+    check_arguments(&arguments, db)
+}
+
+/// The retry state of action events is managed by the server, so users can not set it when creating action events.
+fn reset_retry_state(action_events: &mut [ActionEvent]) {
+    for action_event in action_events {
+        action_event.attempts = 0;
+        action_event.retry_after = None;
+    }
+}
+
+/// The retry state of action events is managed by the server, so updates by users keep the stored state.
+fn keep_retry_state(action_events: &mut [ActionEvent], db: &mut PgConnection) -> QueryResult<()> {
+    let action_event_ids: Vec<_> = action_events
+        .iter()
+        .map(|action_event| action_event.id)
+        .collect();
+    let stored_action_events: HashMap<_, _> = ActionEventDb::get_by_ids(&action_event_ids, db)?
+        .into_iter()
+        .map(|action_event| (action_event.id, action_event))
+        .collect();
+    for action_event in action_events {
+        if let Some(stored_action_event) = stored_action_events.get(&action_event.id) {
+            action_event.attempts = stored_action_event.attempts;
+            action_event.retry_after = stored_action_event.retry_after;
+        }
+    }
+    Ok(())
+}
+
 pub async fn create_action_rules(
     auth: AuthUser,
     mut db: DbConn,
@@ -163,10 +375,12 @@ pub async fn create_action_rules(
     match action_rules {
         UnverifiedSingleOrVec::Single(action_rule) => {
             let action_rule = action_rule.verify_user_without_db(auth)?;
+            check_action_rules(std::slice::from_ref(&action_rule), &mut db)?;
             ActionRuleDb::create(&action_rule, &mut db)
         }
         UnverifiedSingleOrVec::Vec(action_rules) => {
             let action_rules = action_rules.verify_user_without_db(auth)?;
+            check_action_rules(&action_rules, &mut db)?;
             ActionRuleDb::create_multiple(&action_rules, &mut db)
         }
     }
@@ -198,10 +412,12 @@ pub async fn update_action_rules(
     match action_rules {
         UnverifiedSingleOrVec::Single(action_rule) => {
             let action_rule = action_rule.verify_user_without_db(auth)?;
+            check_action_rules(std::slice::from_ref(&action_rule), &mut db)?;
             ActionRuleDb::update(&action_rule, &mut db)
         }
         UnverifiedSingleOrVec::Vec(action_rules) => {
             let action_rules = action_rules.verify_user(auth, &mut db)?;
+            check_action_rules(&action_rules, &mut db)?;
             ActionRuleDb::update_multiple(&action_rules, &mut db)
         }
     }
@@ -216,11 +432,15 @@ pub async fn create_action_events(
 ) -> HandlerResult<StatusCode> {
     match action_events {
         UnverifiedSingleOrVec::Single(action_event) => {
-            let action_event = action_event.verify_user_without_db(auth)?;
+            let mut action_event = action_event.verify_user_without_db(auth)?;
+            check_action_events(std::slice::from_ref(&action_event), &mut db)?;
+            reset_retry_state(std::slice::from_mut(&mut action_event));
             ActionEventDb::create(&action_event, &mut db)
         }
         UnverifiedSingleOrVec::Vec(action_events) => {
-            let action_events = action_events.verify_user_without_db(auth)?;
+            let mut action_events = action_events.verify_user_without_db(auth)?;
+            check_action_events(&action_events, &mut db)?;
+            reset_retry_state(&mut action_events);
             ActionEventDb::create_multiple(&action_events, &mut db)
         }
     }
@@ -270,11 +490,15 @@ pub async fn update_action_events(
 ) -> HandlerResult<StatusCode> {
     match action_events {
         UnverifiedSingleOrVec::Single(action_event) => {
-            let action_event = action_event.verify_user(auth, &mut db)?;
+            let mut action_event = action_event.verify_user(auth, &mut db)?;
+            check_action_events(std::slice::from_ref(&action_event), &mut db)?;
+            keep_retry_state(std::slice::from_mut(&mut action_event), &mut db)?;
             ActionEventDb::update(&action_event, &mut db)
         }
         UnverifiedSingleOrVec::Vec(action_events) => {
-            let action_events = action_events.verify_user(auth, &mut db)?;
+            let mut action_events = action_events.verify_user(auth, &mut db)?;
+            check_action_events(&action_events, &mut db)?;
+            keep_retry_state(&mut action_events, &mut db)?;
             ActionEventDb::update_multiple(&action_events, &mut db)
         }
     }
@@ -314,6 +538,44 @@ pub async fn ap_disable_action_events(
     .map_err(Into::into)
 }
 
+pub async fn ap_create_action_event_results(
+    auth: AuthAP,
+    mut db: DbConn,
+    Json(action_event_results): Json<UnverifiedSingleOrVec<ActionEventResult>>,
+) -> HandlerResult<StatusCode> {
+    let action_event_results = match action_event_results {
+        UnverifiedSingleOrVec::Single(action_event_result) => {
+            vec![action_event_result.verify_ap(auth, &mut db)?]
+        }
+        UnverifiedSingleOrVec::Vec(action_event_results) => {
+            action_event_results.verify_ap(auth, &mut db)?
+        }
+    };
+    ActionEventResultDb::create_multiple_and_register_failed_attempts(
+        &action_event_results,
+        Utc::now(),
+        &mut db,
+    )?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn get_action_event_results(
+    auth: AuthUser,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<ActionEventResultId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<ActionEventResult>>> {
+    match id {
+        Some(id) => {
+            let action_event_result_id = id.verify_user(auth, &mut db)?;
+            ActionEventResultDb::get_by_id(action_event_result_id, &mut db).map(|a| vec![a])
+        }
+        None => ActionEventResultDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
 pub async fn adm_delete_action_events(
     auth: AuthAdmin,
     mut db: DbConn,
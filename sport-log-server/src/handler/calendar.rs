@@ -0,0 +1,66 @@
+use axum::{
+    extract::Query,
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use diesel::result::Error as DieselError;
+use serde::Deserialize;
+use sport_log_types::{render_calendar, CalendarToken};
+
+use crate::{
+    auth::AuthUser,
+    db::*,
+    handler::{HandlerError, HandlerResult},
+    state::DbConn,
+};
+
+/// Wods are included for this many days in the past since they are usually created on the day of the workout.
+const CALENDAR_WOD_DAYS: i64 = 28;
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    token: String,
+}
+
+/// Create a new calendar token which replaces the previous one.
+pub async fn create_calendar_token(
+    auth: AuthUser,
+    mut db: DbConn,
+) -> HandlerResult<Json<CalendarToken>> {
+    CalendarDb::create_token(*auth, &mut db)
+        .map(Json)
+        .map_err(Into::into)
+}
+
+pub async fn delete_calendar_token(auth: AuthUser, mut db: DbConn) -> HandlerResult<StatusCode> {
+    CalendarDb::delete_token(*auth, &mut db)
+        .map(|_| StatusCode::OK)
+        .map_err(Into::into)
+}
+
+/// Get the iCalendar feed with the upcoming action events and the recent wods of the user the token belongs to.
+pub async fn get_calendar(
+    Query(CalendarQuery { token }): Query<CalendarQuery>,
+    mut db: DbConn,
+) -> HandlerResult<impl IntoResponse> {
+    let user_id =
+        CalendarDb::get_user_id_by_token(&token, &mut db).map_err(|error| match error {
+            DieselError::NotFound => HandlerError::from(StatusCode::UNAUTHORIZED),
+            error => HandlerError::from(error),
+        })?;
+
+    let now = Utc::now();
+    let action_events = CalendarDb::get_action_events(user_id, now, &mut db)?;
+    let wods = CalendarDb::get_wods(
+        user_id,
+        (now - Duration::try_days(CALENDAR_WOD_DAYS).unwrap()).date_naive(),
+        &mut db,
+    )?;
+
+    Ok((
+        [(CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        render_calendar(&action_events, &wods, now),
+    ))
+}
@@ -0,0 +1,13 @@
+use axum::{http::StatusCode, Json};
+
+use crate::{auth::AuthAdmin, db::*, handler::HandlerResult, state::DbConn};
+
+pub async fn adm_update_catalogue(
+    _auth: AuthAdmin,
+    mut db: DbConn,
+    Json(catalogue): Json<Catalogue>,
+) -> HandlerResult<StatusCode> {
+    CatalogueDb::apply(&catalogue, &mut db)
+        .map(|_| StatusCode::OK)
+        .map_err(Into::into)
+}
@@ -16,21 +16,28 @@ pub async fn adm_do_garbage_collection(
 ) -> HandlerResult<StatusCode> {
     PlatformDb::hard_delete(last_change, &mut db)?;
     PlatformCredentialDb::hard_delete(last_change, &mut db)?;
+    ActivityMappingDb::hard_delete(last_change, &mut db)?;
     ActionDb::hard_delete(last_change, &mut db)?;
     ActionProviderDb::hard_delete(last_change, &mut db)?;
     ActionRuleDb::hard_delete(last_change, &mut db)?;
     ActionEventDb::hard_delete(last_change, &mut db)?;
+    ActionEventResultDb::hard_delete(last_change, &mut db)?;
     DiaryDb::hard_delete(last_change, &mut db)?;
     WodDb::hard_delete(last_change, &mut db)?;
     MovementDb::hard_delete(last_change, &mut db)?;
     MovementMuscleDb::hard_delete(last_change, &mut db)?;
     StrengthSessionDb::hard_delete(last_change, &mut db)?;
     StrengthSetDb::hard_delete(last_change, &mut db)?;
+    StrengthSupersetDb::hard_delete(last_change, &mut db)?;
     MetconDb::hard_delete(last_change, &mut db)?;
     MetconMovementDb::hard_delete(last_change, &mut db)?;
     MetconSessionDb::hard_delete(last_change, &mut db)?;
+    MetconSplitDb::hard_delete(last_change, &mut db)?;
     RouteDb::hard_delete(last_change, &mut db)?;
     CardioSessionDb::hard_delete(last_change, &mut db)?;
+    TrainingPlanDb::hard_delete(last_change, &mut db)?;
+    TrainingPlanEntryDb::hard_delete(last_change, &mut db)?;
+    PlannedSessionDb::hard_delete(last_change, &mut db)?;
     GroupDb::hard_delete(last_change, &mut db)?;
     GroupUserDb::hard_delete(last_change, &mut db)?;
     SharedDiaryDb::hard_delete(last_change, &mut db)?;
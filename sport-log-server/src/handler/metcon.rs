@@ -1,15 +1,129 @@
+use std::collections::HashMap;
+
 use axum::{extract::Query, http::StatusCode, Json};
+use diesel::PgConnection;
+use rand_core::{OsRng, RngCore};
 use sport_log_types::{
+    wod_parser::{self, MetconDraft, WodText},
     Metcon, MetconId, MetconMovement, MetconMovementId, MetconSession, MetconSessionId,
+    MetconSplit, MetconSplitId,
 };
 
 use crate::{
     auth::AuthUserOrAP,
     db::*,
-    handler::{HandlerResult, IdOption, TimeSpanOption, UnverifiedSingleOrVec},
+    handler::{ErrorMessage, HandlerResult, IdOption, TimeSpanOption, UnverifiedSingleOrVec},
     state::DbConn,
 };
 
+fn check_metcons(metcons: &[Metcon]) -> Result<(), (StatusCode, ErrorMessage)> {
+    for metcon in metcons {
+        metcon.validate().map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Sessions that reference a non-existing metcon are not checked because creating or updating them fails anyway.
+fn check_metcon_sessions(
+    metcon_sessions: &[MetconSession],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let metcon_ids: Vec<_> = metcon_sessions
+        .iter()
+        .map(|metcon_session| metcon_session.metcon_id)
+        .collect();
+    let metcons: HashMap<_, _> = MetconDb::get_by_ids(&metcon_ids, db)
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorMessage::Other {
+                    error: error.to_string(),
+                },
+            )
+        })?
+        .into_iter()
+        .map(|metcon| (metcon.id, metcon))
+        .collect();
+    for metcon_session in metcon_sessions {
+        if let Some(metcon) = metcons.get(&metcon_session.metcon_id) {
+            metcon_session.validate(metcon).map_err(|error| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    ErrorMessage::Other {
+                        error: error.to_owned(),
+                    },
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Splits that reference a non-existing metcon session or metcon movement are not checked because creating or updating them fails anyway.
+fn check_metcon_splits(
+    metcon_splits: &[MetconSplit],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let metcon_movement_ids: Vec<_> = metcon_splits
+        .iter()
+        .filter_map(|metcon_split| metcon_split.metcon_movement_id)
+        .collect();
+    if metcon_movement_ids.is_empty() {
+        return Ok(());
+    }
+    let metcon_session_ids: Vec<_> = metcon_splits
+        .iter()
+        .filter(|metcon_split| metcon_split.metcon_movement_id.is_some())
+        .map(|metcon_split| metcon_split.metcon_session_id)
+        .collect();
+    let to_internal_error = |error: diesel::result::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorMessage::Other {
+                error: error.to_string(),
+            },
+        )
+    };
+    let metcon_sessions: HashMap<_, _> = MetconSessionDb::get_by_ids(&metcon_session_ids, db)
+        .map_err(to_internal_error)?
+        .into_iter()
+        .map(|metcon_session| (metcon_session.id, metcon_session))
+        .collect();
+    let metcon_movements: HashMap<_, _> = MetconMovementDb::get_by_ids(&metcon_movement_ids, db)
+        .map_err(to_internal_error)?
+        .into_iter()
+        .map(|metcon_movement| (metcon_movement.id, metcon_movement))
+        .collect();
+    for metcon_split in metcon_splits {
+        let Some(metcon_movement_id) = metcon_split.metcon_movement_id else {
+            continue;
+        };
+        if let (Some(metcon_session), Some(metcon_movement)) = (
+            metcon_sessions.get(&metcon_split.metcon_session_id),
+            metcon_movements.get(&metcon_movement_id),
+        ) {
+            metcon_split
+                .validate(metcon_session, metcon_movement)
+                .map_err(|error| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        ErrorMessage::Other {
+                            error: error.to_owned(),
+                        },
+                    )
+                })?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn create_metcon_sessions(
     auth: AuthUserOrAP,
     mut db: DbConn,
@@ -18,10 +132,12 @@ pub async fn create_metcon_sessions(
     match metcon_sessions {
         UnverifiedSingleOrVec::Single(metcon_session) => {
             let metcon_session = metcon_session.verify_user_ap_without_db(auth)?;
+            check_metcon_sessions(std::slice::from_ref(&metcon_session), &mut db)?;
             MetconSessionDb::create(&metcon_session, &mut db)
         }
         UnverifiedSingleOrVec::Vec(metcon_sessions) => {
             let metcon_sessions = metcon_sessions.verify_user_ap_without_db(auth)?;
+            check_metcon_sessions(&metcon_sessions, &mut db)?;
             MetconSessionDb::create_multiple(&metcon_sessions, &mut db)
         }
     }
@@ -54,10 +170,12 @@ pub async fn update_metcon_sessions(
     match metcon_sessions {
         UnverifiedSingleOrVec::Single(metcon_session) => {
             let metcon_session = metcon_session.verify_user_ap(auth, &mut db)?;
+            check_metcon_sessions(std::slice::from_ref(&metcon_session), &mut db)?;
             MetconSessionDb::update(&metcon_session, &mut db)
         }
         UnverifiedSingleOrVec::Vec(metcon_sessions) => {
             let metcon_sessions = metcon_sessions.verify_user_ap(auth, &mut db)?;
+            check_metcon_sessions(&metcon_sessions, &mut db)?;
             MetconSessionDb::update_multiple(&metcon_sessions, &mut db)
         }
     }
@@ -73,10 +191,12 @@ pub async fn create_metcons(
     match metcons {
         UnverifiedSingleOrVec::Single(metcon) => {
             let metcon = metcon.verify_user_ap_without_db(auth)?;
+            check_metcons(std::slice::from_ref(&metcon))?;
             MetconDb::create(&metcon, &mut db)
         }
         UnverifiedSingleOrVec::Vec(metcons) => {
             let metcons = metcons.verify_user_ap_without_db(auth)?;
+            check_metcons(&metcons)?;
             MetconDb::create_multiple(&metcons, &mut db)
         }
     }
@@ -108,10 +228,12 @@ pub async fn update_metcons(
     match metcons {
         UnverifiedSingleOrVec::Single(metcon) => {
             let metcon = metcon.verify_user_ap(auth, &mut db)?;
+            check_metcons(std::slice::from_ref(&metcon))?;
             MetconDb::update(&metcon, &mut db)
         }
         UnverifiedSingleOrVec::Vec(metcons) => {
             let metcons = metcons.verify_user_ap(auth, &mut db)?;
+            check_metcons(&metcons)?;
             MetconDb::update_multiple(&metcons, &mut db)
         }
     }
@@ -119,6 +241,28 @@ pub async fn update_metcons(
     .map_err(Into::into)
 }
 
+/// Parse the description of a wod into a [`MetconDraft`] with movements resolved against the movements of the user.
+///
+/// Nothing is created, the draft can be reviewed and then created using the metcon and metcon movement endpoints.
+pub async fn parse_metcon_draft(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(wod_text): Json<WodText>,
+) -> HandlerResult<Json<MetconDraft>> {
+    let movements = MovementDb::get_by_user(*auth, &mut db)?;
+    wod_parser::parse_wod(&wod_text, *auth, &movements, || OsRng.next_u64() as i64)
+        .map(Json)
+        .map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+                .into()
+        })
+}
+
 pub async fn create_metcon_movements(
     auth: AuthUserOrAP,
     mut db: DbConn,
@@ -172,3 +316,61 @@ pub async fn update_metcon_movements(
     .map(|_| StatusCode::OK)
     .map_err(Into::into)
 }
+
+pub async fn create_metcon_splits(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(metcon_splits): Json<UnverifiedSingleOrVec<MetconSplit>>,
+) -> HandlerResult<StatusCode> {
+    match metcon_splits {
+        UnverifiedSingleOrVec::Single(metcon_split) => {
+            let metcon_split = metcon_split.verify_user_ap_create(auth, &mut db)?;
+            check_metcon_splits(std::slice::from_ref(&metcon_split), &mut db)?;
+            MetconSplitDb::create(&metcon_split, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(metcon_splits) => {
+            let metcon_splits = metcon_splits.verify_user_ap_create(auth, &mut db)?;
+            check_metcon_splits(&metcon_splits, &mut db)?;
+            MetconSplitDb::create_multiple(&metcon_splits, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_metcon_splits(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<MetconSplitId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<MetconSplit>>> {
+    match id {
+        Some(id) => {
+            let metcon_split_id = id.verify_user_ap(auth, &mut db)?;
+            MetconSplitDb::get_by_id(metcon_split_id, &mut db).map(|m| vec![m])
+        }
+        None => MetconSplitDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+pub async fn update_metcon_splits(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(metcon_splits): Json<UnverifiedSingleOrVec<MetconSplit>>,
+) -> HandlerResult<StatusCode> {
+    match metcon_splits {
+        UnverifiedSingleOrVec::Single(metcon_split) => {
+            let metcon_split = metcon_split.verify_user_ap(auth, &mut db)?;
+            check_metcon_splits(std::slice::from_ref(&metcon_split), &mut db)?;
+            MetconSplitDb::update(&metcon_split, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(metcon_splits) => {
+            let metcon_splits = metcon_splits.verify_user_ap(auth, &mut db)?;
+            check_metcon_splits(&metcon_splits, &mut db)?;
+            MetconSplitDb::update_multiple(&metcon_splits, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
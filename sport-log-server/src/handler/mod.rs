@@ -8,25 +8,31 @@ pub use crate::error::*;
 mod account;
 mod action;
 mod app;
+mod calendar;
 mod cardio;
+mod catalogue;
 mod diary_wod;
 mod garbage_collection;
 mod metcon;
 mod movement;
 mod platform;
 mod strength;
+mod training_plan;
 mod user;
 
 pub use account::*;
 pub use action::*;
 pub use app::*;
+pub use calendar::*;
 pub use cardio::*;
+pub use catalogue::*;
 pub use diary_wod::*;
 pub use garbage_collection::*;
 pub use metcon::*;
 pub use movement::*;
 pub use platform::*;
 pub use strength::*;
+pub use training_plan::*;
 pub use user::*;
 
 #[derive(Debug, Deserialize)]
@@ -1,5 +1,5 @@
 use axum::{extract::Query, http::StatusCode, Json};
-use sport_log_types::{Movement, MovementId};
+use sport_log_types::{Movement, MovementId, MovementMuscle, MovementMuscleId, MuscleGroup};
 
 use crate::{
     auth::*,
@@ -62,86 +62,65 @@ pub async fn update_movements(
     .map_err(Into::into)
 }
 
-//pub async fn create_movement_muscle(
-//auth: AuthUserOrAP,
-//mut db: DbConn,
-//Json(movement_muscle): Json<Unverified<MovementMuscle>>,
-//) -> HandlerResult<Json<MovementMuscle>> {
-//let movement_muscle = movement_muscle
-//.verify_user_ap_create(auth, &mut db)
-//.map_err(Error::from)?;
-//MovementMuscleDb::create(&movement_muscle, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
-
-//pub async fn create_movement_muscles(
-//auth: AuthUserOrAP,
-//mut db: DbConn,
-//Json(movement_muscles): Json<UnverifiedSingleOrVec<MovementMuscle>>>,
-//) -> HandlerResult<Json<Vec<MovementMuscle>>> {
-//let movement_muscles = movement_muscles
-//.verify_user_ap_create(auth, &mut db)
-//.map_err(|status| Error {
-//status,
-//message: None,
-//})?;
-//MovementMuscleDb::create_multiple(&movement_muscles, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
-
-//pub async fn get_movement_muscle(
-//auth: AuthUserOrAP,
-//Path(movement_muscle_id): Path<UnverifiedId<MovementMuscleId>>,
-//mut db: DbConn,
-//) -> HandlerResult<Json<MovementMuscle>> {
-//let movement_muscle_id = movement_muscle_id
-//.verify_user_ap(auth, &mut db)
-//.map_err(Error::from)?;
-//MovementMuscleDb::get_by_id(movement_muscle_id, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
-
-//pub async fn get_movement_muscles(
-//auth: AuthUserOrAP,
-//mut db: DbConn,
-//) -> HandlerResult<Json<Vec<MovementMuscle>>> {
-//MovementMuscleDb::get_by_user(*auth, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
+pub async fn get_muscle_groups(
+    _auth: AuthUserOrAP,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<MuscleGroup>>> {
+    MuscleGroupDb::get_all(&mut db)
+        .map(Json)
+        .map_err(Into::into)
+}
 
-//pub async fn update_movement_muscle(
-//auth: AuthUserOrAP,
-//mut db: DbConn,
-//Json(movement_muscle): Json<Unverified<MovementMuscle>>,
-//) -> HandlerResult<Json<MovementMuscle>> {
-//let movement_muscle = movement_muscle
-//.verify_user_ap(auth, &mut db)
-//.map_err(Error::from)?;
-//MovementMuscleDb::update(&movement_muscle, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
+pub async fn create_movement_muscles(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(movement_muscles): Json<UnverifiedSingleOrVec<MovementMuscle>>,
+) -> HandlerResult<StatusCode> {
+    match movement_muscles {
+        UnverifiedSingleOrVec::Single(movement_muscle) => {
+            let movement_muscle = movement_muscle.verify_user_ap_create(auth, &mut db)?;
+            MovementMuscleDb::create(&movement_muscle, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(movement_muscles) => {
+            let movement_muscles = movement_muscles.verify_user_ap_create(auth, &mut db)?;
+            MovementMuscleDb::create_multiple(&movement_muscles, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
 
-//pub async fn update_movement_muscles(
-//auth: AuthUserOrAP,
-//mut db: DbConn,
-//Json(movement_muscles): Json<UnverifiedSingleOrVec<MovementMuscle>>>,
-//) -> HandlerResult<Json<Vec<MovementMuscle>>> {
-//let movement_muscles = movement_muscles
-//.verify_user_ap(auth, &mut db)
-//.map_err(Error::from)?;
-//MovementMuscleDb::update_multiple(&movement_muscles, &mut db)
-//.map(Json)
-//.map_err(Into::into)
-//}
+pub async fn get_movement_muscles(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<MovementMuscleId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<MovementMuscle>>> {
+    match id {
+        Some(id) => {
+            let movement_muscle_id = id.verify_user_ap(auth, &mut db)?;
+            MovementMuscleDb::get_by_id(movement_muscle_id, &mut db).map(|m| vec![m])
+        }
+        None => MovementMuscleDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
 
-//pub async fn get_muscle_groups(
-//_auth: AuthUserOrAP,
-//mut db: DbConn,
-//) -> HandlerResult<Json<Vec<MuscleGroup>>> {
-//MuscleGroupDb::get_all(&mut db).map(Json).map_err(Into::into)
-//}
+pub async fn update_movement_muscles(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(movement_muscles): Json<UnverifiedSingleOrVec<MovementMuscle>>,
+) -> HandlerResult<StatusCode> {
+    match movement_muscles {
+        UnverifiedSingleOrVec::Single(movement_muscle) => {
+            let movement_muscle = movement_muscle.verify_user_ap(auth, &mut db)?;
+            MovementMuscleDb::update(&movement_muscle, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(movement_muscles) => {
+            let movement_muscles = movement_muscles.verify_user_ap(auth, &mut db)?;
+            MovementMuscleDb::update_multiple(&movement_muscles, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
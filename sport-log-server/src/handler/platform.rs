@@ -3,10 +3,14 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use sport_log_types::{Platform, PlatformCredential, PlatformCredentialId, PlatformId};
+use diesel::{result::Error as DieselError, PgConnection};
+use sport_log_types::{
+    ActivityMapping, ActivityMappingId, Platform, PlatformCredential, PlatformCredentialId,
+    PlatformId,
+};
 
 use crate::{
-    auth::{AuthAdmin, AuthUser},
+    auth::{AuthAP, AuthAdmin, AuthUser, AuthUserOrAP},
     config::Config,
     db::*,
     handler::{ErrorMessage, HandlerError, HandlerResult, IdOption, UnverifiedSingleOrVec},
@@ -52,6 +56,20 @@ pub async fn ap_create_platform(
         .map_err(Into::into)
 }
 
+/// Update the `credential` flag of the platform of the action provider.
+///
+/// All other fields of the platform are ignored.
+pub async fn ap_update_platform(
+    auth: AuthAP,
+    mut db: DbConn,
+    Json(platform): Json<Unverified<Platform>>,
+) -> HandlerResult<StatusCode> {
+    let platform = platform.verify_ap(auth, &mut db)?;
+    PlatformDb::update_credential(platform.id, platform.credential, &mut db)
+        .map(|_| StatusCode::OK)
+        .map_err(Into::into)
+}
+
 pub async fn adm_get_platforms(
     auth: AuthAdmin,
     Query(IdOption { id }): Query<IdOption<UnverifiedId<PlatformId>>>,
@@ -185,3 +203,111 @@ pub async fn update_platform_credentials(
     .map(|_| StatusCode::OK)
     .map_err(Into::into)
 }
+
+fn database_error(error: &DieselError) -> (StatusCode, ErrorMessage) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorMessage::Other {
+            error: error.to_string(),
+        },
+    )
+}
+
+/// Activity mappings may only reference movements of the same user or public movements.
+///
+/// Action providers may only create and update activity mappings for their own platform.
+fn check_activity_mappings(
+    activity_mappings: &[ActivityMapping],
+    auth: AuthUserOrAP,
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let movement_ids: Vec<_> = activity_mappings
+        .iter()
+        .filter_map(|activity_mapping| activity_mapping.movement_id)
+        .collect();
+    if !MovementDb::check_optional_user_ids(&movement_ids, *auth, db)
+        .map_err(|error| database_error(&error))?
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            ErrorMessage::Other {
+                error: "the activity mapping references a movement of another user".to_owned(),
+            },
+        ));
+    }
+    if let Some(action_provider_id) = auth.action_provider_id() {
+        let platform_id = ActionProviderDb::get_by_id(action_provider_id, db)
+            .map_err(|error| database_error(&error))?
+            .platform_id;
+        if activity_mappings
+            .iter()
+            .any(|activity_mapping| activity_mapping.platform_id != platform_id)
+        {
+            return Err((
+                StatusCode::FORBIDDEN,
+                ErrorMessage::Other {
+                    error: "the activity mapping belongs to another platform".to_owned(),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub async fn create_activity_mappings(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(activity_mappings): Json<UnverifiedSingleOrVec<ActivityMapping>>,
+) -> HandlerResult<StatusCode> {
+    match activity_mappings {
+        UnverifiedSingleOrVec::Single(activity_mapping) => {
+            let activity_mapping = activity_mapping.verify_user_ap_without_db(auth)?;
+            check_activity_mappings(std::slice::from_ref(&activity_mapping), auth, &mut db)?;
+            ActivityMappingDb::create(&activity_mapping, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(activity_mappings) => {
+            let activity_mappings = activity_mappings.verify_user_ap_without_db(auth)?;
+            check_activity_mappings(&activity_mappings, auth, &mut db)?;
+            ActivityMappingDb::create_multiple(&activity_mappings, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_activity_mappings(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<ActivityMappingId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<ActivityMapping>>> {
+    match id {
+        Some(id) => {
+            let activity_mapping_id = id.verify_user_ap(auth, &mut db)?;
+            ActivityMappingDb::get_by_id(activity_mapping_id, &mut db).map(|a| vec![a])
+        }
+        None => ActivityMappingDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+pub async fn update_activity_mappings(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(activity_mappings): Json<UnverifiedSingleOrVec<ActivityMapping>>,
+) -> HandlerResult<StatusCode> {
+    match activity_mappings {
+        UnverifiedSingleOrVec::Single(activity_mapping) => {
+            let activity_mapping = activity_mapping.verify_user_ap(auth, &mut db)?;
+            check_activity_mappings(std::slice::from_ref(&activity_mapping), auth, &mut db)?;
+            ActivityMappingDb::update(&activity_mapping, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(activity_mappings) => {
+            let activity_mappings = activity_mappings.verify_user_ap(auth, &mut db)?;
+            check_activity_mappings(&activity_mappings, auth, &mut db)?;
+            ActivityMappingDb::update_multiple(&activity_mappings, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
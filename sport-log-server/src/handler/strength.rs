@@ -1,13 +1,99 @@
+use std::collections::HashMap;
+
 use axum::{extract::Query, http::StatusCode, Json};
-use sport_log_types::{Eorm, StrengthSession, StrengthSessionId, StrengthSet, StrengthSetId};
+use diesel::PgConnection;
+use sport_log_types::{
+    Eorm, StrengthSession, StrengthSessionId, StrengthSessionUpdate, StrengthSet, StrengthSetId,
+    StrengthSuperset, StrengthSupersetId,
+};
 
 use crate::{
     auth::AuthUserOrAP,
     db::*,
-    handler::{HandlerResult, IdOption, TimeSpanOption, UnverifiedSingleOrVec},
+    handler::{ErrorMessage, HandlerResult, IdOption, TimeSpanOption, UnverifiedSingleOrVec},
     state::DbConn,
 };
 
+/// Sessions may only be added to supersets of the same user.
+fn check_strength_sessions(
+    strength_sessions: &[StrengthSession],
+    auth: AuthUserOrAP,
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    for strength_session in strength_sessions {
+        strength_session.validate().map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                ErrorMessage::Other {
+                    error: error.to_owned(),
+                },
+            )
+        })?;
+    }
+    let mut strength_superset_ids: Vec<_> = strength_sessions
+        .iter()
+        .filter_map(|strength_session| strength_session.strength_superset_id)
+        .collect();
+    strength_superset_ids.sort_unstable();
+    strength_superset_ids.dedup();
+    if StrengthSupersetDb::check_user_ids(&strength_superset_ids, *auth, db).map_err(|error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorMessage::Other {
+                error: error.to_string(),
+            },
+        )
+    })? {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            ErrorMessage::Other {
+                error: "the superset belongs to another user".to_owned(),
+            },
+        ))
+    }
+}
+
+/// Sets that reference a non-existing strength session are not checked because creating or updating them fails anyway.
+fn check_strength_sets(
+    strength_sets: &[StrengthSet],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let strength_session_ids: Vec<_> = strength_sets
+        .iter()
+        .map(|strength_set| strength_set.strength_session_id)
+        .collect();
+    let movement_dimensions: HashMap<_, _> =
+        StrengthSessionDb::get_movement_dimensions(&strength_session_ids, db)
+            .map_err(|error| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ErrorMessage::Other {
+                        error: error.to_string(),
+                    },
+                )
+            })?
+            .into_iter()
+            .collect();
+    for strength_set in strength_sets {
+        if let Some(movement_dimension) = movement_dimensions.get(&strength_set.strength_session_id)
+        {
+            strength_set
+                .validate(*movement_dimension)
+                .map_err(|error| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        ErrorMessage::Other {
+                            error: error.to_owned(),
+                        },
+                    )
+                })?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn create_strength_sessions(
     auth: AuthUserOrAP,
     mut db: DbConn,
@@ -16,10 +102,12 @@ pub async fn create_strength_sessions(
     match strength_sessions {
         UnverifiedSingleOrVec::Single(strength_session) => {
             let strength_session = strength_session.verify_user_ap_without_db(auth)?;
+            check_strength_sessions(std::slice::from_ref(&strength_session), auth, &mut db)?;
             StrengthSessionDb::create(&strength_session, &mut db)
         }
         UnverifiedSingleOrVec::Vec(strength_sessions) => {
             let strength_sessions = strength_sessions.verify_user_ap_without_db(auth)?;
+            check_strength_sessions(&strength_sessions, auth, &mut db)?;
             StrengthSessionDb::create_multiple(&strength_sessions, &mut db)
         }
     }
@@ -49,16 +137,77 @@ pub async fn get_strength_sessions(
 pub async fn update_strength_sessions(
     auth: AuthUserOrAP,
     mut db: DbConn,
-    Json(strength_sessions): Json<UnverifiedSingleOrVec<StrengthSession>>,
+    Json(strength_session_updates): Json<UnverifiedSingleOrVec<StrengthSessionUpdate>>,
 ) -> HandlerResult<StatusCode> {
-    match strength_sessions {
-        UnverifiedSingleOrVec::Single(strength_session) => {
-            let strength_session = strength_session.verify_user_ap(auth, &mut db)?;
-            StrengthSessionDb::update(&strength_session, &mut db)
+    let strength_session_updates = match strength_session_updates {
+        UnverifiedSingleOrVec::Single(strength_session_update) => {
+            vec![strength_session_update.verify_user_ap(auth, &mut db)?]
         }
-        UnverifiedSingleOrVec::Vec(strength_sessions) => {
-            let strength_sessions = strength_sessions.verify_user_ap(auth, &mut db)?;
-            StrengthSessionDb::update_multiple(&strength_sessions, &mut db)
+        UnverifiedSingleOrVec::Vec(strength_session_updates) => {
+            strength_session_updates.verify_user_ap(auth, &mut db)?
+        }
+    };
+    let strength_sessions: Vec<_> = strength_session_updates
+        .iter()
+        .map(|strength_session_update| strength_session_update.strength_session.clone())
+        .collect();
+    check_strength_sessions(&strength_sessions, auth, &mut db)?;
+    StrengthSessionDb::update_from_client(&strength_session_updates, &mut db)
+        .map(|_| StatusCode::OK)
+        .map_err(Into::into)
+}
+
+pub async fn create_strength_supersets(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(strength_supersets): Json<UnverifiedSingleOrVec<StrengthSuperset>>,
+) -> HandlerResult<StatusCode> {
+    match strength_supersets {
+        UnverifiedSingleOrVec::Single(strength_superset) => {
+            let strength_superset = strength_superset.verify_user_ap_without_db(auth)?;
+            StrengthSupersetDb::create(&strength_superset, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(strength_supersets) => {
+            let strength_supersets = strength_supersets.verify_user_ap_without_db(auth)?;
+            StrengthSupersetDb::create_multiple(&strength_supersets, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_strength_supersets(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<StrengthSupersetId>>>,
+    Query(time_span_option): Query<TimeSpanOption>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<StrengthSuperset>>> {
+    match id {
+        Some(id) => {
+            let strength_superset_id = id.verify_user_ap(auth, &mut db)?;
+            StrengthSupersetDb::get_by_id(strength_superset_id, &mut db).map(|s| vec![s])
+        }
+        None => {
+            StrengthSupersetDb::get_by_user_and_timespan(*auth, time_span_option.into(), &mut db)
+        }
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+pub async fn update_strength_supersets(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(strength_supersets): Json<UnverifiedSingleOrVec<StrengthSuperset>>,
+) -> HandlerResult<StatusCode> {
+    match strength_supersets {
+        UnverifiedSingleOrVec::Single(strength_superset) => {
+            let strength_superset = strength_superset.verify_user_ap(auth, &mut db)?;
+            StrengthSupersetDb::update(&strength_superset, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(strength_supersets) => {
+            let strength_supersets = strength_supersets.verify_user_ap(auth, &mut db)?;
+            StrengthSupersetDb::update_multiple(&strength_supersets, &mut db)
         }
     }
     .map(|_| StatusCode::OK)
@@ -73,10 +222,12 @@ pub async fn create_strength_sets(
     match strength_sets {
         UnverifiedSingleOrVec::Single(strength_set) => {
             let strength_set = strength_set.verify_user_ap_create(auth, &mut db)?;
+            check_strength_sets(std::slice::from_ref(&strength_set), &mut db)?;
             StrengthSetDb::create(&strength_set, &mut db)
         }
         UnverifiedSingleOrVec::Vec(strength_sets) => {
             let strength_sets = strength_sets.verify_user_ap_create(auth, &mut db)?;
+            check_strength_sets(&strength_sets, &mut db)?;
             StrengthSetDb::create_multiple(&strength_sets, &mut db)
         }
     }
@@ -108,10 +259,12 @@ pub async fn update_strength_sets(
     match strength_sets {
         UnverifiedSingleOrVec::Single(strength_set) => {
             let strength_set = strength_set.verify_user_ap(auth, &mut db)?;
+            check_strength_sets(std::slice::from_ref(&strength_set), &mut db)?;
             StrengthSetDb::update(&strength_set, &mut db)
         }
         UnverifiedSingleOrVec::Vec(strength_sets) => {
             let strength_sets = strength_sets.verify_user_ap(auth, &mut db)?;
+            check_strength_sets(&strength_sets, &mut db)?;
             StrengthSetDb::update_multiple(&strength_sets, &mut db)
         }
     }
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use axum::{extract::Query, http::StatusCode, Json};
+use chrono::Utc;
+use diesel::{result::Error as DieselError, PgConnection};
+use sport_log_types::{
+    PlannedSession, PlannedSessionId, TrainingPlan, TrainingPlanAdherence, TrainingPlanEntry,
+    TrainingPlanEntryId, TrainingPlanId, TrainingPlanStart,
+};
+
+use crate::{
+    auth::AuthUserOrAP,
+    db::*,
+    handler::{ErrorMessage, HandlerResult, IdOption, UnverifiedSingleOrVec},
+    state::DbConn,
+};
+
+fn database_error(error: &DieselError) -> (StatusCode, ErrorMessage) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorMessage::Other {
+            error: error.to_string(),
+        },
+    )
+}
+
+fn bad_request(error: &str) -> (StatusCode, ErrorMessage) {
+    (
+        StatusCode::BAD_REQUEST,
+        ErrorMessage::Other {
+            error: error.to_owned(),
+        },
+    )
+}
+
+fn check_training_plans(training_plans: &[TrainingPlan]) -> Result<(), (StatusCode, ErrorMessage)> {
+    for training_plan in training_plans {
+        training_plan.validate().map_err(bad_request)?;
+    }
+    Ok(())
+}
+
+/// Entries that reference a non-existing training plan are not checked because creating or updating them fails anyway.
+fn check_training_plan_entries(
+    training_plan_entries: &[TrainingPlanEntry],
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let training_plan_ids: Vec<_> = training_plan_entries
+        .iter()
+        .map(|training_plan_entry| training_plan_entry.training_plan_id)
+        .collect();
+    let weeks: HashMap<_, _> = TrainingPlanDb::get_by_ids(&training_plan_ids, db)
+        .map_err(|error| database_error(&error))?
+        .into_iter()
+        .map(|training_plan| (training_plan.id, training_plan.weeks))
+        .collect();
+    for training_plan_entry in training_plan_entries {
+        training_plan_entry.validate().map_err(bad_request)?;
+        if let Some(weeks) = weeks.get(&training_plan_entry.training_plan_id) {
+            if training_plan_entry.week >= *weeks {
+                return Err(bad_request(
+                    "the week exceeds the weeks of the training plan",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Planned sessions may only reference entries and sessions of the same user and the linked session must match the planned type of the entry.
+fn check_planned_sessions(
+    planned_sessions: &[PlannedSession],
+    auth: AuthUserOrAP,
+    db: &mut PgConnection,
+) -> Result<(), (StatusCode, ErrorMessage)> {
+    let training_plan_entry_ids: Vec<_> = planned_sessions
+        .iter()
+        .map(|planned_session| planned_session.training_plan_entry_id)
+        .collect();
+    let strength_session_ids: Vec<_> = planned_sessions
+        .iter()
+        .filter_map(|planned_session| planned_session.strength_session_id)
+        .collect();
+    let metcon_session_ids: Vec<_> = planned_sessions
+        .iter()
+        .filter_map(|planned_session| planned_session.metcon_session_id)
+        .collect();
+    let cardio_session_ids: Vec<_> = planned_sessions
+        .iter()
+        .filter_map(|planned_session| planned_session.cardio_session_id)
+        .collect();
+
+    if !(TrainingPlanEntryDb::check_user_ids(&training_plan_entry_ids, *auth, db)
+        .map_err(|error| database_error(&error))?
+        && StrengthSessionDb::check_user_ids(&strength_session_ids, *auth, db)
+            .map_err(|error| database_error(&error))?
+        && MetconSessionDb::check_user_ids(&metcon_session_ids, *auth, db)
+            .map_err(|error| database_error(&error))?
+        && CardioSessionDb::check_user_ids(&cardio_session_ids, *auth, db)
+            .map_err(|error| database_error(&error))?)
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            ErrorMessage::Other {
+                error: "the planned session references data of another user".to_owned(),
+            },
+        ));
+    }
+
+    let planned_types: HashMap<_, _> =
+        TrainingPlanEntryDb::get_by_ids(&training_plan_entry_ids, db)
+            .map_err(|error| database_error(&error))?
+            .into_iter()
+            .map(|training_plan_entry| (training_plan_entry.id, training_plan_entry.planned_type))
+            .collect();
+    for planned_session in planned_sessions {
+        if let Some(planned_type) = planned_types.get(&planned_session.training_plan_entry_id) {
+            planned_session
+                .validate(*planned_type)
+                .map_err(bad_request)?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn create_training_plans(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(training_plans): Json<UnverifiedSingleOrVec<TrainingPlan>>,
+) -> HandlerResult<StatusCode> {
+    match training_plans {
+        UnverifiedSingleOrVec::Single(training_plan) => {
+            let training_plan = training_plan.verify_user_ap_without_db(auth)?;
+            check_training_plans(std::slice::from_ref(&training_plan))?;
+            TrainingPlanDb::create(&training_plan, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(training_plans) => {
+            let training_plans = training_plans.verify_user_ap_without_db(auth)?;
+            check_training_plans(&training_plans)?;
+            TrainingPlanDb::create_multiple(&training_plans, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_training_plans(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<TrainingPlanId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<TrainingPlan>>> {
+    match id {
+        Some(id) => {
+            let training_plan_id = id.verify_user_ap(auth, &mut db)?;
+            TrainingPlanDb::get_by_id(training_plan_id, &mut db).map(|t| vec![t])
+        }
+        None => TrainingPlanDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+pub async fn update_training_plans(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(training_plans): Json<UnverifiedSingleOrVec<TrainingPlan>>,
+) -> HandlerResult<StatusCode> {
+    match training_plans {
+        UnverifiedSingleOrVec::Single(training_plan) => {
+            let training_plan = training_plan.verify_user_ap(auth, &mut db)?;
+            check_training_plans(std::slice::from_ref(&training_plan))?;
+            TrainingPlanDb::update(&training_plan, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(training_plans) => {
+            let training_plans = training_plans.verify_user_ap(auth, &mut db)?;
+            check_training_plans(&training_plans)?;
+            TrainingPlanDb::update_multiple(&training_plans, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn create_training_plan_entries(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(training_plan_entries): Json<UnverifiedSingleOrVec<TrainingPlanEntry>>,
+) -> HandlerResult<StatusCode> {
+    match training_plan_entries {
+        UnverifiedSingleOrVec::Single(training_plan_entry) => {
+            let training_plan_entry = training_plan_entry.verify_user_ap_create(auth, &mut db)?;
+            check_training_plan_entries(std::slice::from_ref(&training_plan_entry), &mut db)?;
+            TrainingPlanEntryDb::create(&training_plan_entry, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(training_plan_entries) => {
+            let training_plan_entries =
+                training_plan_entries.verify_user_ap_create(auth, &mut db)?;
+            check_training_plan_entries(&training_plan_entries, &mut db)?;
+            TrainingPlanEntryDb::create_multiple(&training_plan_entries, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_training_plan_entries(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<TrainingPlanEntryId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<TrainingPlanEntry>>> {
+    match id {
+        Some(id) => {
+            let training_plan_entry_id = id.verify_user_ap(auth, &mut db)?;
+            TrainingPlanEntryDb::get_by_id(training_plan_entry_id, &mut db).map(|t| vec![t])
+        }
+        None => TrainingPlanEntryDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+pub async fn update_training_plan_entries(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(training_plan_entries): Json<UnverifiedSingleOrVec<TrainingPlanEntry>>,
+) -> HandlerResult<StatusCode> {
+    match training_plan_entries {
+        UnverifiedSingleOrVec::Single(training_plan_entry) => {
+            let training_plan_entry = training_plan_entry.verify_user_ap(auth, &mut db)?;
+            check_training_plan_entries(std::slice::from_ref(&training_plan_entry), &mut db)?;
+            TrainingPlanEntryDb::update(&training_plan_entry, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(training_plan_entries) => {
+            let training_plan_entries = training_plan_entries.verify_user_ap(auth, &mut db)?;
+            check_training_plan_entries(&training_plan_entries, &mut db)?;
+            TrainingPlanEntryDb::update_multiple(&training_plan_entries, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+/// Start a training plan and return the created planned sessions.
+pub async fn start_training_plan(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(training_plan_start): Json<TrainingPlanStart>,
+) -> HandlerResult<Json<Vec<PlannedSession>>> {
+    if !TrainingPlanDb::check_user_id(training_plan_start.training_plan_id, *auth, &mut db)? {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    TrainingPlanDb::start(*auth, &training_plan_start, &mut db)?
+        .map(Json)
+        .ok_or_else(|| bad_request("the date of a planned session is out of range").into())
+}
+
+pub async fn get_planned_sessions(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<PlannedSessionId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<PlannedSession>>> {
+    match id {
+        Some(id) => {
+            let planned_session_id = id.verify_user_ap(auth, &mut db)?;
+            PlannedSessionDb::get_by_id(planned_session_id, &mut db).map(|p| vec![p])
+        }
+        None => PlannedSessionDb::get_by_user(*auth, &mut db),
+    }
+    .map(Json)
+    .map_err(Into::into)
+}
+
+/// Planned sessions are created by starting a training plan and completed by updating them with the id of the performed session.
+pub async fn update_planned_sessions(
+    auth: AuthUserOrAP,
+    mut db: DbConn,
+    Json(planned_sessions): Json<UnverifiedSingleOrVec<PlannedSession>>,
+) -> HandlerResult<StatusCode> {
+    match planned_sessions {
+        UnverifiedSingleOrVec::Single(planned_session) => {
+            let planned_session = planned_session.verify_user_ap(auth, &mut db)?;
+            check_planned_sessions(std::slice::from_ref(&planned_session), auth, &mut db)?;
+            PlannedSessionDb::update(&planned_session, &mut db)
+        }
+        UnverifiedSingleOrVec::Vec(planned_sessions) => {
+            let planned_sessions = planned_sessions.verify_user_ap(auth, &mut db)?;
+            check_planned_sessions(&planned_sessions, auth, &mut db)?;
+            PlannedSessionDb::update_multiple(&planned_sessions, &mut db)
+        }
+    }
+    .map(|_| StatusCode::OK)
+    .map_err(Into::into)
+}
+
+pub async fn get_training_plan_adherence(
+    auth: AuthUserOrAP,
+    Query(IdOption { id }): Query<IdOption<UnverifiedId<TrainingPlanId>>>,
+    mut db: DbConn,
+) -> HandlerResult<Json<Vec<TrainingPlanAdherence>>> {
+    let training_plan_id = id.map(|id| id.verify_user_ap(auth, &mut db)).transpose()?;
+    PlannedSessionDb::get_adherence(*auth, training_plan_id, Utc::now().date_naive(), &mut db)
+        .map(Json)
+        .map_err(Into::into)
+}
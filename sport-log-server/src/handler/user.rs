@@ -2,7 +2,7 @@ use axum::{extract::State, http::StatusCode, Json};
 use sport_log_types::User;
 
 use crate::{
-    auth::{AuthAdmin, AuthUser},
+    auth::{AuthAdmin, AuthUser, AuthUserOrAP},
     config::Config,
     db::*,
     handler::{check_password, ErrorMessage, HandlerError, HandlerResult, UnverifiedSingleOrVec},
@@ -53,9 +53,34 @@ pub async fn create_user(
         .map_err(Into::into)
 }
 
-pub async fn get_user(auth: AuthUser, mut db: DbConn) -> HandlerResult<Json<User>> {
+/// Action providers can get the user to contact them, e.g. by email,
+/// but only if they are listed in `user_action_providers` in the [`Config`].
+///
+/// The password hash is not sent.
+pub async fn get_user(
+    auth: AuthUserOrAP,
+    State(config): State<&Config>,
+    mut db: DbConn,
+) -> HandlerResult<Json<User>> {
+    if let Some(action_provider_id) = auth.action_provider_id() {
+        let action_provider = ActionProviderDb::get_by_id(action_provider_id, &mut db)?;
+        if !config.user_action_providers.contains(&action_provider.name) {
+            return Err(HandlerError::from((
+                StatusCode::FORBIDDEN,
+                ErrorMessage::Other {
+                    error: "the action provider is not allowed to get the user".to_owned(),
+                },
+            )));
+        }
+    }
+
     UserDb::get_by_id(*auth, &mut db)
-        .map(Json)
+        .map(|user| {
+            Json(User {
+                password: String::new(),
+                ..user
+            })
+        })
         .map_err(Into::into)
 }
 
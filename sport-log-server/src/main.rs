@@ -27,6 +27,7 @@ use tracing_subscriber::EnvFilter;
 
 use crate::{
     config::Config,
+    db::{Catalogue, CatalogueDb, CatalogueError},
     state::{AppState, DbPool},
 };
 
@@ -83,6 +84,30 @@ fn get_db_pool(config: &Config) -> Result<DbPool, String> {
     Ok(pool)
 }
 
+async fn load_catalogue(config: &Config, db_pool: &DbPool) -> Result<(), String> {
+    let Some(catalogue_file) = &config.catalogue_file else {
+        return Ok(());
+    };
+
+    let catalogue = fs::read_to_string(catalogue_file)
+        .await
+        .map_err(|err| format!("failed to read {}: {err}", catalogue_file.display()))?;
+    let catalogue: Catalogue = toml::from_str(&catalogue)
+        .map_err(|err| format!("failed to parse {}: {err}", catalogue_file.display()))?;
+
+    let mut db = db_pool.get().map_err(|err| {
+        format!("failed to retrieve database connection from connection pool: {err}")
+    })?;
+
+    match CatalogueDb::apply(&catalogue, &mut db) {
+        Ok(()) | Err(CatalogueError::Outdated { .. }) => {
+            info!("catalogue is up to date");
+            Ok(())
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
 async fn run_server(router: Router, config: &Config) -> Result<(), String> {
     let address = if cfg!(debug_assertions) {
         &config.debug_address
@@ -120,6 +145,11 @@ async fn main() -> ExitCode {
         }
     };
 
+    if let Err(error) = load_catalogue(config, &db_pool).await {
+        error!("{error}");
+        return ExitCode::FAILURE;
+    }
+
     let state = AppState { db_pool, config };
 
     let router = router::get_router(state);
@@ -34,6 +34,7 @@ async fn get_version() -> Json<Version> {
 pub fn get_router(state: AppState) -> Router {
     let admin_router = Router::new()
         .route(ADM_GARBAGE_COLLECTION, delete(adm_do_garbage_collection))
+        .route(ADM_CATALOGUE, post(adm_update_catalogue))
         .route(
             ADM_PLATFORM,
             post(adm_create_platforms)
@@ -44,6 +45,10 @@ pub fn get_router(state: AppState) -> Router {
             ADM_ACTION_PROVIDER,
             post(adm_create_action_providers).get(adm_get_action_providers),
         ) // needed if ap self registration disabled
+        .route(
+            ADM_ACTION_PROVIDER_HEALTH,
+            get(adm_get_action_provider_health),
+        )
         .route(
             ADM_ACTION_EVENT,
             post(adm_create_action_events)
@@ -61,13 +66,26 @@ pub fn get_router(state: AppState) -> Router {
         .route(ADM_USER, post(adm_create_users)); // needed if user self registration disabled
 
     let ap_router = Router::new()
-        .route(AP_PLATFORM, post(ap_create_platform).get(ap_get_platforms))
+        .route(
+            AP_PLATFORM,
+            post(ap_create_platform)
+                .get(ap_get_platforms)
+                .put(ap_update_platform),
+        )
         .route(
             AP_ACTION_PROVIDER,
             post(ap_create_action_provider).get(ap_get_action_provider),
         )
-        .route(AP_ACTION, post(ap_create_actions).get(ap_get_actions))
+        .route(AP_HEARTBEAT, post(ap_create_heartbeat))
+        .route(
+            AP_ACTION,
+            post(ap_create_actions)
+                .get(ap_get_actions)
+                .put(ap_update_actions)
+                .delete(ap_delete_actions),
+        )
         .route(AP_ACTION_EVENT, delete(ap_disable_action_events))
+        .route(AP_ACTION_EVENT_RESULT, post(ap_create_action_event_results))
         .route(
             AP_EXECUTABLE_ACTION_EVENT,
             get(ap_get_executable_action_events),
@@ -91,7 +109,14 @@ pub fn get_router(state: AppState) -> Router {
                 .get(get_platform_credentials)
                 .put(update_platform_credentials),
         )
+        .route(
+            ACTIVITY_MAPPING,
+            post(create_activity_mappings)
+                .get(get_activity_mappings)
+                .put(update_activity_mappings),
+        )
         .route(ACTION_PROVIDER, get(get_action_providers))
+        .route(ACTION_PROVIDER_HEALTH, get(get_action_provider_health))
         .route(ACTION, get(get_actions))
         .route(
             ACTION_RULE,
@@ -105,6 +130,7 @@ pub fn get_router(state: AppState) -> Router {
                 .get(get_action_events)
                 .put(update_action_events),
         )
+        .route(ACTION_EVENT_RESULT, get(get_action_event_results))
         .route(
             STRENGTH_SESSION,
             post(create_strength_sessions)
@@ -117,6 +143,12 @@ pub fn get_router(state: AppState) -> Router {
                 .get(get_strength_sets)
                 .put(update_strength_sets),
         )
+        .route(
+            STRENGTH_SUPERSET,
+            post(create_strength_supersets)
+                .get(get_strength_supersets)
+                .put(update_strength_supersets),
+        )
         .route(EORM, get(get_eorms))
         .route(
             METCON_SESSION,
@@ -134,6 +166,13 @@ pub fn get_router(state: AppState) -> Router {
                 .get(get_metcon_movements)
                 .put(update_metcon_movements),
         )
+        .route(
+            METCON_SPLIT,
+            post(create_metcon_splits)
+                .get(get_metcon_splits)
+                .put(update_metcon_splits),
+        )
+        .route(METCON_DRAFT, post(parse_metcon_draft))
         .route(
             CARDIO_SESSION,
             post(create_cardio_sessions)
@@ -144,6 +183,29 @@ pub fn get_router(state: AppState) -> Router {
             ROUTE,
             post(create_routes).get(get_routes).put(update_routes),
         )
+        .route(
+            TRAINING_PLAN,
+            post(create_training_plans)
+                .get(get_training_plans)
+                .put(update_training_plans),
+        )
+        .route(
+            TRAINING_PLAN_ENTRY,
+            post(create_training_plan_entries)
+                .get(get_training_plan_entries)
+                .put(update_training_plan_entries),
+        )
+        .route(TRAINING_PLAN_START, post(start_training_plan))
+        .route(TRAINING_PLAN_ADHERENCE, get(get_training_plan_adherence))
+        .route(
+            PLANNED_SESSION,
+            get(get_planned_sessions).put(update_planned_sessions),
+        )
+        .route(CALENDAR, get(get_calendar))
+        .route(
+            CALENDAR_TOKEN,
+            post(create_calendar_token).delete(delete_calendar_token),
+        )
         .route(
             DIARY,
             post(create_diaries).get(get_diaries).put(update_diaries),
@@ -154,6 +216,13 @@ pub fn get_router(state: AppState) -> Router {
             post(create_movements)
                 .get(get_movements)
                 .put(update_movements),
+        )
+        .route(MUSCLE_GROUP, get(get_muscle_groups))
+        .route(
+            MOVEMENT_MUSCLE,
+            post(create_movement_muscles)
+                .get(get_movement_muscles)
+                .put(update_movement_muscles),
         );
 
     let trace_layer = ServiceBuilder::new()
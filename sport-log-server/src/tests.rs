@@ -10,8 +10,11 @@ use axum::{
     Router,
 };
 use base64::{engine::general_purpose::STANDARD, Engine};
-use chrono::{DateTime, Duration, Utc};
-use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
+use chrono::{DateTime, Duration, NaiveDate, SubsecRound, Utc};
+use diesel::{
+    r2d2::{ConnectionManager, CustomizeConnection, Pool},
+    RunQueryDsl,
+};
 use flate2::write::GzDecoder;
 use hyper::header::CONTENT_ENCODING;
 use lazy_static::lazy_static;
@@ -19,11 +22,27 @@ use mime::APPLICATION_JSON;
 use rand::Rng;
 use serde::de::DeserializeOwned;
 use sport_log_types::{
+    schema::muscle_group,
     uri::{
-        route_max_version, ACCOUNT_DATA, ADM_PLATFORM, AP_ACTION_PROVIDER, AP_PLATFORM, DIARY, USER,
+        route_max_version, ACCOUNT_DATA, ACTION_EVENT, ACTION_EVENT_RESULT, ACTION_PROVIDER_HEALTH,
+        ACTION_RULE, ACTIVITY_MAPPING, ADM_ACTION_PROVIDER_HEALTH, ADM_CATALOGUE, ADM_PLATFORM,
+        AP_ACTION, AP_ACTION_EVENT_RESULT, AP_ACTION_PROVIDER, AP_EXECUTABLE_ACTION_EVENT,
+        AP_HEARTBEAT, AP_PLATFORM, CALENDAR, CALENDAR_TOKEN, DIARY, METCON, METCON_DRAFT,
+        METCON_SESSION, METCON_SPLIT, MOVEMENT_MUSCLE, MUSCLE_GROUP, PLANNED_SESSION,
+        STRENGTH_SESSION, STRENGTH_SET, STRENGTH_SUPERSET, TRAINING_PLAN_ADHERENCE,
+        TRAINING_PLAN_ENTRY, TRAINING_PLAN_START, USER, WOD,
     },
-    AccountData, Action, ActionEvent, ActionEventId, ActionId, ActionProvider, ActionProviderId,
-    Diary, DiaryId, Platform, PlatformId, User, UserId, ADMIN_USERNAME, ID_HEADER,
+    wod_parser::{MetconDraft, WodText},
+    AccountData, Action, ActionEvent, ActionEventId, ActionEventResult, ActionEventResultId,
+    ActionEventStatus, ActionId, ActionProvider, ActionProviderHealth, ActionProviderHeartbeat,
+    ActionProviderId, ActionRule, ActionRuleId, ActivityMapping, ActivityMappingId, CalendarToken,
+    Diary, DiaryId, ExecutableActionEvent, Metcon, MetconId, MetconMovement, MetconMovementId,
+    MetconPart, MetconSession, MetconSessionId, MetconSplit, MetconSplitId, MetconType, Movement,
+    MovementDimension, MovementId, MovementMuscle, MovementMuscleId, MuscleGroup, MuscleGroupId,
+    PlannedSession, PlannedType, Platform, PlatformId, StrengthSession, StrengthSessionId,
+    StrengthSet, StrengthSetId, StrengthSuperset, StrengthSupersetId, StrengthSupersetType,
+    TrainingPlan, TrainingPlanAdherence, TrainingPlanEntry, TrainingPlanEntryId, TrainingPlanId,
+    TrainingPlanStart, User, UserId, Weekday, Wod, WodId, ADMIN_USERNAME, ID_HEADER,
 };
 use tower::Service;
 
@@ -70,6 +89,8 @@ lazy_static! {
         description: None,
         create_before: 1,
         delete_after: 1,
+        max_attempts: 3,
+        argument_schema: None,
         deleted: false,
     };
     static ref TEST_DIARY: Diary = Diary {
@@ -537,6 +558,9 @@ async fn ap_as_user_ap_auth() {
         datetime: Utc::now() + Duration::try_days(1).unwrap(),
         arguments: None,
         enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
         deleted: false,
     };
     ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
@@ -551,6 +575,51 @@ async fn ap_as_user_ap_auth() {
     .await;
 }
 
+#[tokio::test]
+async fn ap_as_user_get_user() {
+    let (mut router, db_pool, config) = init().await;
+
+    // create ActionEvent to ensure access permission for user
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: TEST_ACTION.id,
+        datetime: Utc::now() + Duration::try_days(1).unwrap(),
+        arguments: None,
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
+        deleted: false,
+    };
+    ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
+
+    let [basic_header, user_id_header] =
+        auth_as_headers(&TEST_AP.name, TEST_USER.id.0, &TEST_AP.password);
+    let get_user = || {
+        Request::get(route_max_version("", USER, None))
+            .header(basic_header.0.clone(), basic_header.1.clone())
+            .header(user_id_header.0.clone(), user_id_header.1.clone())
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // only the configured action providers can get the user
+    let response = request(&mut router, get_user()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let config = Box::leak(Box::new(Config {
+        user_action_providers: vec![TEST_AP.name.clone()],
+        ..config.clone()
+    }));
+    let mut router = router::get_router(AppState { db_pool, config });
+    let response = request(&mut router, get_user()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let user: User = parse_body(response).await;
+    assert_eq!(user.email, TEST_USER.email);
+    assert!(user.password.is_empty());
+}
+
 #[tokio::test]
 async fn ap_as_user_ap_auth_no_event() {
     let (mut router, db_pool, _) = init().await;
@@ -563,6 +632,9 @@ async fn ap_as_user_ap_auth_no_event() {
         datetime: Utc::now() + Duration::try_days(1).unwrap(),
         arguments: None,
         enabled: false,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
         deleted: false,
     };
     ActionEventDb::create(&action_event1, &mut db_pool.get().unwrap()).unwrap();
@@ -575,6 +647,9 @@ async fn ap_as_user_ap_auth_no_event() {
         datetime: Utc::now() + Duration::try_days(1).unwrap(),
         arguments: None,
         enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
         deleted: true,
     };
     ActionEventDb::create(&action_event2, &mut db_pool.get().unwrap()).unwrap();
@@ -602,6 +677,9 @@ async fn ap_as_user_ap_auth_wrong_credentials() {
         datetime: Utc::now() + Duration::try_days(1).unwrap(),
         arguments: None,
         enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
         deleted: false,
     };
     ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
@@ -627,6 +705,9 @@ async fn ap_as_user_ap_auth_without_credentials() {
         datetime: Utc::now() + Duration::try_days(1).unwrap(),
         arguments: None,
         enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
         deleted: false,
     };
     ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
@@ -956,6 +1037,2168 @@ async fn get_account_data() {
     assert_eq!(account_data.diaries[0].id, TEST_DIARY.id);
 }
 
+#[tokio::test]
+async fn movement_muscle_create() {
+    let (mut router, db_pool, _) = init().await;
+
+    let mut db = db_pool.get().unwrap();
+    let muscle_group = MuscleGroup {
+        id: MuscleGroupId(rnd()),
+        name: "Muscle".to_owned(),
+        description: None,
+    };
+    diesel::insert_into(muscle_group::table)
+        .values(&muscle_group)
+        .execute(&mut db)
+        .unwrap();
+    let own_movement = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Own Movement".to_owned(),
+        description: None,
+        movement_dimension: MovementDimension::Reps,
+        cardio: false,
+        deleted: false,
+    };
+    let predefined_movement = Movement {
+        id: MovementId(rnd()),
+        user_id: None,
+        name: "Predefined Movement".to_owned(),
+        ..own_movement.clone()
+    };
+    MovementDb::create(&own_movement, &mut db).unwrap();
+    MovementDb::create(&predefined_movement, &mut db).unwrap();
+    drop(db);
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", MUSCLE_GROUP, None))
+            .header(header.0.clone(), header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json(&response);
+    let muscle_groups: Vec<MuscleGroup> = parse_body(response).await;
+    assert!(muscle_groups.iter().any(|m| m.id == muscle_group.id));
+
+    // check that muscles can be assigned to own movements
+    let movement_muscle = MovementMuscle {
+        id: MovementMuscleId(rnd()),
+        movement_id: own_movement.id,
+        muscle_group_id: muscle_group.id,
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", MOVEMENT_MUSCLE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&movement_muscle).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // check that muscles can not be moved to predefined movements
+    let moved_movement_muscle = MovementMuscle {
+        movement_id: predefined_movement.id,
+        ..movement_muscle.clone()
+    };
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", MOVEMENT_MUSCLE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&moved_movement_muscle)
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // check that muscles can be unassigned from own movements
+    let deleted_movement_muscle = MovementMuscle {
+        deleted: true,
+        ..movement_muscle
+    };
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", MOVEMENT_MUSCLE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&deleted_movement_muscle)
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // check that muscles can not be assigned to predefined movements
+    let predefined_movement_muscle = MovementMuscle {
+        id: MovementMuscleId(rnd()),
+        movement_id: predefined_movement.id,
+        muscle_group_id: muscle_group.id,
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", MOVEMENT_MUSCLE, None))
+            .header(header.0, header.1)
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&predefined_movement_muscle)
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn catalogue() {
+    let (mut router, db_pool, _) = init().await;
+
+    let mut db = db_pool.get().unwrap();
+    let version = CatalogueDb::get_version(&mut db).unwrap().unwrap_or(0) + 1;
+    let mut catalogue: toml::Value = toml::from_str(include_str!("../catalogue.toml")).unwrap();
+    catalogue["version"] = toml::Value::Integer(version.into());
+
+    CatalogueDb::apply(&catalogue.clone().try_into().unwrap(), &mut db).unwrap();
+    let movement_muscles = MovementMuscleDb::get_by_user(TEST_USER.id, &mut db).unwrap();
+    assert!(movement_muscles
+        .iter()
+        .any(|movement_muscle| movement_muscle.movement_id == MovementId(22)));
+    drop(db);
+
+    // rename a movement and remove a metcon
+    catalogue["version"] = toml::Value::Integer((version + 1).into());
+    catalogue["movements"][0]["name"] = toml::Value::String("Renamed Movement".to_owned());
+    let metcon_id = catalogue["metcons"][0]["id"].clone();
+    catalogue["metcons"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|metcon| metcon["id"] != metcon_id);
+    catalogue["metcon_movements"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|metcon_movement| metcon_movement["metcon_id"] != metcon_id);
+
+    let header = auth_header(ADMIN_USERNAME, ADMIN_PASSWORD_PLAINTEXT);
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ADM_CATALOGUE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&catalogue).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut db = db_pool.get().unwrap();
+    let movement_id = MovementId(catalogue["movements"][0]["id"].as_integer().unwrap());
+    let movement = MovementDb::get_by_id(movement_id, &mut db).unwrap();
+    assert_eq!(movement.name, "Renamed Movement");
+    let metcon = MetconDb::get_by_id(MetconId(metcon_id.as_integer().unwrap()), &mut db).unwrap();
+    assert!(metcon.deleted);
+    drop(db);
+
+    // check that an outdated catalogue is rejected
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ADM_CATALOGUE, None))
+            .header(header.0, header.1)
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&catalogue).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn metcon_validation() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let tabata = Metcon {
+        id: MetconId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Tabata".to_owned(),
+        metcon_type: MetconType::Tabata,
+        rounds: Some(8),
+        timecap: None,
+        work_time: Some(20_000),
+        rest_time: Some(10_000),
+        rep_scheme: None,
+        description: None,
+        deleted: false,
+    };
+    let ladder = Metcon {
+        id: MetconId(rnd()),
+        name: "Ladder".to_owned(),
+        metcon_type: MetconType::ForTime,
+        rounds: Some(3),
+        work_time: None,
+        rest_time: None,
+        rep_scheme: Some(vec![21, 15, 9]),
+        ..tabata.clone()
+    };
+    let invalid_ladder = Metcon {
+        id: MetconId(rnd()),
+        name: "Invalid Ladder".to_owned(),
+        rep_scheme: Some(vec![21, 15]),
+        ..ladder.clone()
+    };
+
+    for (metcon, status) in [
+        (&tabata, StatusCode::OK),
+        (&ladder, StatusCode::OK),
+        (&invalid_ladder, StatusCode::BAD_REQUEST),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", METCON, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(metcon).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let tabata_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        user_id: TEST_USER.id,
+        metcon_id: tabata.id,
+        datetime: Utc::now(),
+        time: None,
+        rounds: None,
+        reps: Some(100),
+        rx: true,
+        comments: None,
+        deleted: false,
+    };
+    let invalid_tabata_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        time: Some(240_000),
+        ..tabata_session.clone()
+    };
+    let unfinished_ladder_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        metcon_id: ladder.id,
+        rounds: Some(2),
+        reps: Some(5),
+        ..tabata_session.clone()
+    };
+    let invalid_ladder_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        rounds: Some(4),
+        ..unfinished_ladder_session.clone()
+    };
+    // the app sends zero rounds and reps for finished sessions
+    let zero_score_ladder_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        time: Some(400_000),
+        rounds: Some(0),
+        reps: Some(0),
+        ..unfinished_ladder_session.clone()
+    };
+    let invalid_finished_ladder_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        time: Some(400_000),
+        ..unfinished_ladder_session.clone()
+    };
+
+    for (metcon_session, status) in [
+        (&tabata_session, StatusCode::OK),
+        (&invalid_tabata_session, StatusCode::BAD_REQUEST),
+        (&unfinished_ladder_session, StatusCode::OK),
+        (&invalid_ladder_session, StatusCode::BAD_REQUEST),
+        (&zero_score_ladder_session, StatusCode::OK),
+        (&invalid_finished_ladder_session, StatusCode::BAD_REQUEST),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", METCON_SESSION, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(metcon_session).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    // the app does not support tabata and intervals metcons yet
+    let account_data =
+        AccountDataDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert!(account_data
+        .metcons
+        .iter()
+        .all(|metcon| metcon.id != tabata.id));
+    assert!(account_data
+        .metcons
+        .iter()
+        .any(|metcon| metcon.id == ladder.id));
+    assert!(account_data
+        .metcon_sessions
+        .iter()
+        .all(|metcon_session| metcon_session.metcon_id != tabata.id));
+
+    // updates do not keep the fields of the old metcon type or score
+    let for_time = Metcon {
+        metcon_type: MetconType::ForTime,
+        rounds: Some(3),
+        work_time: None,
+        rest_time: None,
+        ..tabata.clone()
+    };
+    let finished_ladder_session = MetconSession {
+        time: Some(420_000),
+        rounds: None,
+        reps: None,
+        ..unfinished_ladder_session.clone()
+    };
+    for (route, body) in [
+        (METCON, serde_json::to_string(&for_time).unwrap()),
+        (
+            METCON_SESSION,
+            serde_json::to_string(&finished_ladder_session).unwrap(),
+        ),
+    ] {
+        let response = request(
+            &mut router,
+            Request::put(route_max_version("", route, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(body.into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let metcon = MetconDb::get_by_id(tabata.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(metcon.work_time, None);
+    assert_eq!(metcon.rest_time, None);
+    let metcon_session =
+        MetconSessionDb::get_by_id(unfinished_ladder_session.id, &mut db_pool.get().unwrap())
+            .unwrap();
+    assert_eq!(metcon_session.time, Some(420_000));
+    assert_eq!(metcon_session.rounds, None);
+    assert_eq!(metcon_session.reps, None);
+}
+
+#[tokio::test]
+async fn metcon_split() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+    let header2 = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let metcon = Metcon {
+        id: MetconId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Splits".to_owned(),
+        metcon_type: MetconType::ForTime,
+        rounds: Some(2),
+        timecap: None,
+        work_time: None,
+        rest_time: None,
+        rep_scheme: None,
+        description: None,
+        deleted: false,
+    };
+    MetconDb::create(&metcon, &mut db_pool.get().unwrap()).unwrap();
+    let mut metcon_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        user_id: TEST_USER.id,
+        metcon_id: metcon.id,
+        datetime: Utc::now(),
+        time: Some(300_000),
+        rounds: None,
+        reps: None,
+        rx: true,
+        comments: None,
+        deleted: false,
+    };
+    MetconSessionDb::create(&metcon_session, &mut db_pool.get().unwrap()).unwrap();
+
+    let metcon_splits: Vec<_> = [140_000, 160_000]
+        .into_iter()
+        .enumerate()
+        .map(|(split_number, time)| MetconSplit {
+            id: MetconSplitId(rnd()),
+            metcon_session_id: metcon_session.id,
+            metcon_movement_id: None,
+            split_number: split_number as i32,
+            time,
+            deleted: false,
+        })
+        .collect();
+
+    for (header, status) in [(&header2, StatusCode::FORBIDDEN), (&header, StatusCode::OK)] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", METCON_SPLIT, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(&metcon_splits).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let account_data =
+        AccountDataDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(account_data.metcon_splits.len(), 2);
+
+    // deleting the session also deletes its splits
+    metcon_session.deleted = true;
+    MetconSessionDb::update(&metcon_session, &mut db_pool.get().unwrap()).unwrap();
+
+    let metcon_splits =
+        MetconSplitDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(metcon_splits.len(), 2);
+    assert!(metcon_splits
+        .iter()
+        .all(|metcon_split| metcon_split.deleted));
+}
+
+#[tokio::test]
+async fn metcon_split_chipper() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let movement = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Wall Ball".to_owned(),
+        description: None,
+        movement_dimension: MovementDimension::Reps,
+        cardio: false,
+        deleted: false,
+    };
+    MovementDb::create(&movement, &mut db_pool.get().unwrap()).unwrap();
+    let metcons: Vec<_> = ["Chipper", "Other Chipper"]
+        .into_iter()
+        .map(|name| Metcon {
+            id: MetconId(rnd()),
+            user_id: Some(TEST_USER.id),
+            name: name.to_owned(),
+            metcon_type: MetconType::ForTime,
+            rounds: Some(1),
+            timecap: None,
+            work_time: None,
+            rest_time: None,
+            rep_scheme: None,
+            description: None,
+            deleted: false,
+        })
+        .collect();
+    MetconDb::create_multiple(&metcons, &mut db_pool.get().unwrap()).unwrap();
+    let metcon_movements: Vec<_> = metcons
+        .iter()
+        .map(|metcon| MetconMovement {
+            id: MetconMovementId(rnd()),
+            metcon_id: metcon.id,
+            movement_id: movement.id,
+            distance_unit: None,
+            metcon_part: MetconPart::Main,
+            movement_number: 0,
+            count: 50,
+            male_weight: None,
+            female_weight: None,
+            deleted: false,
+        })
+        .collect();
+    MetconMovementDb::create_multiple(&metcon_movements, &mut db_pool.get().unwrap()).unwrap();
+    let metcon_session = MetconSession {
+        id: MetconSessionId(rnd()),
+        user_id: TEST_USER.id,
+        metcon_id: metcons[0].id,
+        datetime: Utc::now(),
+        time: Some(300_000),
+        rounds: None,
+        reps: None,
+        rx: true,
+        comments: None,
+        deleted: false,
+    };
+    MetconSessionDb::create(&metcon_session, &mut db_pool.get().unwrap()).unwrap();
+
+    let metcon_split = MetconSplit {
+        id: MetconSplitId(rnd()),
+        metcon_session_id: metcon_session.id,
+        metcon_movement_id: Some(metcon_movements[0].id),
+        split_number: 0,
+        time: 120_000,
+        deleted: false,
+    };
+    let foreign_metcon_split = MetconSplit {
+        id: MetconSplitId(rnd()),
+        metcon_movement_id: Some(metcon_movements[1].id),
+        split_number: 1,
+        ..metcon_split.clone()
+    };
+
+    for (metcon_split, status) in [
+        (&foreign_metcon_split, StatusCode::BAD_REQUEST),
+        (&metcon_split, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", METCON_SPLIT, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(metcon_split).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let metcon_splits =
+        MetconSplitDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(metcon_splits.len(), 1);
+    assert_eq!(
+        metcon_splits[0].metcon_movement_id,
+        Some(metcon_movements[0].id)
+    );
+}
+
+#[tokio::test]
+async fn strength_set_validation() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let plank = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Plank".to_owned(),
+        description: None,
+        movement_dimension: MovementDimension::Time,
+        cardio: false,
+        deleted: false,
+    };
+    MovementDb::create(&plank, &mut db_pool.get().unwrap()).unwrap();
+    let mut strength_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        user_id: TEST_USER.id,
+        datetime: Utc::now(),
+        movement_id: plank.id,
+        interval: None,
+        strength_superset_id: None,
+        superset_position: None,
+        comments: None,
+        deleted: false,
+    };
+    StrengthSessionDb::create(&strength_session, &mut db_pool.get().unwrap()).unwrap();
+
+    let hold = StrengthSet {
+        id: StrengthSetId(rnd()),
+        strength_session_id: strength_session.id,
+        set_number: 0,
+        count: 60_000,
+        weight: Some(20.),
+        rpe: Some(8.5),
+        rir: None,
+        tempo: None,
+        rest: Some(90_000),
+        deleted: false,
+    };
+    let hold_with_tempo = StrengthSet {
+        id: StrengthSetId(rnd()),
+        set_number: 1,
+        rpe: None,
+        tempo: Some("31X0".to_owned()),
+        ..hold.clone()
+    };
+    let hold_with_rpe_and_rir = StrengthSet {
+        id: StrengthSetId(rnd()),
+        set_number: 1,
+        rir: Some(2),
+        ..hold.clone()
+    };
+
+    for (strength_set, status) in [
+        (&hold, StatusCode::OK),
+        (&hold_with_tempo, StatusCode::BAD_REQUEST),
+        (&hold_with_rpe_and_rir, StatusCode::BAD_REQUEST),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", STRENGTH_SET, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(strength_set).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    // switching from rpe to rir and clearing the rest does not keep the old values
+    let squat = Movement {
+        id: MovementId(rnd()),
+        name: "Squat".to_owned(),
+        movement_dimension: MovementDimension::Reps,
+        ..plank.clone()
+    };
+    MovementDb::create(&squat, &mut db_pool.get().unwrap()).unwrap();
+    let squat_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        movement_id: squat.id,
+        ..strength_session.clone()
+    };
+    StrengthSessionDb::create(&squat_session, &mut db_pool.get().unwrap()).unwrap();
+    let squats = StrengthSet {
+        id: StrengthSetId(rnd()),
+        strength_session_id: squat_session.id,
+        count: 5,
+        ..hold.clone()
+    };
+    StrengthSetDb::create(&squats, &mut db_pool.get().unwrap()).unwrap();
+    let squats_with_rir = StrengthSet {
+        rpe: None,
+        rir: Some(2),
+        rest: None,
+        ..squats.clone()
+    };
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", STRENGTH_SET, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&squats_with_rir).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let strength_set = StrengthSetDb::get_by_id(squats.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(strength_set.rpe, None);
+    assert_eq!(strength_set.rir, Some(2));
+    assert_eq!(strength_set.rest, None);
+
+    // the archived set keeps its data
+    strength_session.deleted = true;
+    StrengthSessionDb::update(&strength_session, &mut db_pool.get().unwrap()).unwrap();
+
+    let strength_sets =
+        StrengthSetDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    let strength_sets: Vec<_> = strength_sets
+        .into_iter()
+        .filter(|strength_set| strength_set.strength_session_id == strength_session.id)
+        .collect();
+    assert_eq!(strength_sets.len(), 1);
+    assert!(strength_sets[0].deleted);
+    assert_eq!(strength_sets[0].rpe, hold.rpe);
+    assert_eq!(strength_sets[0].rest, hold.rest);
+}
+
+#[tokio::test]
+async fn strength_superset() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+    let header2 = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let complex = StrengthSuperset {
+        id: StrengthSupersetId(rnd()),
+        user_id: TEST_USER.id,
+        datetime: Utc::now(),
+        strength_superset_type: StrengthSupersetType::Complex,
+        comments: None,
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", STRENGTH_SUPERSET, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&complex).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let movements: Vec<_> = ["Clean", "Front Squat", "Jerk"]
+        .into_iter()
+        .map(|name| Movement {
+            id: MovementId(rnd()),
+            user_id: Some(TEST_USER.id),
+            name: name.to_owned(),
+            description: None,
+            movement_dimension: MovementDimension::Reps,
+            cardio: false,
+            deleted: false,
+        })
+        .collect();
+    MovementDb::create_multiple(&movements, &mut db_pool.get().unwrap()).unwrap();
+
+    let strength_sessions: Vec<_> = movements
+        .iter()
+        .enumerate()
+        .map(|(position, movement)| StrengthSession {
+            id: StrengthSessionId(rnd()),
+            user_id: TEST_USER.id,
+            datetime: complex.datetime,
+            movement_id: movement.id,
+            interval: None,
+            strength_superset_id: Some(complex.id),
+            superset_position: Some(position as i32),
+            comments: None,
+            deleted: false,
+        })
+        .collect();
+    let foreign_strength_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        user_id: TEST_USER2.id,
+        ..strength_sessions[0].clone()
+    };
+    let unordered_strength_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        superset_position: None,
+        ..strength_sessions[0].clone()
+    };
+    let unassigned_strength_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        strength_superset_id: None,
+        ..strength_sessions[0].clone()
+    };
+
+    for (header, body, status) in [
+        (
+            &header2,
+            serde_json::to_string(&foreign_strength_session).unwrap(),
+            StatusCode::FORBIDDEN,
+        ),
+        (
+            &header,
+            serde_json::to_string(&unordered_strength_session).unwrap(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &header,
+            serde_json::to_string(&unassigned_strength_session).unwrap(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &header,
+            serde_json::to_string(&strength_sessions).unwrap(),
+            StatusCode::OK,
+        ),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", STRENGTH_SESSION, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(body.into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let account_data =
+        AccountDataDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(account_data.strength_supersets.len(), 1);
+    assert_eq!(account_data.strength_sessions.len(), 3);
+
+    // updating a session without the superset fields keeps its superset
+    let mut old_client_strength_session = serde_json::to_value(&strength_sessions[1]).unwrap();
+    let old_client_object = old_client_strength_session.as_object_mut().unwrap();
+    old_client_object.remove("strength_superset_id");
+    old_client_object.remove("superset_position");
+    old_client_object.insert("interval".to_owned(), 60_000.into());
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", STRENGTH_SESSION, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(old_client_strength_session.to_string().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let strength_session =
+        StrengthSessionDb::get_by_id(strength_sessions[1].id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(strength_session.interval, Some(60_000));
+    assert_eq!(strength_session.strength_superset_id, Some(complex.id));
+    assert_eq!(strength_session.superset_position, Some(1));
+
+    // removing a session from the superset clears its superset id and position
+    let removed_strength_session = StrengthSession {
+        strength_superset_id: None,
+        superset_position: None,
+        ..strength_sessions[2].clone()
+    };
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", STRENGTH_SESSION, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&removed_strength_session)
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let strength_session =
+        StrengthSessionDb::get_by_id(removed_strength_session.id, &mut db_pool.get().unwrap())
+            .unwrap();
+    assert!(strength_session.strength_superset_id.is_none());
+    assert!(strength_session.superset_position.is_none());
+
+    // deleting the superset keeps the sessions
+    StrengthSupersetDb::update(
+        &StrengthSuperset {
+            deleted: true,
+            ..complex
+        },
+        &mut db_pool.get().unwrap(),
+    )
+    .unwrap();
+
+    let strength_sessions =
+        StrengthSessionDb::get_by_user(TEST_USER.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(strength_sessions.len(), 3);
+    assert!(strength_sessions.iter().all(|strength_session| {
+        !strength_session.deleted && strength_session.strength_superset_id.is_none()
+    }));
+}
+
+#[tokio::test]
+async fn training_plan() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let squat = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Squat".to_owned(),
+        description: None,
+        movement_dimension: MovementDimension::Reps,
+        cardio: false,
+        deleted: false,
+    };
+    MovementDb::create(&squat, &mut db_pool.get().unwrap()).unwrap();
+    let metcon = Metcon {
+        id: MetconId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Cindy".to_owned(),
+        metcon_type: MetconType::Amrap,
+        rounds: None,
+        timecap: Some(1_200_000),
+        work_time: None,
+        rest_time: None,
+        rep_scheme: None,
+        description: None,
+        deleted: false,
+    };
+    MetconDb::create(&metcon, &mut db_pool.get().unwrap()).unwrap();
+    let foreign_movement = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER2.id),
+        name: "Deadlift".to_owned(),
+        ..squat.clone()
+    };
+    MovementDb::create(&foreign_movement, &mut db_pool.get().unwrap()).unwrap();
+
+    // an estimated one rep max of 100 kg
+    let strength_session = StrengthSession {
+        id: StrengthSessionId(rnd()),
+        user_id: TEST_USER.id,
+        datetime: Utc::now(),
+        movement_id: squat.id,
+        interval: None,
+        strength_superset_id: None,
+        superset_position: None,
+        comments: None,
+        deleted: false,
+    };
+    StrengthSessionDb::create(&strength_session, &mut db_pool.get().unwrap()).unwrap();
+    StrengthSetDb::create(
+        &StrengthSet {
+            id: StrengthSetId(rnd()),
+            strength_session_id: strength_session.id,
+            set_number: 0,
+            count: 5,
+            weight: Some(89.),
+            rpe: None,
+            rir: None,
+            tempo: None,
+            rest: None,
+            deleted: false,
+        },
+        &mut db_pool.get().unwrap(),
+    )
+    .unwrap();
+
+    let training_plan = TrainingPlan {
+        id: TrainingPlanId(rnd()),
+        user_id: TEST_USER.id,
+        name: "Two Weeks".to_owned(),
+        description: None,
+        weeks: 2,
+        deleted: false,
+    };
+    TrainingPlanDb::create(&training_plan, &mut db_pool.get().unwrap()).unwrap();
+
+    let strength_entry = TrainingPlanEntry {
+        id: TrainingPlanEntryId(rnd()),
+        training_plan_id: training_plan.id,
+        week: 0,
+        day: 0,
+        entry_number: 0,
+        planned_type: PlannedType::Strength,
+        movement_id: Some(squat.id),
+        metcon_id: None,
+        sets: Some(5),
+        count: Some(5),
+        percentage: Some(0.8),
+        distance: None,
+        time: None,
+        description: None,
+        deleted: false,
+    };
+    let metcon_entry = TrainingPlanEntry {
+        id: TrainingPlanEntryId(rnd()),
+        week: 1,
+        day: 2,
+        planned_type: PlannedType::Metcon,
+        movement_id: None,
+        metcon_id: Some(metcon.id),
+        sets: None,
+        count: None,
+        percentage: None,
+        ..strength_entry.clone()
+    };
+    let out_of_plan_entry = TrainingPlanEntry {
+        id: TrainingPlanEntryId(rnd()),
+        week: 2,
+        ..metcon_entry.clone()
+    };
+    let invalid_entry = TrainingPlanEntry {
+        id: TrainingPlanEntryId(rnd()),
+        entry_number: 1,
+        metcon_id: Some(metcon.id),
+        ..strength_entry.clone()
+    };
+
+    let foreign_entry = TrainingPlanEntry {
+        id: TrainingPlanEntryId(rnd()),
+        entry_number: 1,
+        movement_id: Some(foreign_movement.id),
+        ..strength_entry.clone()
+    };
+
+    for (training_plan_entry, status) in [
+        (&strength_entry, StatusCode::OK),
+        (&metcon_entry, StatusCode::OK),
+        (&out_of_plan_entry, StatusCode::BAD_REQUEST),
+        (&invalid_entry, StatusCode::BAD_REQUEST),
+        (&foreign_entry, StatusCode::FORBIDDEN),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", TRAINING_PLAN_ENTRY, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(training_plan_entry).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", TRAINING_PLAN_START, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&TrainingPlanStart {
+                    training_plan_id: training_plan.id,
+                    start_date: NaiveDate::MAX,
+                })
+                .unwrap()
+                .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let start_date = NaiveDate::from_ymd_opt(2020, 1, 6).unwrap();
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", TRAINING_PLAN_START, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(
+                serde_json::to_string(&TrainingPlanStart {
+                    training_plan_id: training_plan.id,
+                    start_date,
+                })
+                .unwrap()
+                .into(),
+            )
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut planned_sessions: Vec<PlannedSession> = parse_body(response).await;
+    planned_sessions.sort_by_key(|planned_session| planned_session.date);
+
+    assert_eq!(planned_sessions.len(), 2);
+    assert_eq!(planned_sessions[0].date, start_date);
+    assert!((planned_sessions[0].weight.unwrap() - 80.).abs() < 0.01);
+    assert_eq!(
+        planned_sessions[1].date,
+        NaiveDate::from_ymd_opt(2020, 1, 15).unwrap()
+    );
+    assert_eq!(planned_sessions[1].weight, None);
+
+    // complete the strength entry
+    let completed = PlannedSession {
+        strength_session_id: Some(strength_session.id),
+        ..planned_sessions[0].clone()
+    };
+    let mismatched = PlannedSession {
+        strength_session_id: Some(strength_session.id),
+        ..planned_sessions[1].clone()
+    };
+
+    for (planned_session, status) in [
+        (&mismatched, StatusCode::BAD_REQUEST),
+        (&completed, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::put(route_max_version("", PLANNED_SESSION, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(planned_session).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), status);
+    }
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", TRAINING_PLAN_ADHERENCE, None))
+            .header(header.0.clone(), header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let adherences: Vec<TrainingPlanAdherence> = parse_body(response).await;
+
+    assert_eq!(adherences.len(), 1);
+    assert_eq!(adherences[0].training_plan_id, training_plan.id);
+    assert_eq!(adherences[0].planned, 2);
+    assert_eq!(adherences[0].completed, 1);
+    assert_eq!(adherences[0].missed, 1);
+    assert!((adherences[0].adherence - 0.5).abs() < f64::EPSILON);
+
+    // unlink the strength session again
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", PLANNED_SESSION, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&planned_sessions[0]).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let planned_session =
+        PlannedSessionDb::get_by_id(planned_sessions[0].id, &mut db_pool.get().unwrap()).unwrap();
+    assert!(planned_session.strength_session_id.is_none());
+}
+
+#[tokio::test]
+async fn metcon_draft() {
+    let (mut router, _, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let cindy = WodText {
+        name: "Cindy".to_owned(),
+        description: "AMRAP 20\n5 Pull-ups\n10 Push-ups\n15 Air Squats".to_owned(),
+    };
+    let no_metcon = WodText {
+        name: "Strength".to_owned(),
+        description: "Back Squat 5x5".to_owned(),
+    };
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", METCON_DRAFT, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&cindy).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let draft: MetconDraft = parse_body(response).await;
+
+    assert_eq!(draft.metcon.metcon_type, MetconType::Amrap);
+    assert_eq!(draft.metcon.timecap, Some(1_200_000));
+    assert_eq!(draft.metcon_movements.len(), 3);
+    assert!(draft.unresolved.is_empty());
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", METCON_DRAFT, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&no_metcon).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn calendar() {
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER2.id,
+        action_id: TEST_ACTION.id,
+        datetime: Utc::now() + Duration::try_days(1).unwrap(),
+        arguments: Some("Box A, Room 1".to_owned()),
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
+        deleted: false,
+    };
+    ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
+
+    let wod = Wod {
+        id: WodId(rnd()),
+        user_id: TEST_USER2.id,
+        date: NaiveDate::from_ymd_opt(2100, 1, 1).unwrap(),
+        description: Some("AMRAP 20".to_owned()),
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", WOD, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&wod).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", CALENDAR_TOKEN, None))
+            .header(header.0.clone(), header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let calendar_token: CalendarToken = parse_body(response).await;
+    assert_eq!(calendar_token.user_id, TEST_USER2.id);
+    assert_eq!(calendar_token.token.len(), 64);
+
+    // the feed is authenticated by the token only
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            CALENDAR,
+            Some(&[("token", &calendar_token.token)]),
+        ))
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/calendar"));
+    let bytes = body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let calendar = std::str::from_utf8(&bytes).unwrap();
+    assert!(calendar.contains(&format!("UID:action-event-{}@sport-log", action_event.id.0)));
+    assert!(calendar.contains("DESCRIPTION:Box A\\, Room 1"));
+    assert!(calendar.contains(&format!("UID:wod-{}@sport-log", wod.id.0)));
+    assert!(calendar.contains("DTSTART;VALUE=DATE:21000101"));
+
+    // creating a new token invalidates the old one
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", CALENDAR_TOKEN, None))
+            .header(header.0.clone(), header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_calendar_token: CalendarToken = parse_body(response).await;
+    assert_ne!(new_calendar_token.token, calendar_token.token);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            CALENDAR,
+            Some(&[("token", &calendar_token.token)]),
+        ))
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = request(
+        &mut router,
+        Request::delete(route_max_version("", CALENDAR_TOKEN, None))
+            .header(header.0.clone(), header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            CALENDAR,
+            Some(&[("token", &new_calendar_token.token)]),
+        ))
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn action_rule_validation() {
+    let (mut router, _, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let action_rule = ActionRule {
+        id: ActionRuleId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: TEST_ACTION.id,
+        weekday: Weekday::Monday,
+        time: Utc::now(),
+        time_zone: "Europe/Vienna".to_owned(),
+        additional_weekdays: vec![Weekday::Wednesday, Weekday::Friday],
+        week_interval: 2,
+        exclusion_dates: vec![NaiveDate::from_ymd_opt(2100, 1, 1).unwrap()],
+        arguments: None,
+        enabled: true,
+        deleted: false,
+    };
+    let unknown_time_zone = ActionRule {
+        id: ActionRuleId(rnd()),
+        time_zone: "Mars/Olympus_Mons".to_owned(),
+        ..action_rule.clone()
+    };
+    let invalid_week_interval = ActionRule {
+        id: ActionRuleId(rnd()),
+        week_interval: 0,
+        ..action_rule.clone()
+    };
+
+    for (action_rule, status) in [
+        (&action_rule, StatusCode::OK),
+        (&unknown_time_zone, StatusCode::BAD_REQUEST),
+        (&invalid_week_interval, StatusCode::BAD_REQUEST),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", ACTION_RULE, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(action_rule).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            ACTION_RULE,
+            Some(&[("id", &action_rule.id.0.to_string())]),
+        ))
+        .header(header.0.clone(), header.1.clone())
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let action_rules: Vec<ActionRule> = parse_body(response).await;
+    assert_eq!(action_rules[0].time_zone, "Europe/Vienna");
+    assert_eq!(action_rules[0].additional_weekdays.len(), 2);
+    assert_eq!(action_rules[0].exclusion_dates, action_rule.exclusion_dates);
+}
+
+#[tokio::test]
+async fn action_event_result() {
+    let (mut router, db_pool, _) = init().await;
+
+    let ap_header = auth_header(&TEST_AP.name, &TEST_AP.password);
+    let header = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER2.id,
+        action_id: TEST_ACTION.id,
+        datetime: Utc::now().trunc_subsecs(0) + Duration::try_days(1).unwrap(),
+        arguments: None,
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
+        deleted: false,
+    };
+    ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
+
+    let action_event_result = ActionEventResult {
+        id: ActionEventResultId(rnd()),
+        user_id: TEST_USER2.id,
+        action_id: TEST_ACTION.id,
+        action_event_id: action_event.id,
+        action_event_datetime: action_event.datetime,
+        status: ActionEventStatus::UserError,
+        message: Some("can not log in: login failed".to_owned()),
+        datetime: Utc::now(),
+        deleted: false,
+    };
+    let foreign_user = ActionEventResult {
+        id: ActionEventResultId(rnd()),
+        user_id: TEST_USER.id,
+        ..action_event_result.clone()
+    };
+    let wrong_datetime = ActionEventResult {
+        id: ActionEventResultId(rnd()),
+        action_event_datetime: action_event.datetime + Duration::try_hours(1).unwrap(),
+        ..action_event_result.clone()
+    };
+    let non_existing_event = ActionEventResult {
+        id: ActionEventResultId(rnd()),
+        action_event_id: ActionEventId(rnd()),
+        ..action_event_result.clone()
+    };
+
+    for (action_event_result, status) in [
+        (&foreign_user, StatusCode::FORBIDDEN),
+        (&wrong_datetime, StatusCode::FORBIDDEN),
+        (&non_existing_event, StatusCode::FORBIDDEN),
+        (&action_event_result, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", AP_ACTION_EVENT_RESULT, None))
+                .header(ap_header.0.clone(), ap_header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(action_event_result).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    // users can not report results
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", AP_ACTION_EVENT_RESULT, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&action_event_result).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            ACTION_EVENT_RESULT,
+            Some(&[("id", &action_event_result.id.0.to_string())]),
+        ))
+        .header(header.0.clone(), header.1.clone())
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let action_event_results: Vec<ActionEventResult> = parse_body(response).await;
+    assert_eq!(action_event_results.len(), 1);
+    assert_eq!(action_event_results[0].status, ActionEventStatus::UserError);
+    assert_eq!(action_event_results[0].message, action_event_result.message);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", ACCOUNT_DATA, None))
+            .header(header.0, header.1)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_data: AccountData = parse_body(response).await;
+    assert!(account_data
+        .action_event_results
+        .iter()
+        .any(|result| result.id == action_event_result.id));
+}
+
+#[tokio::test]
+async fn action_event_retry() {
+    async fn executable_action_event_ids(router: &mut Router) -> Vec<ActionEventId> {
+        let header = auth_header(&TEST_AP.name, &TEST_AP.password);
+        let response = request(
+            router,
+            Request::get(route_max_version("", AP_EXECUTABLE_ACTION_EVENT, None))
+                .header(header.0, header.1)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let exec_action_events: Vec<ExecutableActionEvent> = parse_body(response).await;
+        exec_action_events
+            .into_iter()
+            .map(|exec_action_event| exec_action_event.action_event_id)
+            .collect()
+    }
+
+    let (mut router, db_pool, _) = init().await;
+
+    let header = auth_header(&TEST_AP.name, &TEST_AP.password);
+
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: TEST_ACTION.id,
+        datetime: Utc::now().trunc_subsecs(0) - Duration::try_hours(1).unwrap(),
+        arguments: None,
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
+        deleted: false,
+    };
+    ActionEventDb::create(&action_event, &mut db_pool.get().unwrap()).unwrap();
+
+    assert!(executable_action_event_ids(&mut router)
+        .await
+        .contains(&action_event.id));
+
+    for attempt in 1..=TEST_ACTION.max_attempts {
+        let action_event_result = ActionEventResult {
+            id: ActionEventResultId(rnd()),
+            user_id: TEST_USER.id,
+            action_id: TEST_ACTION.id,
+            action_event_id: action_event.id,
+            action_event_datetime: action_event.datetime,
+            status: ActionEventStatus::TransientError,
+            message: Some("platform not reachable".to_owned()),
+            datetime: Utc::now(),
+            deleted: false,
+        };
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", AP_ACTION_EVENT_RESULT, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(&action_event_result).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the action event is not executable until the backoff expired
+        assert!(!executable_action_event_ids(&mut router)
+            .await
+            .contains(&action_event.id));
+
+        let action_event =
+            ActionEventDb::get_by_id(action_event.id, &mut db_pool.get().unwrap()).unwrap();
+        assert_eq!(action_event.attempts, attempt);
+        assert!(action_event.retry_after.unwrap() > Utc::now());
+        assert_eq!(action_event.enabled, attempt < TEST_ACTION.max_attempts);
+    }
+
+    // users can neither reset nor set the retry state
+    let user_header = auth_header(&TEST_USER.username, &TEST_USER.password);
+    let new_action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        datetime: action_event.datetime + Duration::try_days(1).unwrap(),
+        attempts: TEST_ACTION.max_attempts,
+        retry_after: Some(Utc::now() + Duration::try_days(1).unwrap()),
+        ..action_event.clone()
+    };
+    let route = route_max_version("", ACTION_EVENT, None);
+    for (builder, action_event) in [
+        (Request::put(&route), &action_event),
+        (Request::post(&route), &new_action_event),
+    ] {
+        let response = request(
+            &mut router,
+            builder
+                .header(user_header.0.clone(), user_header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(action_event).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let action_event =
+        ActionEventDb::get_by_id(action_event.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(action_event.attempts, TEST_ACTION.max_attempts);
+    assert!(action_event.retry_after.is_some());
+    let new_action_event =
+        ActionEventDb::get_by_id(new_action_event.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(new_action_event.attempts, 0);
+    assert!(new_action_event.retry_after.is_none());
+}
+
+#[tokio::test]
+async fn action_event_time_zone() {
+    let (mut router, _, _) = init().await;
+
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: TEST_ACTION.id,
+        datetime: Utc::now() + Duration::try_days(1).unwrap(),
+        arguments: None,
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "Europe/Vienna".to_owned(),
+        deleted: false,
+    };
+    let unknown_time_zone = ActionEvent {
+        id: ActionEventId(rnd()),
+        datetime: action_event.datetime + Duration::try_days(1).unwrap(),
+        time_zone: "Mars/Olympus_Mons".to_owned(),
+        ..action_event.clone()
+    };
+
+    for (action_event, status) in [
+        (&unknown_time_zone, StatusCode::BAD_REQUEST),
+        (&action_event, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", ACTION_EVENT, None))
+                .header(header.0.clone(), header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(action_event).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    let ap_header = auth_header(&TEST_AP.name, &TEST_AP.password);
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", AP_EXECUTABLE_ACTION_EVENT, None))
+            .header(ap_header.0, ap_header.1)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let exec_action_events: Vec<ExecutableActionEvent> = parse_body(response).await;
+    let exec_action_event = exec_action_events
+        .iter()
+        .find(|exec_action_event| exec_action_event.action_event_id == action_event.id)
+        .unwrap();
+    assert_eq!(exec_action_event.time_zone, "Europe/Vienna");
+}
+
+#[tokio::test]
+async fn action_provider_health() {
+    async fn health(
+        router: &mut Router,
+        route: &str,
+        header: (HeaderName, String),
+    ) -> Vec<ActionProviderHealth> {
+        let response = request(
+            router,
+            Request::get(route_max_version("", route, None))
+                .header(header.0, header.1)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        parse_body(response).await
+    }
+
+    let (mut router, db_pool, _) = init().await;
+
+    let ap_header = auth_header(&TEST_AP.name, &TEST_AP.password);
+    let header = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let heartbeat = ActionProviderHeartbeat {
+        action_provider_id: TEST_AP.id,
+        version: "1.0.0".to_owned(),
+        datetime: Utc::now() - Duration::try_days(1).unwrap(),
+        run_interval: 3_600_000,
+        action_events: 3,
+        successes: 1,
+        user_errors: 1,
+        transient_errors: 1,
+    };
+    let foreign_action_provider = ActionProviderHeartbeat {
+        action_provider_id: ActionProviderId(rnd()),
+        ..heartbeat.clone()
+    };
+    let invalid_counts = ActionProviderHeartbeat {
+        action_events: 2,
+        ..heartbeat.clone()
+    };
+    let overflowing_counts = ActionProviderHeartbeat {
+        action_events: i32::MAX,
+        successes: i32::MAX,
+        user_errors: i32::MAX,
+        transient_errors: i32::MAX,
+        ..heartbeat.clone()
+    };
+
+    for (heartbeat, status) in [
+        (&foreign_action_provider, StatusCode::FORBIDDEN),
+        (&invalid_counts, StatusCode::BAD_REQUEST),
+        (&overflowing_counts, StatusCode::BAD_REQUEST),
+        (&heartbeat, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", AP_HEARTBEAT, None))
+                .header(ap_header.0.clone(), ap_header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(heartbeat).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    // the datetime of the heartbeat is set by the server
+    let admin_health = health(
+        &mut router,
+        ADM_ACTION_PROVIDER_HEALTH,
+        auth_header(ADMIN_USERNAME, ADMIN_PASSWORD_PLAINTEXT),
+    )
+    .await;
+    let ap_health = admin_health
+        .iter()
+        .find(|health| health.action_provider_id == TEST_AP.id)
+        .unwrap();
+    assert!(!ap_health.overdue);
+    assert_eq!(ap_health.heartbeat.as_ref().unwrap().version, "1.0.0");
+
+    // users only see the action providers they have action rules for
+    let action_rule = ActionRule {
+        id: ActionRuleId(rnd()),
+        user_id: TEST_USER2.id,
+        action_id: TEST_ACTION.id,
+        weekday: Weekday::Monday,
+        time: Utc::now(),
+        time_zone: "UTC".to_owned(),
+        additional_weekdays: vec![],
+        week_interval: 1,
+        exclusion_dates: vec![],
+        arguments: None,
+        enabled: true,
+        deleted: false,
+    };
+    ActionRuleDb::create(&action_rule, &mut db_pool.get().unwrap()).unwrap();
+
+    let user_health = health(&mut router, ACTION_PROVIDER_HEALTH, header).await;
+    assert_eq!(user_health.len(), 1);
+    assert_eq!(user_health[0].action_provider_id, TEST_AP.id);
+    assert!(!user_health[0].overdue);
+}
+
+#[tokio::test]
+async fn action_argument_schema() {
+    let (mut router, _, _) = init().await;
+
+    let ap_header = auth_header(&TEST_AP.name, &TEST_AP.password);
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+
+    let action = Action {
+        id: ActionId(rnd()),
+        name: format!("schema-action-{}", rnd()),
+        action_provider_id: TEST_AP.id,
+        description: None,
+        create_before: 1,
+        delete_after: 1,
+        max_attempts: 3,
+        argument_schema: Some(
+            r#"{"type": "object", "properties": {"class": {"type": "string"}}, "required": ["class"]}"#
+                .to_owned(),
+        ),
+        deleted: false,
+    };
+    let invalid_schema = Action {
+        id: ActionId(rnd()),
+        name: format!("schema-action-{}", rnd()),
+        argument_schema: Some(r#"{"type": "text"}"#.to_owned()),
+        ..action.clone()
+    };
+
+    for (action, status) in [
+        (&invalid_schema, StatusCode::BAD_REQUEST),
+        (&action, StatusCode::OK),
+    ] {
+        let response = request(
+            &mut router,
+            Request::post(route_max_version("", AP_ACTION, None))
+                .header(ap_header.0.clone(), ap_header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(action).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    let action_rule = ActionRule {
+        id: ActionRuleId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: action.id,
+        weekday: Weekday::Monday,
+        time: Utc::now(),
+        time_zone: "UTC".to_owned(),
+        additional_weekdays: vec![],
+        week_interval: 1,
+        exclusion_dates: vec![],
+        arguments: Some(r#"{"class": "CrossFit"}"#.to_owned()),
+        enabled: true,
+        deleted: false,
+    };
+    let missing_class = ActionRule {
+        id: ActionRuleId(rnd()),
+        weekday: Weekday::Tuesday,
+        arguments: Some(r#"{"room": "A"}"#.to_owned()),
+        ..action_rule.clone()
+    };
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ACTION_RULE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&missing_class).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: serde_json::Value = parse_body(response).await;
+    assert_eq!(
+        error["message"]["invalid_arguments"]["errors"][0]["path"],
+        ""
+    );
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ACTION_RULE, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&action_rule).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let action_event = ActionEvent {
+        id: ActionEventId(rnd()),
+        user_id: TEST_USER.id,
+        action_id: action.id,
+        datetime: Utc::now() + Duration::try_days(1).unwrap(),
+        arguments: None,
+        enabled: true,
+        attempts: 0,
+        retry_after: None,
+        time_zone: "UTC".to_owned(),
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ACTION_EVENT, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&action_event).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn ap_update_and_delete_actions() {
+    let (mut router, db_pool, _) = init().await;
+
+    let ap_header = auth_header(&TEST_AP.name, &TEST_AP.password);
+
+    let mut action = Action {
+        id: ActionId(rnd()),
+        name: format!("update-action-{}", rnd()),
+        ..TEST_ACTION.clone()
+    };
+
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", AP_ACTION, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&action).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    action.description = Some("updated description".to_owned());
+    action.max_attempts = 5;
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", AP_ACTION, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&action).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            AP_ACTION,
+            Some(&[("id", &action.id.0.to_string())]),
+        ))
+        .header(ap_header.0.clone(), ap_header.1.clone())
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let actions: Vec<Action> = parse_body(response).await;
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].description, action.description);
+    assert_eq!(actions[0].max_attempts, action.max_attempts);
+
+    let foreign_action = Action {
+        action_provider_id: ActionProviderId(rnd()),
+        ..action.clone()
+    };
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", AP_ACTION, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&foreign_action).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = request(
+        &mut router,
+        Request::delete(route_max_version("", AP_ACTION, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&[action.id]).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", AP_ACTION, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let actions: Vec<Action> = parse_body(response).await;
+    assert!(actions
+        .iter()
+        .filter(|a| a.id == action.id)
+        .all(|a| a.deleted));
+
+    // the name can not be changed by the action provider
+    let platform = Platform {
+        name: format!("renamed-platform-{}", rnd()),
+        credential: !TEST_PLATFORM.credential,
+        ..TEST_PLATFORM.clone()
+    };
+    let foreign_platform = Platform {
+        id: PlatformId(rnd()),
+        name: format!("foreign-platform-{}", rnd()),
+        ..TEST_PLATFORM.clone()
+    };
+    for (platform, status) in [
+        (&platform, StatusCode::OK),
+        (&foreign_platform, StatusCode::FORBIDDEN),
+    ] {
+        let response = request(
+            &mut router,
+            Request::put(route_max_version("", AP_PLATFORM, None))
+                .header(ap_header.0.clone(), ap_header.1.clone())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(platform).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), status);
+    }
+
+    let platform = PlatformDb::get_by_id(TEST_PLATFORM.id, &mut db_pool.get().unwrap()).unwrap();
+    assert_eq!(platform.name, TEST_PLATFORM.name);
+    assert_eq!(platform.credential, !TEST_PLATFORM.credential);
+}
+
+#[tokio::test]
+async fn activity_mapping() {
+    let (mut router, db_pool, _) = init().await;
+
+    let [ap_header, user_id_header] =
+        auth_as_headers(&TEST_AP.name, TEST_USER.id.0, &TEST_AP.password);
+    let header = auth_header(&TEST_USER.username, &TEST_USER.password);
+    let header2 = auth_header(&TEST_USER2.username, &TEST_USER2.password);
+
+    let movement = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER.id),
+        name: "Mapped Movement".to_owned(),
+        description: None,
+        movement_dimension: MovementDimension::Distance,
+        cardio: true,
+        deleted: false,
+    };
+    let foreign_movement = Movement {
+        id: MovementId(rnd()),
+        user_id: Some(TEST_USER2.id),
+        ..movement.clone()
+    };
+    let other_platform = Platform {
+        id: PlatformId(rnd()),
+        name: "other-platform".to_owned(),
+        credential: false,
+        deleted: false,
+    };
+    {
+        let mut db = db_pool.get().unwrap();
+        MovementDb::create(&movement, &mut db).unwrap();
+        MovementDb::create(&foreign_movement, &mut db).unwrap();
+        PlatformDb::create(&other_platform, &mut db).unwrap();
+        // create ActionEvent to ensure access permission for action provider
+        let action_event = ActionEvent {
+            id: ActionEventId(rnd()),
+            user_id: TEST_USER.id,
+            action_id: TEST_ACTION.id,
+            datetime: Utc::now() + Duration::try_days(1).unwrap(),
+            arguments: None,
+            enabled: true,
+            attempts: 0,
+            retry_after: None,
+            time_zone: "UTC".to_owned(),
+            deleted: false,
+        };
+        ActionEventDb::create(&action_event, &mut db).unwrap();
+    }
+
+    // the action provider surfaces an activity it can not map
+    let mut activity_mapping = ActivityMapping {
+        id: ActivityMappingId(rnd()),
+        user_id: TEST_USER.id,
+        platform_id: TEST_PLATFORM.id,
+        activity: "22".to_owned(),
+        movement_id: None,
+        deleted: false,
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ACTIVITY_MAPPING, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(user_id_header.0.clone(), user_id_header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&activity_mapping).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the user maps the activity to a movement
+    activity_mapping.movement_id = Some(movement.id);
+    let response = request(
+        &mut router,
+        Request::put(route_max_version("", ACTIVITY_MAPPING, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&activity_mapping).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version("", ACTIVITY_MAPPING, None))
+            .header(ap_header.0.clone(), ap_header.1.clone())
+            .header(user_id_header.0.clone(), user_id_header.1.clone())
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let activity_mappings: Vec<ActivityMapping> = parse_body(response).await;
+    assert_eq!(activity_mappings.len(), 1);
+    assert_eq!(activity_mappings[0].movement_id, Some(movement.id));
+
+    let response = request(
+        &mut router,
+        Request::get(route_max_version(
+            "",
+            ACTIVITY_MAPPING,
+            Some(&[("id", &activity_mapping.id.0.to_string())]),
+        ))
+        .header(header2.0.clone(), header2.1.clone())
+        .body(Body::empty())
+        .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // the user can not map to a movement of another user
+    // and the action provider can not create mappings for another platform
+    let foreign_movement_mapping = ActivityMapping {
+        movement_id: Some(foreign_movement.id),
+        ..activity_mapping.clone()
+    };
+    let other_platform_mapping = ActivityMapping {
+        id: ActivityMappingId(rnd()),
+        platform_id: other_platform.id,
+        movement_id: None,
+        ..activity_mapping.clone()
+    };
+    for (request_builder, activity_mapping) in [
+        (
+            Request::put(route_max_version("", ACTIVITY_MAPPING, None))
+                .header(header.0.clone(), header.1.clone()),
+            &foreign_movement_mapping,
+        ),
+        (
+            Request::post(route_max_version("", ACTIVITY_MAPPING, None))
+                .header(ap_header.0.clone(), ap_header.1.clone())
+                .header(user_id_header.0.clone(), user_id_header.1.clone()),
+            &other_platform_mapping,
+        ),
+    ] {
+        let response = request(
+            &mut router,
+            request_builder
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(serde_json::to_string(activity_mapping).unwrap().into())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    // every activity can only be mapped once per platform
+    // (this has to be the last request because the failed insert aborts the test transaction)
+    let duplicate = ActivityMapping {
+        id: ActivityMappingId(rnd()),
+        ..activity_mapping
+    };
+    let response = request(
+        &mut router,
+        Request::post(route_max_version("", ACTIVITY_MAPPING, None))
+            .header(header.0.clone(), header.1.clone())
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+            .body(serde_json::to_string(&duplicate).unwrap().into())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
 #[tokio::test]
 async fn user_self_registration() {
     let (mut router, _, config) = init().await;
@@ -1,6 +1,10 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "action_event_status"))]
+    pub struct ActionEventStatus;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "cardio_type"))]
     pub struct CardioType;
@@ -9,6 +13,10 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "distance_unit"))]
     pub struct DistanceUnit;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "metcon_part"))]
+    pub struct MetconPart;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "metcon_type"))]
     pub struct MetconType;
@@ -17,10 +25,18 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "movement_dimension"))]
     pub struct MovementDimension;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "planned_type"))]
+    pub struct PlannedType;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "position"))]
     pub struct Position;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "strength_superset_type"))]
+    pub struct StrengthSupersetType;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "weekday"))]
     pub struct Weekday;
@@ -37,6 +53,8 @@ diesel::table! {
         description -> Nullable<Text>,
         create_before -> Int4,
         delete_after -> Int4,
+        max_attempts -> Int4,
+        argument_schema -> Nullable<Text>,
         last_change -> Timestamptz,
         deleted -> Bool,
     }
@@ -52,6 +70,28 @@ diesel::table! {
         datetime -> Timestamptz,
         arguments -> Nullable<Text>,
         enabled -> Bool,
+        attempts -> Int4,
+        retry_after -> Nullable<Timestamptz>,
+        #[max_length = 64]
+        time_zone -> Varchar,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ActionEventStatus;
+
+    action_event_result (id) {
+        id -> Int8,
+        user_id -> Int8,
+        action_id -> Int8,
+        action_event_id -> Int8,
+        action_event_datetime -> Timestamptz,
+        status -> ActionEventStatus,
+        message -> Nullable<Text>,
+        datetime -> Timestamptz,
         last_change -> Timestamptz,
         deleted -> Bool,
     }
@@ -73,6 +113,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    action_provider_heartbeat (action_provider_id) {
+        action_provider_id -> Int8,
+        #[max_length = 80]
+        version -> Varchar,
+        datetime -> Timestamptz,
+        run_interval -> Int4,
+        action_events -> Int4,
+        successes -> Int4,
+        user_errors -> Int4,
+        transient_errors -> Int4,
+        last_change -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::Weekday;
@@ -83,6 +140,11 @@ diesel::table! {
         action_id -> Int8,
         weekday -> Weekday,
         time -> Timestamptz,
+        #[max_length = 64]
+        time_zone -> Varchar,
+        additional_weekdays -> Array<Weekday>,
+        week_interval -> Int4,
+        exclusion_dates -> Array<Date>,
         arguments -> Nullable<Text>,
         enabled -> Bool,
         last_change -> Timestamptz,
@@ -90,6 +152,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    activity_mapping (id) {
+        id -> Int8,
+        user_id -> Int8,
+        platform_id -> Int8,
+        #[max_length = 80]
+        activity -> Varchar,
+        movement_id -> Nullable<Int8>,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    calendar_token (user_id) {
+        user_id -> Int8,
+        #[max_length = 64]
+        token -> Varchar,
+        last_change -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::CardioType;
@@ -118,6 +206,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    catalogue_version (version) {
+        version -> Int4,
+        applied -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
 
@@ -178,6 +275,9 @@ diesel::table! {
         metcon_type -> MetconType,
         rounds -> Nullable<Int4>,
         timecap -> Nullable<Int4>,
+        work_time -> Nullable<Int4>,
+        rest_time -> Nullable<Int4>,
+        rep_scheme -> Nullable<Array<Int4>>,
         description -> Nullable<Text>,
         last_change -> Timestamptz,
         deleted -> Bool,
@@ -187,12 +287,14 @@ diesel::table! {
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::DistanceUnit;
+    use super::sql_types::MetconPart;
 
     metcon_movement (id) {
         id -> Int8,
         metcon_id -> Int8,
         movement_id -> Int8,
         distance_unit -> Nullable<DistanceUnit>,
+        metcon_part -> MetconPart,
         movement_number -> Int4,
         count -> Int4,
         male_weight -> Nullable<Float4>,
@@ -220,6 +322,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    metcon_split (id) {
+        id -> Int8,
+        metcon_session_id -> Int8,
+        metcon_movement_id -> Nullable<Int8>,
+        split_number -> Int4,
+        time -> Int4,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::MovementDimension;
@@ -260,6 +376,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    planned_session (id) {
+        id -> Int8,
+        user_id -> Int8,
+        training_plan_entry_id -> Int8,
+        date -> Date,
+        weight -> Nullable<Float4>,
+        strength_session_id -> Nullable<Int8>,
+        metcon_session_id -> Nullable<Int8>,
+        cardio_session_id -> Nullable<Int8>,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
 
@@ -365,6 +498,8 @@ diesel::table! {
         datetime -> Timestamptz,
         movement_id -> Int8,
         interval -> Nullable<Int4>,
+        strength_superset_id -> Nullable<Int8>,
+        superset_position -> Nullable<Int4>,
         comments -> Nullable<Text>,
         last_change -> Timestamptz,
         deleted -> Bool,
@@ -380,6 +515,65 @@ diesel::table! {
         set_number -> Int4,
         count -> Int4,
         weight -> Nullable<Float4>,
+        rpe -> Nullable<Float4>,
+        rir -> Nullable<Int4>,
+        #[max_length = 4]
+        tempo -> Nullable<Varchar>,
+        rest -> Nullable<Int4>,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::StrengthSupersetType;
+
+    strength_superset (id) {
+        id -> Int8,
+        user_id -> Int8,
+        datetime -> Timestamptz,
+        strength_superset_type -> StrengthSupersetType,
+        comments -> Nullable<Text>,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    training_plan (id) {
+        id -> Int8,
+        user_id -> Int8,
+        #[max_length = 80]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        weeks -> Int4,
+        last_change -> Timestamptz,
+        deleted -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::PlannedType;
+
+    training_plan_entry (id) {
+        id -> Int8,
+        training_plan_id -> Int8,
+        week -> Int4,
+        day -> Int4,
+        entry_number -> Int4,
+        planned_type -> PlannedType,
+        movement_id -> Nullable<Int8>,
+        metcon_id -> Nullable<Int8>,
+        sets -> Nullable<Int4>,
+        count -> Nullable<Int4>,
+        percentage -> Nullable<Float4>,
+        distance -> Nullable<Int4>,
+        time -> Nullable<Int4>,
+        description -> Nullable<Text>,
         last_change -> Timestamptz,
         deleted -> Bool,
     }
@@ -416,9 +610,16 @@ diesel::table! {
 diesel::joinable!(action -> action_provider (action_provider_id));
 diesel::joinable!(action_event -> action (action_id));
 diesel::joinable!(action_event -> user (user_id));
+diesel::joinable!(action_event_result -> action (action_id));
+diesel::joinable!(action_event_result -> user (user_id));
 diesel::joinable!(action_provider -> platform (platform_id));
+diesel::joinable!(action_provider_heartbeat -> action_provider (action_provider_id));
 diesel::joinable!(action_rule -> action (action_id));
 diesel::joinable!(action_rule -> user (user_id));
+diesel::joinable!(activity_mapping -> movement (movement_id));
+diesel::joinable!(activity_mapping -> platform (platform_id));
+diesel::joinable!(activity_mapping -> user (user_id));
+diesel::joinable!(calendar_token -> user (user_id));
 diesel::joinable!(cardio_session -> movement (movement_id));
 diesel::joinable!(cardio_session -> route (route_id));
 diesel::joinable!(cardio_session -> user (user_id));
@@ -430,9 +631,16 @@ diesel::joinable!(metcon_movement -> metcon (metcon_id));
 diesel::joinable!(metcon_movement -> movement (movement_id));
 diesel::joinable!(metcon_session -> metcon (metcon_id));
 diesel::joinable!(metcon_session -> user (user_id));
+diesel::joinable!(metcon_split -> metcon_movement (metcon_movement_id));
+diesel::joinable!(metcon_split -> metcon_session (metcon_session_id));
 diesel::joinable!(movement -> user (user_id));
 diesel::joinable!(movement_muscle -> movement (movement_id));
 diesel::joinable!(movement_muscle -> muscle_group (muscle_group_id));
+diesel::joinable!(planned_session -> cardio_session (cardio_session_id));
+diesel::joinable!(planned_session -> metcon_session (metcon_session_id));
+diesel::joinable!(planned_session -> strength_session (strength_session_id));
+diesel::joinable!(planned_session -> training_plan_entry (training_plan_entry_id));
+diesel::joinable!(planned_session -> user (user_id));
 diesel::joinable!(platform_credential -> platform (platform_id));
 diesel::joinable!(platform_credential -> user (user_id));
 diesel::joinable!(route -> user (user_id));
@@ -445,16 +653,27 @@ diesel::joinable!(shared_metcon_session -> metcon_session (metcon_session_id));
 diesel::joinable!(shared_strength_session -> group (group_id));
 diesel::joinable!(shared_strength_session -> strength_session (strength_session_id));
 diesel::joinable!(strength_session -> movement (movement_id));
+diesel::joinable!(strength_session -> strength_superset (strength_superset_id));
 diesel::joinable!(strength_session -> user (user_id));
 diesel::joinable!(strength_set -> strength_session (strength_session_id));
+diesel::joinable!(strength_superset -> user (user_id));
+diesel::joinable!(training_plan -> user (user_id));
+diesel::joinable!(training_plan_entry -> metcon (metcon_id));
+diesel::joinable!(training_plan_entry -> movement (movement_id));
+diesel::joinable!(training_plan_entry -> training_plan (training_plan_id));
 diesel::joinable!(wod -> user (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     action,
     action_event,
+    action_event_result,
     action_provider,
+    action_provider_heartbeat,
     action_rule,
+    activity_mapping,
+    calendar_token,
     cardio_session,
+    catalogue_version,
     diary,
     eorm,
     group,
@@ -462,9 +681,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     metcon,
     metcon_movement,
     metcon_session,
+    metcon_split,
     movement,
     movement_muscle,
     muscle_group,
+    planned_session,
     platform,
     platform_credential,
     route,
@@ -474,6 +695,9 @@ diesel::allow_tables_to_appear_in_same_query!(
     shared_strength_session,
     strength_session,
     strength_set,
+    strength_superset,
+    training_plan,
+    training_plan_entry,
     user,
     wod,
 );
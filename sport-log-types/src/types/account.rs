@@ -14,15 +14,22 @@ pub struct AccountData {
     pub movement_muscles: Vec<MovementMuscle>,
     pub strength_sessions: Vec<StrengthSession>,
     pub strength_sets: Vec<StrengthSet>,
+    pub strength_supersets: Vec<StrengthSuperset>,
     pub metcons: Vec<Metcon>,
     pub metcon_sessions: Vec<MetconSession>,
     pub metcon_movements: Vec<MetconMovement>,
+    pub metcon_splits: Vec<MetconSplit>,
     pub cardio_sessions: Vec<CardioSession>,
     pub routes: Vec<Route>,
+    pub training_plans: Vec<TrainingPlan>,
+    pub training_plan_entries: Vec<TrainingPlanEntry>,
+    pub planned_sessions: Vec<PlannedSession>,
     pub platforms: Vec<Platform>,
     pub platform_credentials: Vec<PlatformCredential>,
+    pub activity_mappings: Vec<ActivityMapping>,
     pub action_providers: Vec<ActionProvider>,
     pub actions: Vec<Action>,
     pub action_rules: Vec<ActionRule>,
     pub action_events: Vec<ActionEvent>,
+    pub action_event_results: Vec<ActionEventResult>,
 }
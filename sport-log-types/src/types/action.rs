@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 #[cfg(feature = "db")]
 use diesel::{deserialize::FromSqlRow, expression::AsExpression, prelude::*, sql_types::BigInt};
 #[cfg(feature = "db")]
@@ -10,7 +10,10 @@ use sport_log_derive::{IdFromSql, IdToSql};
 
 #[cfg(feature = "db")]
 use crate::{
-    schema::{action, action_event, action_provider, action_rule},
+    schema::{
+        action, action_event, action_event_result, action_provider, action_provider_heartbeat,
+        action_rule,
+    },
     Platform, User,
 };
 use crate::{types::IdString, PlatformId, UserId};
@@ -54,6 +57,99 @@ pub struct ActionProvider {
     pub deleted: bool,
 }
 
+/// The heartbeat an [`ActionProvider`] sends at the end of every run.
+///
+/// `run_interval` is the time between two runs in milliseconds.
+/// The counts describe the [`ActionEvent`]s executed in the last run.
+/// `datetime` is set by the server when the heartbeat is received.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(
+        table_name = action_provider_heartbeat,
+        primary_key(action_provider_id),
+        belongs_to(ActionProvider)
+    )
+)]
+pub struct ActionProviderHeartbeat {
+    pub action_provider_id: ActionProviderId,
+    pub version: String,
+    pub datetime: DateTime<Utc>,
+    pub run_interval: i32,
+    pub action_events: i32,
+    pub successes: i32,
+    pub user_errors: i32,
+    pub transient_errors: i32,
+}
+
+impl ActionProviderHeartbeat {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.run_interval <= 0 {
+            return Err("the run interval must be positive");
+        }
+        if self.action_events < 0
+            || self.successes < 0
+            || self.user_errors < 0
+            || self.transient_errors < 0
+        {
+            return Err("the counts must not be negative");
+        }
+        let results = i64::from(self.successes)
+            + i64::from(self.user_errors)
+            + i64::from(self.transient_errors);
+        if results > i64::from(self.action_events) {
+            return Err("the results must not exceed the number of action events");
+        }
+        Ok(())
+    }
+
+    /// An action provider is overdue if it missed more than one run.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        Duration::try_milliseconds(2 * i64::from(self.run_interval))
+            .and_then(|grace_period| self.datetime.checked_add_signed(grace_period))
+            .is_some_and(|deadline| now > deadline)
+    }
+}
+
+/// The health of an [`ActionProvider`] derived from its last [`ActionProviderHeartbeat`].
+///
+/// An action provider which never sent a heartbeat is overdue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActionProviderHealth {
+    pub action_provider_id: ActionProviderId,
+    pub action_provider_name: String,
+    pub heartbeat: Option<ActionProviderHeartbeat>,
+    pub overdue: bool,
+}
+
+impl ActionProviderHealth {
+    pub fn new(
+        action_provider_id: ActionProviderId,
+        action_provider_name: String,
+        heartbeat: Option<ActionProviderHeartbeat>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let overdue = match &heartbeat {
+            Some(heartbeat) => heartbeat.is_overdue(now),
+            None => true,
+        };
+        Self {
+            action_provider_id,
+            action_provider_name,
+            heartbeat,
+            overdue,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
@@ -69,6 +165,12 @@ pub struct ActionProvider {
 )]
 pub struct ActionId(pub i64);
 
+/// An action offered by an [`ActionProvider`].
+///
+/// An [`ActionEvent`] whose execution failed transiently is retried until it has been attempted `max_attempts` times.
+///
+/// If `argument_schema` is set, the arguments of [`ActionRule`]s and [`ActionEvent`]s of this action must be JSON documents valid against this JSON Schema.
+/// See [`argument_schema`](crate::argument_schema) for how schemas are checked.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(
     feature = "db",
@@ -80,7 +182,7 @@ pub struct ActionId(pub i64);
         Selectable,
         AsChangeset,
     ),
-    diesel(table_name = action, belongs_to(ActionProvider))
+    diesel(table_name = action, belongs_to(ActionProvider), treat_none_as_null = true)
 )]
 pub struct Action {
     pub id: ActionId,
@@ -90,9 +192,26 @@ pub struct Action {
     pub description: Option<String>,
     pub create_before: i32,
     pub delete_after: i32,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: i32,
+    #[serde(default)]
+    pub argument_schema: Option<String>,
     pub deleted: bool,
 }
 
+fn default_max_attempts() -> i32 {
+    3
+}
+
+impl Action {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.max_attempts < 1 {
+            return Err("the maximum number of attempts must be at least 1");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(
     feature = "db",
@@ -133,6 +252,13 @@ impl Weekday {
 )]
 pub struct ActionRuleId(pub i64);
 
+/// A recurring schedule from which [`ActionEvent`]s are created.
+///
+/// Events are created on `weekday` and all `additional_weekdays` at the local time of `time` in `time_zone` (an IANA time zone name like `Europe/Vienna`),
+/// so the local time stays the same across daylight saving time transitions.
+///
+/// Only every `week_interval`-th week counted from the week of `time` is used.
+/// No events are created on the local dates in `exclusion_dates`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(
     feature = "db",
@@ -152,12 +278,40 @@ pub struct ActionRule {
     pub action_id: ActionId,
     pub weekday: Weekday,
     pub time: DateTime<Utc>,
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+    #[serde(default)]
+    pub additional_weekdays: Vec<Weekday>,
+    #[serde(default = "default_week_interval")]
+    pub week_interval: i32,
+    #[serde(default)]
+    pub exclusion_dates: Vec<NaiveDate>,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub arguments: Option<String>,
     pub enabled: bool,
     pub deleted: bool,
 }
 
+fn default_time_zone() -> String {
+    "UTC".to_owned()
+}
+
+fn default_week_interval() -> i32 {
+    1
+}
+
+impl ActionRule {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.week_interval < 1 {
+            return Err("the week interval must be at least 1");
+        }
+        self.time_zone
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| "the time zone is unknown")?;
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
@@ -194,6 +348,80 @@ pub struct ActionEvent {
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub arguments: Option<String>,
     pub enabled: bool,
+    /// The number of transiently failed executions.
+    ///
+    /// Managed by the server, values sent by users are ignored.
+    #[serde(default)]
+    pub attempts: i32,
+    /// The action event is not executed again before this time.
+    ///
+    /// Managed by the server, values sent by users are ignored.
+    #[serde(default)]
+    pub retry_after: Option<DateTime<Utc>>,
+    /// The time zone of the [`ActionRule`] the event was created from, used by action providers to display `datetime`.
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+    pub deleted: bool,
+}
+
+impl ActionEvent {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        self.time_zone
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| "the time zone is unknown")?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "db",
+    derive(DbEnum),
+    ExistingTypePath = "crate::schema::sql_types::ActionEventStatus"
+)]
+pub enum ActionEventStatus {
+    Success,
+    /// The action event can not be executed because of the data of the user, e.g. wrong credentials.
+    UserError,
+    /// The execution failed because of a temporary problem, e.g. the platform could not be reached.
+    TransientError,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct ActionEventResultId(pub i64);
+
+/// The result of the execution of an [`ActionEvent`] reported by its [`ActionProvider`].
+///
+/// `action_id` and `action_event_datetime` are copied from the [`ActionEvent`] because action events are deleted soon after their execution.
+/// `datetime` is the time of the execution.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(table_name = action_event_result, belongs_to(User), belongs_to(Action))
+)]
+pub struct ActionEventResult {
+    pub id: ActionEventResultId,
+    pub user_id: UserId,
+    pub action_id: ActionId,
+    pub action_event_id: ActionEventId,
+    pub action_event_datetime: DateTime<Utc>,
+    pub status: ActionEventStatus,
+    pub message: Option<String>,
+    pub datetime: DateTime<Utc>,
     pub deleted: bool,
 }
 
@@ -205,6 +433,10 @@ pub struct CreatableActionRule {
     pub action_id: ActionId,
     pub weekday: Weekday,
     pub time: DateTime<Utc>,
+    pub time_zone: String,
+    pub additional_weekdays: Vec<Weekday>,
+    pub week_interval: i32,
+    pub exclusion_dates: Vec<NaiveDate>,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub arguments: Option<String>,
     pub create_before: i32,
@@ -214,10 +446,13 @@ pub struct CreatableActionRule {
 #[cfg_attr(feature = "db", derive(Queryable))]
 pub struct ExecutableActionEvent {
     pub action_event_id: ActionEventId,
+    pub action_id: ActionId,
     pub action_name: String,
     pub datetime: DateTime<Utc>,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub arguments: Option<String>,
+    pub attempts: i32,
+    pub time_zone: String,
     pub user_id: UserId,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub username: Option<String>,
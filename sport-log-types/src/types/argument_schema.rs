@@ -0,0 +1,159 @@
+//! Validation of the arguments of [`ActionRule`](crate::ActionRule)s and [`ActionEvent`](crate::ActionEvent)s against the JSON Schema of their [`Action`](crate::Action).
+//!
+//! Schemas are validated against the meta-schema of their draft (2020-12 if `$schema` is not set).
+//! The `format` keyword is enforced and unknown formats are rejected so that no constraint is silently ignored.
+//! References to external documents can not be resolved, all `$ref`s have to point into the schema itself.
+
+use jsonschema::{ValidationError, Validator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A validation error of a schema or of arguments.
+///
+/// `path` is a JSON Pointer to the invalid value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentError {
+    pub path: String,
+    pub message: String,
+}
+
+impl ArgumentError {
+    fn invalid_json(error: &serde_json::Error) -> Self {
+        Self {
+            path: String::new(),
+            message: format!("invalid json: {error}"),
+        }
+    }
+}
+
+impl From<ValidationError<'_>> for ArgumentError {
+    fn from(error: ValidationError<'_>) -> Self {
+        Self {
+            path: error.instance_path().as_str().to_owned(),
+            message: error.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArgumentSchema(Validator);
+
+impl ArgumentSchema {
+    pub fn parse(schema: &str) -> Result<Self, Vec<ArgumentError>> {
+        let schema: Value = serde_json::from_str(schema)
+            .map_err(|error| vec![ArgumentError::invalid_json(&error)])?;
+
+        jsonschema::options()
+            .should_validate_formats(true)
+            .should_ignore_unknown_formats(false)
+            .build(&schema)
+            .map(Self)
+            .map_err(|error| vec![error.into()])
+    }
+
+    /// Validate the JSON encoded arguments.
+    ///
+    /// Missing arguments are validated as `null`.
+    pub fn validate(&self, arguments: Option<&str>) -> Result<(), Vec<ArgumentError>> {
+        let arguments = match arguments {
+            Some(arguments) => serde_json::from_str(arguments)
+                .map_err(|error| vec![ArgumentError::invalid_json(&error)])?,
+            None => Value::Null,
+        };
+
+        let errors: Vec<_> = self.0.iter_errors(&arguments).map(Into::into).collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "type": "object",
+        "properties": {
+            "class": { "type": "string", "minLength": 1 },
+            "spots": { "type": "integer", "minimum": 1, "maximum": 20 },
+            "days": { "type": "array", "items": { "enum": ["mon", "wed", "fri"] } }
+        },
+        "required": ["class"],
+        "additionalProperties": false
+    }"#;
+
+    #[test]
+    fn valid_arguments() {
+        let schema = ArgumentSchema::parse(SCHEMA).unwrap();
+
+        assert!(schema
+            .validate(Some(
+                r#"{"class": "CrossFit", "spots": 2, "days": ["mon", "fri"]}"#
+            ))
+            .is_ok());
+        assert!(schema.validate(Some(r#"{"class": "Yoga"}"#)).is_ok());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let schema = ArgumentSchema::parse(SCHEMA).unwrap();
+
+        let errors = schema
+            .validate(Some(r#"{"spots": 2.5, "days": ["tue"], "room": "A"}"#))
+            .unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(errors.len(), 4);
+        assert!(paths.contains(&""));
+        assert!(paths.contains(&"/spots"));
+        assert!(paths.contains(&"/days/0"));
+
+        assert_eq!(schema.validate(None).unwrap_err()[0].path, "");
+        assert!(schema.validate(Some("not json")).is_err());
+    }
+
+    #[test]
+    fn standard_keywords() {
+        let schema = ArgumentSchema::parse(
+            r##"{
+                "$defs": { "time": { "type": "string", "pattern": "^[0-2][0-9]:[0-5][0-9]$" } },
+                "type": "object",
+                "properties": {
+                    "to": { "type": "string", "format": "email" },
+                    "at": { "$ref": "#/$defs/time" },
+                    "spots": { "oneOf": [{ "type": "integer" }, { "const": "all" }] }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        assert!(schema
+            .validate(Some(
+                r#"{"to": "me@example.com", "at": "07:30", "spots": "all"}"#
+            ))
+            .is_ok());
+
+        let errors = schema
+            .validate(Some(r#"{"to": "me", "at": "7:30", "spots": 1.5}"#))
+            .unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(paths.len(), 3);
+        assert!(paths.contains(&"/to"));
+        assert!(paths.contains(&"/at"));
+        assert!(paths.contains(&"/spots"));
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let errors =
+            ArgumentSchema::parse(r#"{"properties": {"a": {"type": "text"}}}"#).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/properties/a/type");
+
+        assert!(ArgumentSchema::parse(r#"{"type": "string", "format": "shoe-size"}"#).is_err());
+        assert!(ArgumentSchema::parse(r#"{"$ref": "https://example.com/schema.json"}"#).is_err());
+        assert!(ArgumentSchema::parse("not json").is_err());
+    }
+}
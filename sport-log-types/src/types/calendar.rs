@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+#[cfg(feature = "db")]
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "db")]
+use crate::{schema::calendar_token, User};
+use crate::{ActionEventId, UserId, Wod};
+
+/// The secret token which authenticates the iCalendar feed of a user.
+///
+/// Calendar apps can not send credentials, so the token is part of the feed url.
+/// Creating a new token invalidates the previous one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(Insertable, Associations, Identifiable, Queryable, Selectable),
+    diesel(table_name = calendar_token, primary_key(user_id), belongs_to(User))
+)]
+pub struct CalendarToken {
+    pub user_id: UserId,
+    pub token: String,
+}
+
+/// An upcoming [`ActionEvent`](crate::ActionEvent) together with the name of its [`Action`](crate::Action).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "db", derive(Queryable))]
+pub struct CalendarActionEvent {
+    pub action_event_id: ActionEventId,
+    pub action_name: String,
+    pub datetime: DateTime<Utc>,
+    pub arguments: Option<String>,
+}
+
+/// Render the action events and wods as an iCalendar (RFC 5545) feed.
+///
+/// The UIDs are derived from the ids so calendar apps update existing events instead of duplicating them.
+pub fn render_calendar(
+    action_events: &[CalendarActionEvent],
+    wods: &[Wod],
+    now: DateTime<Utc>,
+) -> String {
+    let dtstamp = now.format("%Y%m%dT%H%M%SZ");
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//sport-log//sport-log//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "X-WR-CALNAME:Sport Log".to_owned(),
+    ];
+    for action_event in action_events {
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!(
+            "UID:action-event-{}@sport-log",
+            action_event.action_event_id.0
+        ));
+        lines.push(format!("DTSTAMP:{dtstamp}"));
+        lines.push(format!(
+            "DTSTART:{}",
+            action_event.datetime.format("%Y%m%dT%H%M%SZ")
+        ));
+        lines.push(format!("SUMMARY:{}", escape(&action_event.action_name)));
+        if let Some(arguments) = &action_event.arguments {
+            lines.push(format!("DESCRIPTION:{}", escape(arguments)));
+        }
+        lines.push("END:VEVENT".to_owned());
+    }
+    for wod in wods {
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!("UID:wod-{}@sport-log", wod.id.0));
+        lines.push(format!("DTSTAMP:{dtstamp}"));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", wod.date.format("%Y%m%d")));
+        lines.push("SUMMARY:Wod".to_owned());
+        if let Some(description) = &wod.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    let mut calendar = String::new();
+    for line in lines {
+        fold(&line, &mut calendar);
+    }
+    calendar
+}
+
+/// Escape special characters of a text value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append the line folded into lines of at most 75 octets, each terminated by CRLF.
+fn fold(line: &str, calendar: &mut String) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            calendar.push_str("\r\n ");
+            len = 1;
+        }
+        len += c.len_utf8();
+        calendar.push(c);
+    }
+    calendar.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+
+    use super::*;
+    use crate::WodId;
+
+    #[test]
+    fn render() {
+        let action_event = CalendarActionEvent {
+            action_event_id: ActionEventId(1),
+            action_name: "CrossFit".to_owned(),
+            datetime: Utc.with_ymd_and_hms(2026, 10, 20, 17, 0, 0).unwrap(),
+            arguments: Some("Box A, Room 1; Coach: Sam".to_owned()),
+        };
+        let wod = Wod {
+            id: WodId(2),
+            user_id: UserId(3),
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            description: Some("AMRAP 20\n5 Pull-Ups".to_owned()),
+            deleted: false,
+        };
+        let now = Utc.with_ymd_and_hms(2026, 10, 19, 8, 0, 0).unwrap();
+
+        let calendar = render_calendar(&[action_event], &[wod], now);
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("UID:action-event-1@sport-log\r\n"));
+        assert!(calendar.contains("DTSTART:20261020T170000Z\r\n"));
+        assert!(calendar.contains("DESCRIPTION:Box A\\, Room 1\\; Coach: Sam\r\n"));
+        assert!(calendar.contains("UID:wod-2@sport-log\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20261019\r\n"));
+        assert!(calendar.contains("DESCRIPTION:AMRAP 20\\n5 Pull-Ups\r\n"));
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let mut calendar = String::new();
+        fold(&format!("DESCRIPTION:{}", "ä".repeat(100)), &mut calendar);
+
+        for line in calendar.split_terminator("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(
+            calendar.replace("\r\n ", "").trim_end(),
+            format!("DESCRIPTION:{}", "ä".repeat(100))
+        );
+    }
+}
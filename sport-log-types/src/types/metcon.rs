@@ -10,7 +10,7 @@ use sport_log_derive::{IdFromSql, IdToSql};
 
 #[cfg(feature = "db")]
 use crate::{
-    schema::{metcon, metcon_movement, metcon_session},
+    schema::{metcon, metcon_movement, metcon_session, metcon_split},
     Movement, User,
 };
 use crate::{types::IdString, MovementId, UserId};
@@ -25,6 +25,24 @@ pub enum MetconType {
     Amrap,
     Emom,
     ForTime,
+    Tabata,
+    Intervals,
+}
+
+/// The part of a [`Metcon`] a [`MetconMovement`] belongs to.
+///
+/// Movements of the [`MetconPart::BuyIn`] are performed once before the first round, movements of the [`MetconPart::CashOut`] once after the last round.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "db",
+    derive(DbEnum),
+    ExistingTypePath = "crate::schema::sql_types::MetconPart"
+)]
+pub enum MetconPart {
+    BuyIn,
+    #[default]
+    Main,
+    CashOut,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,7 +77,15 @@ pub struct MetconId(pub i64);
 /// If `metcon_type` is [`MetconType::Emom`] rounds and timecap should be set (rounds determines how many rounds should be performed and `timecap`/`rounds` determines how long each round takes).
 ///
 /// If `metcon_type` is [`MetconType::ForTime`] `rounds` should be set and `timecap` can be None or have a value.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// If `metcon_type` is [`MetconType::Tabata`] or [`MetconType::Intervals`] `rounds`, `work_time` and `rest_time` should be set and `timecap` should be `None`.
+/// A classic Tabata consists of 8 rounds with 20 seconds of work and 10 seconds of rest.
+///
+/// `work_time` and `rest_time` must be `None` for all other metcon types.
+///
+/// `rep_scheme` contains the number of reps of each movement per round (e.g. `[21, 15, 9]`) and overrides the `count` of the [`MetconMovement`]s in [`MetconPart::Main`].
+/// It can only be used with [`MetconType::ForTime`], where it must have exactly `rounds` entries, and with [`MetconType::Amrap`], where the last entry is repeated until the timecap is reached.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "db",
     derive(
@@ -70,23 +96,73 @@ pub struct MetconId(pub i64);
         Selectable,
         AsChangeset,
     ),
-    diesel(table_name = metcon, belongs_to(User))
+    diesel(table_name = metcon, belongs_to(User), treat_none_as_null = true)
 )]
 pub struct Metcon {
     pub id: MetconId,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub user_id: Option<UserId>,
     pub name: String,
     pub metcon_type: MetconType,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub rounds: Option<i32>,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub timecap: Option<i32>,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
+    pub work_time: Option<i32>,
+    pub rest_time: Option<i32>,
+    pub rep_scheme: Option<Vec<i32>>,
     pub description: Option<String>,
     pub deleted: bool,
 }
 
+impl Metcon {
+    /// Check that the fields describing the structure of the metcon match its [`MetconType`].
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self.metcon_type {
+            MetconType::Amrap if self.rounds.is_some() || self.timecap.is_none() => {
+                return Err("an amrap must have a timecap and no rounds");
+            }
+            MetconType::Emom if self.rounds.is_none() || self.timecap.is_none() => {
+                return Err("an emom must have rounds and a timecap");
+            }
+            MetconType::ForTime if self.rounds.is_none() => {
+                return Err("a for time metcon must have rounds");
+            }
+            MetconType::Tabata | MetconType::Intervals
+                if self.rounds.is_none()
+                    || self.work_time.is_none()
+                    || self.rest_time.is_none()
+                    || self.timecap.is_some() =>
+            {
+                return Err(
+                    "tabata and intervals must have rounds, a work time and a rest time but no timecap",
+                );
+            }
+            MetconType::Amrap | MetconType::Emom | MetconType::ForTime
+                if self.work_time.is_some() || self.rest_time.is_some() =>
+            {
+                return Err("only tabata and intervals can have a work time and a rest time");
+            }
+            _ => {}
+        }
+
+        match (&self.rep_scheme, self.metcon_type) {
+            (None, _) => Ok(()),
+            (Some(rep_scheme), _) if rep_scheme.iter().any(|reps| *reps < 1) => {
+                Err("all entries of the rep scheme must be at least 1")
+            }
+            (Some(rep_scheme), MetconType::ForTime)
+                if usize::try_from(self.rounds.unwrap_or(0)) == Ok(rep_scheme.len()) =>
+            {
+                Ok(())
+            }
+            (Some(rep_scheme), MetconType::Amrap) if !rep_scheme.is_empty() => Ok(()),
+            (Some(_), MetconType::ForTime) => {
+                Err("the rep scheme must contain exactly one entry per round")
+            }
+            (Some(_), MetconType::Amrap) => Err("the rep scheme must not be empty"),
+            (Some(_), _) => Err("only for time metcons and amraps can have a rep scheme"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
@@ -96,7 +172,7 @@ pub struct Metcon {
 )]
 pub struct MetconMovementId(pub i64);
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "db",
     derive(
@@ -114,6 +190,8 @@ pub struct MetconMovement {
     pub metcon_id: MetconId,
     pub movement_id: MovementId,
     pub distance_unit: Option<DistanceUnit>,
+    #[serde(default)]
+    pub metcon_part: MetconPart,
     pub movement_number: i32,
     pub count: i32,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
@@ -123,7 +201,7 @@ pub struct MetconMovement {
     pub deleted: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
     feature = "db",
@@ -132,6 +210,22 @@ pub struct MetconMovement {
 )]
 pub struct MetconSessionId(pub i64);
 
+/// The result of a [`Metcon`].
+///
+/// Which of `time`, `rounds` and `reps` must be set depends on the [`MetconType`] of the metcon:
+///
+/// [`MetconType::Amrap`]: `rounds` and `reps` are set, `time` is `None`.
+///
+/// [`MetconType::Emom`]: `time`, `rounds` and `reps` are `None`.
+///
+/// [`MetconType::ForTime`]: either `time` is set, or `rounds` and `reps` are set if the metcon was not finished within the timecap.
+/// If `time` is set, `rounds` and `reps` of `0` count as unset.
+///
+/// [`MetconType::Tabata`]: `reps` contains the total number of reps, `time` and `rounds` are `None`.
+///
+/// [`MetconType::Intervals`]: `time` is `None`, `rounds` can contain the number of completed intervals and `reps` the total number of reps.
+///
+/// `rounds` must never exceed the `rounds` of the metcon.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(
     feature = "db",
@@ -143,21 +237,104 @@ pub struct MetconSessionId(pub i64);
         Selectable,
         AsChangeset,
     ),
-    diesel(table_name = metcon_session, belongs_to(User), belongs_to(Metcon))
+    diesel(
+        table_name = metcon_session,
+        belongs_to(User),
+        belongs_to(Metcon),
+        treat_none_as_null = true
+    )
 )]
 pub struct MetconSession {
     pub id: MetconSessionId,
     pub user_id: UserId,
     pub metcon_id: MetconId,
     pub datetime: DateTime<Utc>,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub time: Option<i32>,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub rounds: Option<i32>,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub reps: Option<i32>,
     pub rx: bool,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub comments: Option<String>,
     pub deleted: bool,
 }
+
+impl MetconSession {
+    /// Check that the score of the session matches the [`MetconType`] of its `metcon`.
+    pub fn validate(&self, metcon: &Metcon) -> Result<(), &'static str> {
+        let valid = match metcon.metcon_type {
+            MetconType::Amrap => {
+                self.time.is_none() && self.rounds.is_some() && self.reps.is_some()
+            }
+            MetconType::Emom => self.time.is_none() && self.rounds.is_none() && self.reps.is_none(),
+            MetconType::ForTime => match self.time {
+                Some(_) => self.rounds.unwrap_or(0) == 0 && self.reps.unwrap_or(0) == 0,
+                None => self.rounds.is_some() && self.reps.is_some(),
+            },
+            MetconType::Tabata => {
+                self.time.is_none() && self.rounds.is_none() && self.reps.is_some()
+            }
+            MetconType::Intervals => self.time.is_none(),
+        };
+        if !valid {
+            return Err("the score does not match the metcon type");
+        }
+
+        match (self.rounds, metcon.rounds) {
+            (Some(rounds), Some(max_rounds)) if rounds > max_rounds => {
+                Err("the rounds exceed the rounds of the metcon")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct MetconSplitId(pub i64);
+
+/// The split time of a single round of a [`MetconSession`].
+///
+/// `split_number` orders the splits of a session starting at 0 and `time` is the duration of the round in milliseconds.
+///
+/// For chippers a split can instead be the time of a single [`MetconMovement`] of the metcon of the session.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(table_name = metcon_split, belongs_to(MetconSession), treat_none_as_null = true)
+)]
+pub struct MetconSplit {
+    pub id: MetconSplitId,
+    pub metcon_session_id: MetconSessionId,
+    #[serde(default)]
+    pub metcon_movement_id: Option<MetconMovementId>,
+    pub split_number: i32,
+    pub time: i32,
+    pub deleted: bool,
+}
+
+impl MetconSplit {
+    /// Check that the [`MetconMovement`] of a chipper split belongs to the metcon of the [`MetconSession`].
+    pub fn validate(
+        &self,
+        metcon_session: &MetconSession,
+        metcon_movement: &MetconMovement,
+    ) -> Result<(), &'static str> {
+        if metcon_movement.metcon_id == metcon_session.metcon_id {
+            Ok(())
+        } else {
+            Err("the metcon movement does not belong to the metcon of the session")
+        }
+    }
+}
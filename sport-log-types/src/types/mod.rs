@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 mod account;
 mod action;
 mod admin;
+pub mod argument_schema;
+mod calendar;
 mod cardio;
 mod diary_wod;
 mod metcon;
@@ -11,13 +13,16 @@ mod movement;
 mod platform;
 mod sharing;
 mod strength;
+mod training_plan;
 pub mod uri;
 mod user;
 mod version;
+pub mod wod_parser;
 
 pub use account::*;
 pub use action::*;
 pub use admin::*;
+pub use calendar::*;
 pub use cardio::*;
 pub use diary_wod::*;
 pub use metcon::*;
@@ -25,6 +30,7 @@ pub use movement::*;
 pub use platform::*;
 pub use sharing::*;
 pub use strength::*;
+pub use training_plan::*;
 pub use user::*;
 pub use version::*;
 
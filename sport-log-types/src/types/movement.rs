@@ -47,7 +47,7 @@ pub struct MovementId(pub i64);
 /// Movements can be predefined (`user_id` is [`None`]) or can be user-defined (`user_id` contains the id of the user).
 ///
 /// `categories` decides whether the Movement can be used in Cardio or Strength Sessions or both. For Metcons the `categories` does not matter.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "db",
     derive(
@@ -81,7 +81,7 @@ pub struct Movement {
 )]
 pub struct MuscleGroupId(pub i64);
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "db",
     derive(Insertable, Identifiable, Queryable, Selectable),
@@ -102,7 +102,7 @@ pub struct MuscleGroup {
 )]
 pub struct MovementMuscleId(pub i64);
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(
     feature = "db",
     derive(
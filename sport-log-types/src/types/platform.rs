@@ -7,10 +7,10 @@ use sport_log_derive::{IdFromSql, IdToSql};
 
 #[cfg(feature = "db")]
 use crate::{
-    schema::{platform, platform_credential},
+    schema::{activity_mapping, platform, platform_credential},
     User,
 };
-use crate::{types::IdString, UserId};
+use crate::{types::IdString, MovementId, UserId};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
@@ -84,3 +84,46 @@ pub struct PlatformCredential {
     pub password: String,
     pub deleted: bool,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct ActivityMappingId(pub i64);
+
+/// Maps an activity of a [`Platform`] to a [`Movement`](crate::Movement) of a [`User`](crate::User).
+///
+/// `activity` is the key the platform uses for the activity (f.ex. the activity id of a workout).
+///
+/// Import [`ActionProviders`](crate::ActionProvider) create [`ActivityMappings`](ActivityMapping) without `movement_id` for activities they can not map,
+/// so that the [`User`](crate::User) can choose the [`Movement`](crate::Movement) for them.
+/// `movement_id` is also reset to `None` if the [`Movement`](crate::Movement) is deleted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(
+        table_name = activity_mapping,
+        belongs_to(User),
+        belongs_to(Platform),
+        treat_none_as_null = true
+    )
+)]
+pub struct ActivityMapping {
+    pub id: ActivityMappingId,
+    pub user_id: UserId,
+    pub platform_id: PlatformId,
+    pub activity: String,
+    pub movement_id: Option<MovementId>,
+    pub deleted: bool,
+}
@@ -1,17 +1,70 @@
 use chrono::{DateTime, Utc};
 #[cfg(feature = "db")]
 use diesel::{deserialize::FromSqlRow, expression::AsExpression, prelude::*, sql_types::BigInt};
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "db")]
+use diesel_derive_enum::DbEnum;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use sport_log_derive::IdString;
 #[cfg(feature = "db")]
 use sport_log_derive::{IdFromSql, IdToSql};
 
 #[cfg(feature = "db")]
 use crate::{
-    schema::{eorm, strength_session, strength_set},
+    schema::{eorm, strength_session, strength_set, strength_superset},
     Movement, User,
 };
-use crate::{types::IdString, MovementId, UserId};
+use crate::{types::IdString, MovementDimension, MovementId, UserId};
+
+/// The way the [`StrengthSession`]s of a [`StrengthSuperset`] are performed.
+///
+/// [`StrengthSupersetType::Superset`]: the sets of the movements are alternated with little to no rest in between.
+///
+/// [`StrengthSupersetType::Complex`]: the movements are performed back to back without putting the weight down and form one set together.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "db",
+    derive(DbEnum),
+    ExistingTypePath = "crate::schema::sql_types::StrengthSupersetType"
+)]
+pub enum StrengthSupersetType {
+    Superset,
+    Complex,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct StrengthSupersetId(pub i64);
+
+/// Groups several [`StrengthSession`]s into a superset or complex.
+///
+/// Each [`StrengthSession`] still belongs to exactly one movement and references the superset with `strength_superset_id`.
+/// The order of the movements within the superset is given by `superset_position` of the sessions.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(table_name = strength_superset, belongs_to(User), treat_none_as_null = true)
+)]
+pub struct StrengthSuperset {
+    pub id: StrengthSupersetId,
+    pub user_id: UserId,
+    pub datetime: DateTime<Utc>,
+    pub strength_superset_type: StrengthSupersetType,
+    pub comments: Option<String>,
+    pub deleted: bool,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
@@ -33,7 +86,12 @@ pub struct StrengthSessionId(pub i64);
         Selectable,
         AsChangeset,
     ),
-    diesel(table_name = strength_session,belongs_to(User),belongs_to(Movement))
+    diesel(
+        table_name = strength_session,
+        belongs_to(User),
+        belongs_to(Movement),
+        treat_none_as_null = true
+    )
 )]
 pub struct StrengthSession {
     pub id: StrengthSessionId,
@@ -42,11 +100,59 @@ pub struct StrengthSession {
     pub movement_id: MovementId,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub interval: Option<i32>,
+    #[serde(default)]
+    pub strength_superset_id: Option<StrengthSupersetId>,
+    /// The position of the session within its [`StrengthSuperset`].
+    #[serde(default)]
+    pub superset_position: Option<i32>,
     #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub comments: Option<String>,
     pub deleted: bool,
 }
 
+impl StrengthSession {
+    /// Check that `strength_superset_id` and `superset_position` are either both set or both unset.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match (self.strength_superset_id, self.superset_position) {
+            (Some(_), None) => return Err("a session in a superset needs a superset position"),
+            (None, Some(_)) => {
+                return Err("a session that is not in a superset can not have a superset position")
+            }
+            _ => {}
+        }
+        if self.superset_position.is_some_and(|position| position < 0) {
+            return Err("the superset position must not be negative");
+        }
+        Ok(())
+    }
+}
+
+/// A [`StrengthSession`] sent by a client to update an existing session.
+///
+/// Clients that do not know about supersets omit `strength_superset_id` and `superset_position`,
+/// so the superset of the session is only changed if at least one of them is present.
+#[derive(Debug, Clone)]
+pub struct StrengthSessionUpdate {
+    pub strength_session: StrengthSession,
+    pub update_superset: bool,
+}
+
+impl<'de> Deserialize<'de> for StrengthSessionUpdate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let update_superset =
+            value.get("strength_superset_id").is_some() || value.get("superset_position").is_some();
+        let strength_session = StrengthSession::deserialize(value).map_err(D::Error::custom)?;
+        Ok(Self {
+            strength_session,
+            update_superset,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
@@ -56,6 +162,20 @@ pub struct StrengthSession {
 )]
 pub struct StrengthSetId(pub i64);
 
+/// A single set of a [`StrengthSession`].
+///
+/// The unit of `count` depends on the [`MovementDimension`] of the movement of the session:
+///
+/// [`MovementDimension::Reps`]: the number of repetitions, at most 10,000.
+///
+/// [`MovementDimension::Time`]: the duration in milliseconds, between 1 second and 24 hours.
+///
+/// [`MovementDimension::Energy`]: the energy in calories, at most 10,000.
+///
+/// [`MovementDimension::Distance`]: the distance in meters, at most 1,000 km.
+///
+/// `rir` (reps in reserve) and `tempo` are only meaningful for [`MovementDimension::Reps`].
+/// At most one of `rpe` and `rir` can be set.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(
     feature = "db",
@@ -67,18 +187,79 @@ pub struct StrengthSetId(pub i64);
         Selectable,
         AsChangeset,
     ),
-    diesel(table_name = strength_set, belongs_to(StrengthSession))
+    diesel(
+        table_name = strength_set,
+        belongs_to(StrengthSession),
+        treat_none_as_null = true
+    )
 )]
 pub struct StrengthSet {
     pub id: StrengthSetId,
     pub strength_session_id: StrengthSessionId,
     pub set_number: i32,
     pub count: i32,
-    #[cfg_attr(features = "db", changeset_options(treat_none_as_null = "true"))]
     pub weight: Option<f32>,
+    /// Rate of perceived exertion between 1 and 10.
+    #[serde(default)]
+    pub rpe: Option<f32>,
+    /// Reps in reserve.
+    #[serde(default)]
+    pub rir: Option<i32>,
+    /// Tempo in the common four digit notation (eccentric, bottom, concentric, top), e.g. `31X0`.
+    #[serde(default)]
+    pub tempo: Option<String>,
+    /// Rest after the set in milliseconds.
+    #[serde(default)]
+    pub rest: Option<i32>,
     pub deleted: bool,
 }
 
+impl StrengthSet {
+    /// Check that the set is consistent and that `count` and the other fields make sense for the [`MovementDimension`] of the movement of the session.
+    pub fn validate(&self, movement_dimension: MovementDimension) -> Result<(), &'static str> {
+        let (count_range, error) = match movement_dimension {
+            MovementDimension::Reps => (1..=10_000, "the reps must be between 1 and 10,000"),
+            MovementDimension::Time => (
+                1_000..=86_400_000,
+                "the time must be between 1 second and 24 hours",
+            ),
+            MovementDimension::Energy => (1..=10_000, "the calories must be between 1 and 10,000"),
+            MovementDimension::Distance => (
+                1..=1_000_000,
+                "the distance must be between 1 meter and 1,000 km",
+            ),
+        };
+        if !count_range.contains(&self.count) {
+            return Err(error);
+        }
+        if let Some(rpe) = self.rpe {
+            if !(1. ..=10.).contains(&rpe) {
+                return Err("the rpe must be between 1 and 10");
+            }
+        }
+        if self.rir.is_some_and(|rir| rir < 0) {
+            return Err("the rir must not be negative");
+        }
+        if self.rpe.is_some() && self.rir.is_some() {
+            return Err("only one of rpe and rir can be set");
+        }
+        if let Some(tempo) = &self.tempo {
+            if tempo.len() != 4 || !tempo.chars().all(|c| c.is_ascii_digit() || c == 'X') {
+                return Err("the tempo must consist of four digits or 'X'");
+            }
+        }
+        if self.rest.is_some_and(|rest| rest < 0) {
+            return Err("the rest must not be negative");
+        }
+        if movement_dimension != MovementDimension::Reps
+            && (self.rir.is_some() || self.tempo.is_some())
+        {
+            return Err("rir and tempo are only allowed for movements with dimension reps");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
 #[serde(try_from = "IdString", into = "IdString")]
 #[cfg_attr(
@@ -99,3 +280,50 @@ pub struct Eorm {
     pub reps: i32,
     pub percentage: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strength_set(count: i32) -> StrengthSet {
+        StrengthSet {
+            id: StrengthSetId(1),
+            strength_session_id: StrengthSessionId(1),
+            set_number: 0,
+            count,
+            weight: None,
+            rpe: None,
+            rir: None,
+            tempo: None,
+            rest: None,
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn count_per_movement_dimension() {
+        for (movement_dimension, valid, invalid) in [
+            (MovementDimension::Reps, [1, 10_000], [0, 10_001]),
+            (
+                MovementDimension::Time,
+                [1_000, 86_400_000],
+                [999, 86_400_001],
+            ),
+            (MovementDimension::Energy, [1, 10_000], [0, 10_001]),
+            (MovementDimension::Distance, [1, 1_000_000], [0, 1_000_001]),
+        ] {
+            for count in valid {
+                assert!(
+                    strength_set(count).validate(movement_dimension).is_ok(),
+                    "{movement_dimension:?} {count}"
+                );
+            }
+            for count in invalid {
+                assert!(
+                    strength_set(count).validate(movement_dimension).is_err(),
+                    "{movement_dimension:?} {count}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::NaiveDate;
+#[cfg(feature = "db")]
+use diesel::{deserialize::FromSqlRow, expression::AsExpression, prelude::*, sql_types::BigInt};
+#[cfg(feature = "db")]
+use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
+use sport_log_derive::IdString;
+#[cfg(feature = "db")]
+use sport_log_derive::{IdFromSql, IdToSql};
+
+#[cfg(feature = "db")]
+use crate::{
+    schema::{planned_session, training_plan, training_plan_entry},
+    User,
+};
+use crate::{
+    types::IdString, CardioSessionId, MetconId, MetconSessionId, MovementId, StrengthSessionId,
+    UserId,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct TrainingPlanId(pub i64);
+
+/// A multi-week training plan consisting of [`TrainingPlanEntry`]s.
+///
+/// A plan can be started for a date which creates a [`PlannedSession`] for every entry.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(table_name = training_plan, belongs_to(User), treat_none_as_null = true)
+)]
+pub struct TrainingPlan {
+    pub id: TrainingPlanId,
+    pub user_id: UserId,
+    pub name: String,
+    pub description: Option<String>,
+    pub weeks: i32,
+    pub deleted: bool,
+}
+
+impl TrainingPlan {
+    /// The maximum number of weeks of a training plan.
+    pub const MAX_WEEKS: i32 = 520;
+
+    /// Check that the number of weeks is between 1 and [`TrainingPlan::MAX_WEEKS`].
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if (1..=Self::MAX_WEEKS).contains(&self.weeks) {
+            Ok(())
+        } else {
+            Err("the weeks of the training plan are out of range")
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "db",
+    derive(DbEnum),
+    ExistingTypePath = "crate::schema::sql_types::PlannedType"
+)]
+pub enum PlannedType {
+    Strength,
+    Metcon,
+    Cardio,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct TrainingPlanEntryId(pub i64);
+
+/// A planned unit of work on a `day` (0 to 6, starting with the day the plan is started) of a `week` (starting at 0) of a [`TrainingPlan`].
+///
+/// Which fields must be set depends on the [`PlannedType`]:
+///
+/// [`PlannedType::Strength`]: `movement_id`, `sets` and `count` are set.
+/// `percentage` is the fraction of the estimated one rep max of the user for the movement, e.g. `0.75`.
+///
+/// [`PlannedType::Metcon`]: `metcon_id` is set.
+///
+/// [`PlannedType::Cardio`]: `movement_id` is set, `distance` (meter) and `time` (milliseconds) are optional targets.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(
+        table_name = training_plan_entry,
+        belongs_to(TrainingPlan),
+        treat_none_as_null = true
+    )
+)]
+pub struct TrainingPlanEntry {
+    pub id: TrainingPlanEntryId,
+    pub training_plan_id: TrainingPlanId,
+    pub week: i32,
+    pub day: i32,
+    pub entry_number: i32,
+    pub planned_type: PlannedType,
+    pub movement_id: Option<MovementId>,
+    pub metcon_id: Option<MetconId>,
+    pub sets: Option<i32>,
+    pub count: Option<i32>,
+    pub percentage: Option<f32>,
+    pub distance: Option<i32>,
+    pub time: Option<i32>,
+    pub description: Option<String>,
+    pub deleted: bool,
+}
+
+impl TrainingPlanEntry {
+    /// Check that the fields of the entry match its [`PlannedType`].
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let valid = match self.planned_type {
+            PlannedType::Strength => {
+                self.movement_id.is_some()
+                    && self.metcon_id.is_none()
+                    && self.sets.is_some()
+                    && self.count.is_some()
+                    && self.distance.is_none()
+                    && self.time.is_none()
+            }
+            PlannedType::Metcon => {
+                self.movement_id.is_none()
+                    && self.metcon_id.is_some()
+                    && self.sets.is_none()
+                    && self.count.is_none()
+                    && self.percentage.is_none()
+                    && self.distance.is_none()
+                    && self.time.is_none()
+            }
+            PlannedType::Cardio => {
+                self.movement_id.is_some()
+                    && self.metcon_id.is_none()
+                    && self.sets.is_none()
+                    && self.count.is_none()
+                    && self.percentage.is_none()
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err("the entry does not match the planned type")
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, IdString)]
+#[serde(try_from = "IdString", into = "IdString")]
+#[cfg_attr(
+    feature = "db",
+    derive(Hash, FromSqlRow, AsExpression, IdToSql, IdFromSql),
+    diesel(sql_type = BigInt)
+)]
+pub struct PlannedSessionId(pub i64);
+
+/// A [`TrainingPlanEntry`] scheduled for a `date` after a [`TrainingPlan`] has been started.
+///
+/// `weight` is calculated from the `percentage` of the entry and the records of the user when the plan is started.
+///
+/// A planned session is completed by setting the id of the session of the matching [`PlannedType`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "db",
+    derive(
+        Insertable,
+        Associations,
+        Identifiable,
+        Queryable,
+        Selectable,
+        AsChangeset,
+    ),
+    diesel(
+        table_name = planned_session,
+        belongs_to(User),
+        belongs_to(TrainingPlanEntry),
+        treat_none_as_null = true
+    )
+)]
+pub struct PlannedSession {
+    pub id: PlannedSessionId,
+    pub user_id: UserId,
+    pub training_plan_entry_id: TrainingPlanEntryId,
+    pub date: NaiveDate,
+    pub weight: Option<f32>,
+    pub strength_session_id: Option<StrengthSessionId>,
+    pub metcon_session_id: Option<MetconSessionId>,
+    pub cardio_session_id: Option<CardioSessionId>,
+    pub deleted: bool,
+}
+
+impl PlannedSession {
+    /// Check that only a session matching the [`PlannedType`] of the entry is linked.
+    pub fn validate(&self, planned_type: PlannedType) -> Result<(), &'static str> {
+        let valid = match planned_type {
+            PlannedType::Strength => {
+                self.metcon_session_id.is_none() && self.cardio_session_id.is_none()
+            }
+            PlannedType::Metcon => {
+                self.strength_session_id.is_none() && self.cardio_session_id.is_none()
+            }
+            PlannedType::Cardio => {
+                self.strength_session_id.is_none() && self.metcon_session_id.is_none()
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err("the linked session does not match the planned type")
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.strength_session_id.is_some()
+            || self.metcon_session_id.is_some()
+            || self.cardio_session_id.is_some()
+    }
+}
+
+/// Start a [`TrainingPlan`] on `start_date`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrainingPlanStart {
+    pub training_plan_id: TrainingPlanId,
+    pub start_date: NaiveDate,
+}
+
+/// The adherence of a user to a started [`TrainingPlan`].
+///
+/// `missed` counts the planned sessions before today that have not been completed.
+/// `adherence` is the fraction of the due planned sessions that have been completed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrainingPlanAdherence {
+    pub training_plan_id: TrainingPlanId,
+    pub planned: i64,
+    pub completed: i64,
+    pub missed: i64,
+    pub adherence: f64,
+}
@@ -33,37 +33,56 @@ pub const USER: &str = "/user";
 
 pub const PLATFORM: &str = "/platform";
 pub const PLATFORM_CREDENTIAL: &str = "/platform_credential";
+pub const ACTIVITY_MAPPING: &str = "/activity_mapping";
 pub const ACTION_PROVIDER: &str = "/action_provider";
+pub const ACTION_PROVIDER_HEALTH: &str = "/action_provider_health";
 pub const ACTION: &str = "/action";
 pub const ACTION_RULE: &str = "/action_rule";
 pub const ACTION_EVENT: &str = "/action_event";
+pub const ACTION_EVENT_RESULT: &str = "/action_event_result";
 
 pub const STRENGTH_SESSION: &str = "/strength_session";
 pub const STRENGTH_SET: &str = "/strength_set";
+pub const STRENGTH_SUPERSET: &str = "/strength_superset";
 pub const EORM: &str = "/eorm";
 
 pub const METCON_SESSION: &str = "/metcon_session";
 pub const METCON: &str = "/metcon";
 pub const METCON_MOVEMENT: &str = "/metcon_movement";
+pub const METCON_SPLIT: &str = "/metcon_split";
+pub const METCON_DRAFT: &str = "/metcon_draft";
 
 pub const CARDIO_SESSION: &str = "/cardio_session";
 pub const ROUTE: &str = "/route";
 
+pub const TRAINING_PLAN: &str = "/training_plan";
+pub const TRAINING_PLAN_ENTRY: &str = "/training_plan_entry";
+pub const TRAINING_PLAN_START: &str = "/training_plan_start";
+pub const TRAINING_PLAN_ADHERENCE: &str = "/training_plan_adherence";
+pub const PLANNED_SESSION: &str = "/planned_session";
+
+pub const CALENDAR: &str = "/calendar";
+pub const CALENDAR_TOKEN: &str = "/calendar_token";
+
 pub const DIARY: &str = "/diary";
 pub const WOD: &str = "/wod";
 
 pub const MOVEMENT: &str = "/movement";
+pub const MUSCLE_GROUP: &str = "/muscle_group";
+pub const MOVEMENT_MUSCLE: &str = "/movement_muscle";
 
 // admin URIs
 
 const ADM: &str = "/adm";
 
 pub const ADM_GARBAGE_COLLECTION: &str = concatcp!(ADM, "/garbage_collection");
+pub const ADM_CATALOGUE: &str = concatcp!(ADM, "/catalogue");
 
 pub const ADM_USER: &str = concatcp!(ADM, USER);
 
 pub const ADM_PLATFORM: &str = concatcp!(ADM, PLATFORM);
 pub const ADM_ACTION_PROVIDER: &str = concatcp!(ADM, ACTION_PROVIDER);
+pub const ADM_ACTION_PROVIDER_HEALTH: &str = concatcp!(ADM, ACTION_PROVIDER_HEALTH);
 pub const ADM_ACTION_EVENT: &str = concatcp!(ADM, ACTION_EVENT);
 pub const ADM_CREATABLE_ACTION_RULE: &str = concatcp!(ADM, "/creatable_action_rule");
 pub const ADM_DELETABLE_ACTION_EVENT: &str = concatcp!(ADM, "/deletable_action_event");
@@ -74,6 +93,8 @@ const AP: &str = "/ap";
 
 pub const AP_PLATFORM: &str = concatcp!(AP, PLATFORM);
 pub const AP_ACTION_PROVIDER: &str = concatcp!(AP, ACTION_PROVIDER);
+pub const AP_HEARTBEAT: &str = concatcp!(AP, "/heartbeat");
 pub const AP_ACTION: &str = concatcp!(AP, ACTION);
 pub const AP_ACTION_EVENT: &str = concatcp!(AP, ACTION_EVENT);
+pub const AP_ACTION_EVENT_RESULT: &str = concatcp!(AP, ACTION_EVENT_RESULT);
 pub const AP_EXECUTABLE_ACTION_EVENT: &str = concatcp!(AP, "/executable_action_event");
@@ -0,0 +1,583 @@
+//! Parser for the common notation of workouts of the day.
+//!
+//! [`parse_wod`] turns the free text description of a [`Wod`](crate::Wod) into a draft [`Metcon`] with its [`MetconMovement`]s.
+//! The parser is line based and recognises
+//! - headers like `AMRAP 20`, `EMOM 10`, `Tabata`, `For Time`, `3 Rounds for Time`, `Time Cap: 12`,
+//! - rep schemes like `21-15-9`,
+//! - `Buy-In` and `Cash-Out` sections,
+//! - movement lines like `15 Thrusters (43/29 kg)`, `400m Run`, `30 Cal Row` or `Deadlift @ 100kg`.
+//!
+//! Movements are resolved against the given list of movements by name.
+//! Lines that can not be resolved are returned in [`MetconDraft::unresolved`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    DistanceUnit, Metcon, MetconId, MetconMovement, MetconMovementId, MetconPart, MetconType,
+    Movement, MovementDimension, MovementId, UserId,
+};
+
+/// The name and description of a wod that should be parsed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WodText {
+    pub name: String,
+    pub description: String,
+}
+
+/// A [`Metcon`] with its [`MetconMovement`]s that has been parsed from the description of a wod but not yet been created.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetconDraft {
+    pub metcon: Metcon,
+    pub metcon_movements: Vec<MetconMovement>,
+    /// Lines of the description that could not be resolved to a movement.
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Header {
+    metcon_type: Option<MetconType>,
+    rounds: Option<i32>,
+    timecap: Option<i32>,
+    work_time: Option<i32>,
+    rest_time: Option<i32>,
+    rep_scheme: Option<Vec<i32>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct ParsedMovement {
+    name: String,
+    count: Option<i32>,
+    distance_unit: Option<DistanceUnit>,
+    dimension: MovementDimension,
+    male_weight: Option<f32>,
+    female_weight: Option<f32>,
+}
+
+const MINUTE: i32 = 60_000;
+
+/// Parse the description of a wod into a [`MetconDraft`] owned by `user_id`.
+///
+/// `movements` are the movements the user has access to.
+/// `new_id` is called to generate the ids of the metcon and the metcon movements.
+///
+/// Returns an error if no metcon type was found, if a number is zero or too large or if the resulting metcon is not valid.
+pub fn parse_wod(
+    wod_text: &WodText,
+    user_id: UserId,
+    movements: &[Movement],
+    mut new_id: impl FnMut() -> i64,
+) -> Result<MetconDraft, &'static str> {
+    let mut header = Header::default();
+    let mut metcon_part = MetconPart::Main;
+    let mut parsed_movements = vec![];
+    let mut unresolved = vec![];
+
+    for line in wod_text.description.lines() {
+        let line = line.trim().trim_start_matches(['-', '*', '•']).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let lower = line.to_lowercase();
+        let (head, tail) = match lower.split_once(':') {
+            Some((head, tail)) => (head.trim(), tail.trim()),
+            None => (lower.as_str(), ""),
+        };
+
+        if let Some(part) = parse_metcon_part(head) {
+            metcon_part = part;
+            if tail.is_empty() {
+                continue;
+            }
+            for movement in tail.split(',') {
+                parsed_movements.push((metcon_part, movement.trim().to_owned()));
+            }
+            metcon_part = MetconPart::Main;
+        } else if parse_header(head, &lower, &mut header)? {
+            metcon_part = MetconPart::Main;
+            // invalid movements are kept so that their error is returned below
+            for movement in tail
+                .split(',')
+                .filter(|movement| !matches!(parse_movement(movement), Ok(None)))
+            {
+                parsed_movements.push((metcon_part, movement.trim().to_owned()));
+            }
+        } else {
+            parsed_movements.push((metcon_part, lower.clone()));
+        }
+    }
+
+    let Some(metcon_type) = header.metcon_type else {
+        return Err("no metcon type found");
+    };
+    let rounds = match metcon_type {
+        MetconType::Amrap => None,
+        MetconType::ForTime => header
+            .rep_scheme
+            .as_ref()
+            .map(|rep_scheme| rep_scheme.len() as i32)
+            .or(header.rounds)
+            .or(Some(1)),
+        MetconType::Emom | MetconType::Tabata | MetconType::Intervals => header.rounds,
+    };
+    let metcon = Metcon {
+        id: MetconId(new_id()),
+        user_id: Some(user_id),
+        name: wod_text.name.clone(),
+        metcon_type,
+        rounds,
+        timecap: header.timecap,
+        work_time: header.work_time,
+        rest_time: header.rest_time,
+        rep_scheme: header.rep_scheme,
+        description: Some(wod_text.description.clone()),
+        deleted: false,
+    };
+    metcon.validate()?;
+
+    let mut metcon_movements = vec![];
+    for (metcon_part, text) in parsed_movements {
+        let resolved = parse_movement(&text)?
+            .and_then(|parsed| resolve_movement(&parsed, movements).map(|id| (parsed, id)));
+        match resolved {
+            Some((parsed, movement_id)) => metcon_movements.push(MetconMovement {
+                id: MetconMovementId(new_id()),
+                metcon_id: metcon.id,
+                movement_id,
+                distance_unit: parsed.distance_unit,
+                metcon_part,
+                movement_number: metcon_movements.len() as i32,
+                count: parsed.count.unwrap_or(1),
+                male_weight: parsed.male_weight,
+                female_weight: parsed.female_weight,
+                deleted: false,
+            }),
+            None => unresolved.push(text),
+        }
+    }
+
+    Ok(MetconDraft {
+        metcon,
+        metcon_movements,
+        unresolved,
+    })
+}
+
+fn parse_metcon_part(head: &str) -> Option<MetconPart> {
+    let head = head.replace([' ', '-'], "");
+    if head.starts_with("buyin") {
+        Some(MetconPart::BuyIn)
+    } else if head.starts_with("cashout") {
+        Some(MetconPart::CashOut)
+    } else {
+        None
+    }
+}
+
+/// Update `header` with the information contained in `head` (or the whole `line` for the timecap) and return whether it is a header line.
+fn parse_header(head: &str, line: &str, header: &mut Header) -> Result<bool, &'static str> {
+    let head_numbers = numbers(head);
+    let mut is_header = false;
+
+    if let Some(rep_scheme) = parse_rep_scheme(head) {
+        header.rep_scheme = Some(rep_scheme);
+        if header.metcon_type.is_none() {
+            header.metcon_type = Some(MetconType::ForTime);
+        }
+        is_header = true;
+    }
+    if head.contains("amrap") || head.contains("as many rounds") {
+        header.metcon_type = Some(MetconType::Amrap);
+        header.timecap = head_numbers
+            .first()
+            .map(|minutes| scale(*minutes, MINUTE))
+            .transpose()?;
+        is_header = true;
+    } else if head.contains("emom") || head.contains("every minute") {
+        header.metcon_type = Some(MetconType::Emom);
+        header.rounds = head_numbers
+            .first()
+            .map(|rounds| scale(*rounds, 1))
+            .transpose()?;
+        header.timecap = head_numbers
+            .first()
+            .map(|minutes| scale(*minutes, MINUTE))
+            .transpose()?;
+        is_header = true;
+    } else if head.contains("tabata") {
+        header.metcon_type = Some(MetconType::Tabata);
+        header.rounds = Some(8);
+        header.work_time = Some(20_000);
+        header.rest_time = Some(10_000);
+        is_header = true;
+    } else if head.contains("for time") || head.split_whitespace().any(|word| word == "rft") {
+        header.metcon_type = Some(MetconType::ForTime);
+        if head.contains("round") || head.contains("rft") {
+            header.rounds = head_numbers
+                .first()
+                .map(|rounds| scale(*rounds, 1))
+                .transpose()?;
+        }
+        is_header = true;
+    } else if head.contains("round") && head_numbers.len() == 1 && !is_header {
+        header.rounds = Some(scale(head_numbers[0], 1)?);
+        is_header = true;
+    }
+    let words: Vec<_> = line.split_whitespace().collect();
+    if let Some(index) = words
+        .iter()
+        .position(|word| matches!(word.trim_end_matches(':'), "cap" | "timecap" | "tc"))
+    {
+        if let Some(minutes) = numbers(&words[index + 1..].join(" ")).first() {
+            header.timecap = Some(scale(*minutes, MINUTE)?);
+            is_header = true;
+        }
+    }
+
+    Ok(is_header)
+}
+
+/// Parse rep schemes like `21-15-9` at the start of the line.
+fn parse_rep_scheme(head: &str) -> Option<Vec<i32>> {
+    let scheme = head.split_whitespace().next()?;
+    if !scheme.contains('-') {
+        return None;
+    }
+    let reps: Option<Vec<i32>> = scheme
+        .split('-')
+        .map(|reps| reps.parse().ok().filter(|reps| *reps > 0))
+        .collect();
+    reps.filter(|reps| reps.len() > 1)
+}
+
+/// Multiply a number of the description with the `factor` of its unit.
+///
+/// Returns an error if the number is zero or the result does not fit into an `i32`.
+fn scale(number: i32, factor: i32) -> Result<i32, &'static str> {
+    if number <= 0 {
+        return Err("numbers in the description must be positive");
+    }
+    number
+        .checked_mul(factor)
+        .ok_or("a number in the description is too large")
+}
+
+/// Numbers that do not fit into an `i32` are skipped.
+fn numbers(text: &str) -> Vec<i32> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok())
+        .collect()
+}
+
+fn parse_unit(unit: &str) -> Option<(Option<DistanceUnit>, MovementDimension, i32)> {
+    match unit {
+        "m" | "meter" | "meters" | "metre" | "metres" => {
+            Some((Some(DistanceUnit::Meter), MovementDimension::Distance, 1))
+        }
+        "km" | "kilometer" | "kilometers" => {
+            Some((Some(DistanceUnit::Km), MovementDimension::Distance, 1))
+        }
+        "mi" | "mile" | "miles" => Some((Some(DistanceUnit::Mile), MovementDimension::Distance, 1)),
+        "yd" | "yard" | "yards" => Some((Some(DistanceUnit::Yard), MovementDimension::Distance, 1)),
+        "ft" | "foot" | "feet" => Some((Some(DistanceUnit::Foot), MovementDimension::Distance, 1)),
+        "cal" | "cals" | "calorie" | "calories" => Some((None, MovementDimension::Energy, 1)),
+        "s" | "sec" | "secs" | "second" | "seconds" => Some((None, MovementDimension::Time, 1_000)),
+        "min" | "mins" | "minute" | "minutes" => Some((None, MovementDimension::Time, MINUTE)),
+        "x" | "rep" | "reps" => Some((None, MovementDimension::Reps, 1)),
+        _ => None,
+    }
+}
+
+fn parse_weight(text: &str) -> Option<f32> {
+    let number: String = text
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.parse().ok().filter(|weight| *weight > 0.)
+}
+
+fn pounds_to_kg(pounds: f32) -> f32 {
+    (pounds * 0.453_592 * 2.).round() / 2.
+}
+
+/// Parse a movement line like `15 Thrusters (43/29 kg)`, `400 m Run` or `Deadlift @ 100kg`.
+///
+/// Returns `Ok(None)` if the line does not contain a movement name and an error if the count is zero or too large.
+fn parse_movement(text: &str) -> Result<Option<ParsedMovement>, &'static str> {
+    let text = text.replace(['(', ')', ','], " ");
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+
+    let mut count = None;
+    let mut distance_unit = None;
+    let mut dimension = MovementDimension::Reps;
+    if let Some(first) = words.first().copied() {
+        let digits: String = first.chars().take_while(char::is_ascii_digit).collect();
+        if !digits.is_empty() {
+            let number = digits
+                .parse::<i32>()
+                .map_err(|_| "a number in the description is too large")?;
+            let suffix = &first[digits.len()..];
+            let unit = if suffix.is_empty() {
+                let unit = words.get(1).and_then(|word| parse_unit(word));
+                if unit.is_some() {
+                    words.remove(1);
+                }
+                unit
+            } else {
+                parse_unit(suffix)
+            };
+            words.remove(0);
+            let (unit, unit_dimension, factor) = unit.unwrap_or((None, MovementDimension::Reps, 1));
+            count = Some(scale(number, factor)?);
+            distance_unit = unit;
+            dimension = unit_dimension;
+        }
+    }
+
+    let mut male_weight = None;
+    let mut female_weight = None;
+    let mut pounds = false;
+    let mut name = vec![];
+    for word in words {
+        let word = word.trim_start_matches('@');
+        if word.is_empty() || word == "rx" || word == "#" {
+            continue;
+        }
+        let unit_start = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '#');
+        match &word[unit_start.len()..] {
+            "lb" | "lbs" | "#" => pounds = true,
+            "kg" | "kgs" | "" => {}
+            _ => {
+                name.push(word);
+                continue;
+            }
+        }
+        if unit_start.is_empty() {
+            continue;
+        }
+        if let Some((male, female)) = unit_start.split_once('/') {
+            male_weight = parse_weight(male);
+            female_weight = parse_weight(female);
+        } else if let Some(weight) = parse_weight(unit_start) {
+            male_weight = Some(weight);
+            female_weight = Some(weight);
+        } else {
+            name.push(word);
+        }
+    }
+    if pounds {
+        male_weight = male_weight.map(pounds_to_kg);
+        female_weight = female_weight.map(pounds_to_kg);
+    }
+
+    let name = name.join(" ");
+    if name.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(ParsedMovement {
+        name,
+        count,
+        distance_unit,
+        dimension,
+        male_weight,
+        female_weight,
+    }))
+}
+
+fn normalize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+    name.strip_suffix('s')
+        .map(ToOwned::to_owned)
+        .unwrap_or(name)
+}
+
+/// Find the movement whose name matches the parsed name best.
+///
+/// Exact matches are preferred over movements whose name is contained in the parsed name, which are preferred over movements whose name contains the parsed name.
+/// Ties are broken by the [`MovementDimension`] implied by the unit of the count.
+fn resolve_movement(parsed: &ParsedMovement, movements: &[Movement]) -> Option<MovementId> {
+    let target = normalize(&parsed.name);
+    if target.len() < 3 {
+        return None;
+    }
+    movements
+        .iter()
+        .filter(|movement| !movement.deleted)
+        .filter_map(|movement| {
+            let name = normalize(&movement.name);
+            let score = if name == target {
+                (3, 0)
+            } else if target.contains(&name) {
+                (2, name.len() as i64)
+            } else if name.contains(&target) {
+                (1, -(name.len() as i64))
+            } else {
+                return None;
+            };
+            let dimension_matches = movement.movement_dimension == parsed.dimension;
+            Some(((score.0, dimension_matches, score.1), movement.id))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, movement_id)| movement_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movement(id: i64, name: &str, movement_dimension: MovementDimension) -> Movement {
+        Movement {
+            id: MovementId(id),
+            user_id: None,
+            name: name.to_owned(),
+            description: None,
+            movement_dimension,
+            cardio: false,
+            deleted: false,
+        }
+    }
+
+    fn movements() -> Vec<Movement> {
+        vec![
+            movement(1, "Thruster", MovementDimension::Reps),
+            movement(2, "Pull-Up", MovementDimension::Reps),
+            movement(3, "Running", MovementDimension::Distance),
+            movement(4, "Row Erg", MovementDimension::Distance),
+            movement(5, "Row Erg", MovementDimension::Energy),
+            movement(6, "Deadlift", MovementDimension::Reps),
+            movement(7, "Burpee", MovementDimension::Reps),
+        ]
+    }
+
+    fn parse(description: &str) -> Result<MetconDraft, &'static str> {
+        let mut id = 0;
+        parse_wod(
+            &WodText {
+                name: "Wod".to_owned(),
+                description: description.to_owned(),
+            },
+            UserId(1),
+            &movements(),
+            || {
+                id += 1;
+                id
+            },
+        )
+    }
+
+    #[test]
+    fn rep_scheme_for_time() {
+        let draft = parse("For Time\n21-15-9\nThrusters (43/29 kg)\nPull-ups").unwrap();
+
+        assert_eq!(draft.metcon.metcon_type, MetconType::ForTime);
+        assert_eq!(draft.metcon.rounds, Some(3));
+        assert_eq!(draft.metcon.rep_scheme, Some(vec![21, 15, 9]));
+        assert_eq!(draft.metcon_movements.len(), 2);
+        assert_eq!(draft.metcon_movements[0].movement_id, MovementId(1));
+        assert_eq!(draft.metcon_movements[0].male_weight, Some(43.));
+        assert_eq!(draft.metcon_movements[0].female_weight, Some(29.));
+        assert_eq!(draft.metcon_movements[1].movement_id, MovementId(2));
+        assert!(draft.unresolved.is_empty());
+    }
+
+    #[test]
+    fn amrap_with_buy_in() {
+        let draft = parse(
+            "Buy-In: 1000m Row\nAMRAP 20\n5 Pull-ups\n10 Deadlifts @ 225/155 lb\n30 Cal Row\nCash-Out: 50 Burpees",
+        )
+        .unwrap();
+
+        assert_eq!(draft.metcon.metcon_type, MetconType::Amrap);
+        assert_eq!(draft.metcon.timecap, Some(20 * MINUTE));
+        assert_eq!(draft.metcon.rounds, None);
+
+        let movements: Vec<_> = draft
+            .metcon_movements
+            .iter()
+            .map(|movement| {
+                (
+                    movement.movement_id.0,
+                    movement.metcon_part,
+                    movement.count,
+                    movement.distance_unit,
+                )
+            })
+            .collect();
+        assert_eq!(
+            movements,
+            [
+                (4, MetconPart::BuyIn, 1000, Some(DistanceUnit::Meter)),
+                (2, MetconPart::Main, 5, None),
+                (6, MetconPart::Main, 10, None),
+                (5, MetconPart::Main, 30, None),
+                (7, MetconPart::CashOut, 50, None),
+            ]
+        );
+        assert_eq!(draft.metcon_movements[2].male_weight, Some(102.));
+        assert_eq!(draft.metcon_movements[2].female_weight, Some(70.5));
+    }
+
+    #[test]
+    fn emom_and_unresolved() {
+        let draft = parse("EMOM 12\n3 Deadlifts\n5 Handstand Push-ups").unwrap();
+
+        assert_eq!(draft.metcon.metcon_type, MetconType::Emom);
+        assert_eq!(draft.metcon.rounds, Some(12));
+        assert_eq!(draft.metcon.timecap, Some(12 * MINUTE));
+        assert_eq!(draft.metcon_movements.len(), 1);
+        assert_eq!(draft.unresolved, ["5 handstand push-ups"]);
+    }
+
+    #[test]
+    fn rounds_for_time_with_timecap() {
+        let draft = parse("5 Rounds for Time\n400m Run\n15 Burpees\nTime Cap: 25").unwrap();
+
+        assert_eq!(draft.metcon.metcon_type, MetconType::ForTime);
+        assert_eq!(draft.metcon.rounds, Some(5));
+        assert_eq!(draft.metcon.timecap, Some(25 * MINUTE));
+        assert_eq!(draft.metcon_movements[0].movement_id, MovementId(3));
+        assert_eq!(draft.metcon_movements[0].count, 400);
+    }
+
+    #[test]
+    fn no_metcon_type() {
+        assert!(parse("Back Squat 5x5").is_err());
+    }
+
+    #[test]
+    fn too_large_numbers() {
+        for description in [
+            "AMRAP 40000\n5 Pull-ups",
+            "EMOM 40000\n3 Deadlifts",
+            "For Time\n100 Burpees\nTime Cap: 40000",
+            "For Time\n40000 min Row Erg",
+            "For Time\n99999999999 Burpees",
+        ] {
+            assert_eq!(
+                parse(description).unwrap_err(),
+                "a number in the description is too large",
+                "{description}"
+            );
+        }
+    }
+
+    #[test]
+    fn zero_numbers() {
+        for description in [
+            "AMRAP 0\n5 Pull-ups",
+            "EMOM 0\n3 Deadlifts",
+            "0 Rounds for Time\n15 Burpees",
+            "For Time\n0 Burpees",
+            "AMRAP 10: 0 Pull-ups",
+        ] {
+            assert_eq!(
+                parse(description).unwrap_err(),
+                "numbers in the description must be positive",
+                "{description}"
+            );
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! In-memory fake of wodify for tests of the wodify action providers.
+//!
+//! Tests seed users, classes, wods and results in the [`FakeWodify`],
+//! pass [`FakeWodify::client`] to the action provider and assert on the [`FakeState`] afterwards.
+//!
+//! Operations that wodify would not offer, like reserving a spot in a full class or using the client before the login, panic.
+//!
+//! Only available with the `fake` feature.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{Class, MetconResult, ReservationState, Result, WodDescription, WodifyClient};
+
+/// Everything that is known to the [`FakeWodify`].
+#[derive(Debug, Default)]
+pub struct FakeState {
+    /// Pairs of username and password.
+    pub users: Vec<(String, String)>,
+    pub classes: Vec<Class>,
+    /// Pairs of username and class id.
+    pub reservations: Vec<(String, String)>,
+    /// Pairs of username and class id.
+    pub waitlists: Vec<(String, String)>,
+    /// The number of spots by class id.
+    ///
+    /// Classes without capacity have unlimited spots.
+    pub capacities: Vec<(String, usize)>,
+    pub wods: Vec<(NaiveDate, WodDescription)>,
+    /// The results by username and date.
+    pub metcon_results: Vec<(String, NaiveDate, MetconResult)>,
+}
+
+impl FakeState {
+    fn reservation_state(&self, username: &str, class_id: &str) -> ReservationState {
+        let entry = |(user, id): &(String, String)| user == username && id == class_id;
+        if self.reservations.iter().any(entry) {
+            return ReservationState::Reserved;
+        }
+        if self.waitlists.iter().any(entry) {
+            return ReservationState::Waitlisted;
+        }
+
+        let reserved = self
+            .reservations
+            .iter()
+            .filter(|(_, id)| id == class_id)
+            .count();
+        let full = self
+            .capacities
+            .iter()
+            .any(|(id, capacity)| id == class_id && reserved >= *capacity);
+        if full {
+            ReservationState::Full
+        } else {
+            ReservationState::Available
+        }
+    }
+}
+
+/// A fake wodify shared by all clients created with [`FakeWodify::client`].
+#[derive(Debug, Default, Clone)]
+pub struct FakeWodify {
+    state: Arc<Mutex<FakeState>>,
+}
+
+impl FakeWodify {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn add_user(&self, username: &str, password: &str) {
+        self.state()
+            .users
+            .push((username.to_owned(), password.to_owned()));
+    }
+
+    /// Create a new client that is not logged in.
+    pub fn client(&self) -> FakeClient {
+        FakeClient {
+            state: self.state.clone(),
+            username: Mutex::default(),
+        }
+    }
+}
+
+/// [`WodifyClient`] of a [`FakeWodify`].
+#[derive(Debug)]
+pub struct FakeClient {
+    state: Arc<Mutex<FakeState>>,
+    /// The logged in user.
+    username: Mutex<Option<String>>,
+}
+
+impl FakeClient {
+    /// Get the state and the logged in user.
+    fn session(&self) -> (MutexGuard<'_, FakeState>, String) {
+        let username = self
+            .username
+            .lock()
+            .unwrap()
+            .clone()
+            .expect("the client is not logged in");
+        (self.state.lock().unwrap(), username)
+    }
+
+    /// Get the state and the logged in user and check that the class exists.
+    fn class_session(&self, class: &Class) -> (MutexGuard<'_, FakeState>, String) {
+        let (state, username) = self.session();
+        assert!(
+            state.classes.iter().any(|c| c.id == class.id),
+            "class {} does not exist",
+            class.id
+        );
+        (state, username)
+    }
+}
+
+impl WodifyClient for FakeClient {
+    async fn login(&self, username: &str, password: &str) -> Result<bool> {
+        let valid = self
+            .state
+            .lock()
+            .unwrap()
+            .users
+            .iter()
+            .any(|(user, pw)| user == username && pw == password);
+        if valid {
+            *self.username.lock().unwrap() = Some(username.to_owned());
+        }
+
+        Ok(valid)
+    }
+
+    async fn find_class(&self, name: &str, start: NaiveDateTime) -> Result<Option<Class>> {
+        let (state, _) = self.session();
+        Ok(state
+            .classes
+            .iter()
+            .find(|class| class.name.contains(name) && class.start == start)
+            .cloned())
+    }
+
+    async fn reserve(&self, class: &Class) -> Result<()> {
+        let (mut state, username) = self.class_session(class);
+        assert_eq!(
+            state.reservation_state(&username, &class.id),
+            ReservationState::Available
+        );
+        state.reservations.push((username, class.id.clone()));
+
+        Ok(())
+    }
+
+    async fn join_waitlist(&self, class: &Class) -> Result<()> {
+        let (mut state, username) = self.class_session(class);
+        assert_eq!(
+            state.reservation_state(&username, &class.id),
+            ReservationState::Full
+        );
+        state.waitlists.push((username, class.id.clone()));
+
+        Ok(())
+    }
+
+    async fn cancel(&self, class: &Class) -> Result<()> {
+        let (mut state, username) = self.class_session(class);
+        let entry = (username, class.id.clone());
+        let count = state.reservations.len() + state.waitlists.len();
+        state
+            .reservations
+            .retain(|reservation| *reservation != entry);
+        state.waitlists.retain(|waitlist| *waitlist != entry);
+        assert_ne!(
+            state.reservations.len() + state.waitlists.len(),
+            count,
+            "class {} is neither reserved nor waitlisted",
+            class.id
+        );
+
+        Ok(())
+    }
+
+    async fn reservation_state(&self, class: &Class) -> Result<ReservationState> {
+        let (state, username) = self.class_session(class);
+        Ok(state.reservation_state(&username, &class.id))
+    }
+
+    async fn wod(&self, date: NaiveDate) -> Result<Option<WodDescription>> {
+        let (state, _) = self.session();
+        Ok(state
+            .wods
+            .iter()
+            .find(|(wod_date, _)| *wod_date == date)
+            .map(|(_, wod)| wod.clone()))
+    }
+
+    async fn metcon_result(&self, date: NaiveDate) -> Result<Option<MetconResult>> {
+        let (state, username) = self.session();
+        Ok(state
+            .metcon_results
+            .iter()
+            .find(|(user, result_date, _)| *user == username && *result_date == date)
+            .map(|(_, _, metcon_result)| metcon_result.clone()))
+    }
+}
@@ -0,0 +1,270 @@
+//! Client for wodify used by the wodify action providers.
+//!
+//! [`WebDriverClient`] remote controls firefox through geckodriver and scrapes the wodify website.
+//! The action providers only depend on [`WodifyClient`], so they can be tested against the in-memory `fake::FakeWodify`.
+
+use std::{
+    fmt::{self, Display},
+    future::Future,
+    io::Error as IoError,
+    result::Result as StdResult,
+};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use thirtyfour::error::WebDriverError;
+use thiserror::Error;
+
+#[cfg(feature = "fake")]
+pub mod fake;
+mod webdriver;
+
+pub use crate::webdriver::{Geckodriver, WebDriverClient};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Io(#[from] IoError),
+    #[error("{0}")]
+    WebDriver(Box<WebDriverError>),
+    #[error("class {0} disappeared from the schedule")]
+    ClassNotFound(String),
+}
+
+impl From<WebDriverError> for Error {
+    fn from(error: WebDriverError) -> Self {
+        Self::WebDriver(Box::new(error))
+    }
+}
+
+pub type Result<T> = StdResult<T, Error>;
+
+/// A class in the schedule of the gym.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class {
+    /// The row of the class in the schedule.
+    pub id: String,
+    pub name: String,
+    /// The start of the class in the local time of the gym.
+    pub start: NaiveDateTime,
+}
+
+/// The state of the reservation of the logged in user for a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReservationState {
+    /// Not reserved and there are spots left.
+    Available,
+    /// Not reserved and there are no spots left.
+    Full,
+    Reserved,
+    Waitlisted,
+}
+
+impl Display for ReservationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Available => "available",
+            Self::Full => "full",
+            Self::Reserved => "reserved",
+            Self::Waitlisted => "waitlisted",
+        })
+    }
+}
+
+/// The wod of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WodDescription {
+    pub name: String,
+    pub description: String,
+}
+
+/// The result of the athlete for the metcon of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetconResult {
+    /// The result as displayed by wodify, e.g. `12:34` or `5 + 12`.
+    pub result: String,
+    pub rx: bool,
+    pub comments: String,
+}
+
+impl MetconResult {
+    /// Parse the score from the result.
+    ///
+    /// Times like `12:34` or `1:02:03`, rounds and reps like `5 + 12`, `5 rds + 12 reps` or `5 rounds`
+    /// and plain reps like `150` or `150 reps` are recognized.
+    /// Returns `None` for all other results.
+    pub fn score(&self) -> Option<Score> {
+        let result = self.result.trim().to_lowercase();
+
+        if result.contains(':') {
+            let parts = result
+                .split(':')
+                .map(|part| part.trim().parse::<i32>().ok().filter(|n| *n >= 0))
+                .collect::<Option<Vec<_>>>()?;
+            let (hours, minutes, seconds) = match parts[..] {
+                [minutes, seconds] => (0, minutes, seconds),
+                [hours, minutes, seconds] if minutes < 60 => (hours, minutes, seconds),
+                _ => return None,
+            };
+            if seconds >= 60 {
+                return None;
+            }
+            let time = hours
+                .checked_mul(60)?
+                .checked_add(minutes)?
+                .checked_mul(60)?
+                .checked_add(seconds)?
+                .checked_mul(1000)?;
+            return (time > 0).then_some(Score {
+                time: Some(time),
+                ..Score::default()
+            });
+        }
+
+        if let Some((rounds, reps)) = result.split_once('+') {
+            return Some(Score {
+                rounds: Some(number(rounds, ROUNDS)?),
+                reps: Some(number(reps, REPS)?),
+                ..Score::default()
+            });
+        }
+
+        if let Some(rounds) = ROUNDS
+            .iter()
+            .find_map(|unit| result.strip_suffix(unit))
+            .and_then(|rounds| number(rounds, &[]))
+        {
+            return Some(Score {
+                rounds: Some(rounds),
+                reps: Some(0),
+                ..Score::default()
+            });
+        }
+
+        Some(Score {
+            reps: Some(number(&result, REPS)?),
+            ..Score::default()
+        })
+    }
+}
+
+const ROUNDS: &[&str] = &["rounds", "round", "rds", "rd"];
+const REPS: &[&str] = &["reps", "rep"];
+
+/// Parse a non-negative number optionally followed by one of the `units`.
+fn number(value: &str, units: &[&str]) -> Option<i32> {
+    let value = value.trim();
+    let value = units
+        .iter()
+        .find_map(|unit| value.strip_suffix(unit))
+        .unwrap_or(value);
+    value.trim().parse().ok().filter(|number| *number >= 0)
+}
+
+/// The score of a [`MetconResult`] in the format of a metcon session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    /// The time in milliseconds.
+    pub time: Option<i32>,
+    pub rounds: Option<i32>,
+    pub reps: Option<i32>,
+}
+
+/// A logged in session of a wodify user.
+///
+/// A client holds the session of a single user, so every action event needs its own client.
+pub trait WodifyClient: Send + Sync {
+    /// Log in with the credentials of the user.
+    ///
+    /// Returns `false` if the credentials are wrong.
+    fn login(&self, username: &str, password: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Find the class whose name contains `name` and which starts at `start`.
+    fn find_class(
+        &self,
+        name: &str,
+        start: NaiveDateTime,
+    ) -> impl Future<Output = Result<Option<Class>>> + Send;
+
+    /// Reserve a spot in the class.
+    fn reserve(&self, class: &Class) -> impl Future<Output = Result<()>> + Send;
+
+    /// Join the waitlist of a full class.
+    fn join_waitlist(&self, class: &Class) -> impl Future<Output = Result<()>> + Send;
+
+    /// Cancel the reservation or leave the waitlist of the class.
+    fn cancel(&self, class: &Class) -> impl Future<Output = Result<()>> + Send;
+
+    fn reservation_state(
+        &self,
+        class: &Class,
+    ) -> impl Future<Output = Result<ReservationState>> + Send;
+
+    fn wod(&self, date: NaiveDate) -> impl Future<Output = Result<Option<WodDescription>>> + Send;
+
+    fn metcon_result(
+        &self,
+        date: NaiveDate,
+    ) -> impl Future<Output = Result<Option<MetconResult>>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(result: &str) -> Option<Score> {
+        MetconResult {
+            result: result.to_owned(),
+            rx: true,
+            comments: String::new(),
+        }
+        .score()
+    }
+
+    #[test]
+    fn parse_score() {
+        let time = |time| {
+            Some(Score {
+                time: Some(time),
+                ..Score::default()
+            })
+        };
+        let rounds_reps = |rounds, reps| {
+            Some(Score {
+                rounds: Some(rounds),
+                reps: Some(reps),
+                ..Score::default()
+            })
+        };
+
+        assert_eq!(score("4:12"), time(252_000));
+        assert_eq!(score(" 12:05 "), time(725_000));
+        assert_eq!(score("1:02:03"), time(3_723_000));
+        assert_eq!(score("5 + 12"), rounds_reps(5, 12));
+        assert_eq!(score("5+12"), rounds_reps(5, 12));
+        assert_eq!(score("5 Rds + 12 Reps"), rounds_reps(5, 12));
+        assert_eq!(score("7 rounds"), rounds_reps(7, 0));
+        assert_eq!(
+            score("150 reps"),
+            Some(Score {
+                reps: Some(150),
+                ..Score::default()
+            })
+        );
+        assert_eq!(
+            score("150"),
+            Some(Score {
+                reps: Some(150),
+                ..Score::default()
+            })
+        );
+
+        assert_eq!(score("0:00"), None);
+        assert_eq!(score("4:72"), None);
+        assert_eq!(score("1:72:00"), None);
+        assert_eq!(score("600:00:00"), None);
+        assert_eq!(score("9999999:00:00"), None);
+        assert_eq!(score("-5 + 3"), None);
+        assert_eq!(score("100 kg"), None);
+        assert_eq!(score(""), None);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{process::Stdio, time::Duration as StdDuration};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+use sysinfo::System;
+use thirtyfour::{prelude::*, FirefoxCapabilities, WebDriver};
+use tokio::{
+    process::{Child, Command},
+    time,
+};
+use tracing::debug;
+
+use crate::{Class, Error, MetconResult, ReservationState, Result, WodDescription, WodifyClient};
+
+const GECKODRIVER: &str = "geckodriver";
+const WEBDRIVER_ADDRESS: &str = "http://localhost:4444/";
+
+const LOGIN_URL: &str = "https://app.wodify.com";
+const CALENDAR_URL: &str = "https://app.wodify.com/Schedule/CalendarListViewEntry.aspx";
+const WOD_URL: &str = "https://app.wodify.com/WOD/WODEntry.aspx";
+const METCON_RESULTS_URL: &str = "https://app.wodify.com/Performance/MyPerformance_Metcon.aspx";
+
+/// A running geckodriver process.
+pub struct Geckodriver {
+    child: Child,
+    caps: FirefoxCapabilities,
+}
+
+impl Geckodriver {
+    /// Kill all running geckodriver processes and start a new one.
+    pub async fn start(headless: bool) -> Result<Self> {
+        for p in System::new_all().processes_by_name(GECKODRIVER) {
+            p.kill();
+        }
+
+        let child = Command::new(GECKODRIVER)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        time::sleep(StdDuration::from_secs(1)).await; // make sure geckodriver is available
+
+        let mut caps = DesiredCapabilities::firefox();
+        if headless {
+            caps.set_headless()?;
+        }
+
+        Ok(Self { child, caps })
+    }
+
+    /// Open a new browser.
+    pub async fn client(&self) -> Result<WebDriverClient> {
+        Ok(WebDriverClient {
+            driver: WebDriver::new(WEBDRIVER_ADDRESS, self.caps.clone()).await?,
+        })
+    }
+
+    pub async fn kill(mut self) -> Result<()> {
+        debug!("terminating webdriver");
+        self.child.kill().await?;
+
+        Ok(())
+    }
+}
+
+/// [`WodifyClient`] remote controlling a browser.
+pub struct WebDriverClient {
+    driver: WebDriver,
+}
+
+impl WebDriverClient {
+    /// Close the browser.
+    pub async fn quit(self) -> Result<()> {
+        debug!("closing browser");
+        self.driver.quit().await?;
+
+        Ok(())
+    }
+
+    /// The rows of the classes on the day `date` in the schedule together with their row number.
+    async fn class_rows(&self, date: &str) -> Result<Vec<(usize, WebElement)>> {
+        let table = self
+            .driver
+            .find(By::ClassName("TableRecords"))
+            .await?
+            .find(By::Tag("tbody"))
+            .await?;
+
+        let rows = table.find_all(By::Tag("tr")).await?;
+
+        let mut start_row_number = rows.len();
+        for (i, row) in rows.iter().enumerate() {
+            if let Ok(day) = row
+                .find(By::XPath("./td[1]/span[contains(@class, \"h3\")]"))
+                .await
+            {
+                if day.inner_html().await?.contains(date) {
+                    start_row_number = i + 1;
+                    break;
+                }
+            }
+        }
+
+        let mut end_row_number = rows.len();
+        for (i, row) in rows[start_row_number..].iter().enumerate() {
+            if row
+                .find(By::XPath("./td[1]/span[contains(@class, \"h3\")]"))
+                .await
+                .is_ok()
+            {
+                end_row_number = start_row_number + i;
+                break;
+            }
+        }
+
+        Ok(rows
+            .into_iter()
+            .enumerate()
+            .take(end_row_number)
+            .skip(start_row_number)
+            .collect())
+    }
+
+    /// The icon in the row of the class that reserves, joins the waitlist or cancels depending on the reservation state.
+    ///
+    /// Its title describes what clicking it does, e.g. `Cancel Reservation`.
+    async fn reservation_icon(&self, class: &Class) -> Result<WebElement> {
+        let date = class.start.format("%m/%d/%Y").to_string();
+        let row_number: usize = class.id.parse().unwrap_or(usize::MAX);
+
+        let row = self
+            .class_rows(&date)
+            .await?
+            .into_iter()
+            .find(|(number, _)| *number == row_number)
+            .map(|(_, row)| row);
+        let Some(row) = row else {
+            return Err(Error::ClassNotFound(class.name.clone()));
+        };
+
+        Ok(row.find(By::XPath("./td[3]/div")).await?)
+    }
+
+    async fn click_reservation_icon(&self, class: &Class) -> Result<()> {
+        let icon = self.reservation_icon(class).await?;
+        icon.scroll_into_view().await?;
+        icon.click().await?;
+
+        Ok(())
+    }
+}
+
+impl WodifyClient for WebDriverClient {
+    async fn login(&self, username: &str, password: &str) -> Result<bool> {
+        self.driver.delete_all_cookies().await?;
+        self.driver.goto(LOGIN_URL).await?;
+
+        time::sleep(StdDuration::from_secs(3)).await;
+
+        self.driver
+            .find(By::Id("Input_UserName"))
+            .await?
+            .send_keys(username)
+            .await?;
+        self.driver
+            .find(By::Id("Input_Password"))
+            .await?
+            .send_keys(password)
+            .await?;
+        self.driver
+            .find(By::ClassName("signin-btn"))
+            .await?
+            .click()
+            .await?;
+
+        time::sleep(StdDuration::from_secs(5)).await;
+
+        if self.driver.find(By::LinkText("Logout")).await.is_err() {
+            return Ok(false);
+        }
+        debug!("login successful");
+
+        Ok(true)
+    }
+
+    async fn find_class(&self, name: &str, start: NaiveDateTime) -> Result<Option<Class>> {
+        let time = start.format("%-H:%M").to_string();
+        let date = start.format("%m/%d/%Y").to_string();
+
+        // (re)load the schedule
+        self.driver.goto(CALENDAR_URL).await?;
+
+        for (row_number, row) in self.class_rows(&date).await? {
+            let title = row
+                .find(By::XPath("./td[1]/div/span"))
+                .await?
+                .attr("title")
+                .await?;
+
+            if let Some(title) = title {
+                if title.contains(name) && title.contains(&time) {
+                    return Ok(Some(Class {
+                        id: row_number.to_string(),
+                        name: title,
+                        start,
+                    }));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn reserve(&self, class: &Class) -> Result<()> {
+        self.click_reservation_icon(class).await
+    }
+
+    async fn join_waitlist(&self, class: &Class) -> Result<()> {
+        self.click_reservation_icon(class).await
+    }
+
+    async fn cancel(&self, class: &Class) -> Result<()> {
+        self.click_reservation_icon(class).await
+    }
+
+    async fn reservation_state(&self, class: &Class) -> Result<ReservationState> {
+        let title = self
+            .reservation_icon(class)
+            .await?
+            .attr("title")
+            .await?
+            .unwrap_or_default()
+            .to_lowercase();
+
+        Ok(if title.contains("waitlist") && title.contains("leave") {
+            ReservationState::Waitlisted
+        } else if title.contains("cancel") {
+            ReservationState::Reserved
+        } else if title.contains("waitlist") {
+            ReservationState::Full
+        } else {
+            ReservationState::Available
+        })
+    }
+
+    async fn wod(&self, date: NaiveDate) -> Result<Option<WodDescription>> {
+        let date = date.format("%m/%d/%Y").to_string();
+
+        self.driver.goto(WOD_URL).await?;
+
+        let date_input = self.driver.find(By::Id("AthleteTheme_wtLayoutNormal_block_wtSubNavigation_W_Utils_UI_wt3_block_wtDateInputFrom")).await?;
+        date_input.clear().await?;
+        date_input.send_keys(&date).await?;
+
+        time::sleep(StdDuration::from_secs(3)).await;
+
+        let Ok(wod) = self
+            .driver
+            .find(By::Id(
+                "AthleteTheme_wtLayoutNormal_block_wtMainContent_WOD_UI_wt9_block_wtWODComponentsList",
+            ))
+            .await
+        else {
+            return Ok(None);
+        };
+
+        let name = wod
+            .find(By::ClassName("component_name"))
+            .await?
+            .inner_html()
+            .await?;
+
+        let description = wod
+            .find(By::ClassName("component_wrapper"))
+            .await?
+            .inner_html()
+            .await?;
+
+        Ok(Some(WodDescription {
+            name: parse_inner_html(&name),
+            description: parse_inner_html(&description),
+        }))
+    }
+
+    async fn metcon_result(&self, date: NaiveDate) -> Result<Option<MetconResult>> {
+        let date = date.format("%m/%d/%Y").to_string();
+
+        self.driver.goto(METCON_RESULTS_URL).await?;
+
+        let Ok(result_table) = self.driver.find(By::ClassName("TableRecords")).await else {
+            return Ok(None);
+        };
+        let result_entry = result_table
+            .find(By::Tag("tbody"))
+            .await?
+            .find(By::Tag("tr"))
+            .await?
+            .find_all(By::Tag("td"))
+            .await?;
+
+        if result_entry[0].inner_html().await? != date {
+            return Ok(None);
+        }
+
+        Ok(Some(MetconResult {
+            result: result_entry[6].inner_html().await?,
+            rx: result_entry[7]
+                .find(By::ClassName("RxOnNoClick"))
+                .await
+                .is_ok(),
+            comments: parse_inner_html(&result_entry[9].inner_html().await?),
+        }))
+    }
+}
+
+fn parse_inner_html(inner_html: &str) -> String {
+    //static re: LazyCell<Regex> = LazyCell::new(|| Regex::new(r"</*.+?>").unwrap()); // TODO needs LazyCell stabilization
+    let content = inner_html
+        .replace("<br>", "\n")
+        .replace("<p>", "\n")
+        .replace("&nbsp;", " ");
+    Regex::new(r"</*.+?>")
+        .unwrap()
+        .replace_all(&content, "")
+        .replace("\n\n", "\n")
+        .replace("\n ", "\n")
+        .trim()
+        .to_owned()
+}